@@ -0,0 +1,558 @@
+//! 配置包导入导出（新工位克隆）
+//!
+//! 配置包是一个 zip 文件：
+//!
+//! - `manifest.json`：`BundleManifest`，记录包格式版本、导出程序版本与包含的文件
+//! - `app_config.json` / `log_config.json` / `serial_config.json`：各配置文件内容
+//!
+//! 检测设置位于 `app_config.json` 的 `detection` 分区，随之一起导出。
+//! 导入时先按模式（合并 / 替换）生成目标配置，用各配置的 `validate()` 校验并计算
+//! 差异（`ImportPlan`），全部通过后才由调用方写盘，因此 dry-run 与实际导入结果一致。
+
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use super::{AppConfig, ConfigFile};
+
+/// 当前配置包格式版本
+pub const BUNDLE_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// 单个文件的大小上限，防止异常的包占满内存
+const MAX_ENTRY_SIZE: u64 = 4 * 1024 * 1024;
+
+/// 包内文件说明
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleEntry {
+    pub kind: ConfigFile,
+    pub file: String,
+    /// 配置文件自身的格式版本（无版本字段的配置为 None）
+    pub version: Option<u32>,
+}
+
+/// `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleManifest {
+    pub format_version: u32,
+    /// 导出时的程序版本
+    pub app_version: String,
+    pub created_at_ms: u64,
+    /// 导出工位，便于确认包的来源，如 "冰箱总装产线#1 / 工位：外观检测工位1"
+    #[serde(default)]
+    pub source: String,
+    pub files: Vec<BundleEntry>,
+}
+
+/// 导入模式
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ImportMode {
+    /// 以包内内容覆盖同名字段；设备按 device_id 合并，本机独有的设备保留
+    Merge,
+    /// 包内包含的配置文件整体替换本机配置
+    Replace,
+}
+
+/// 单项差异类型
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ChangeKind {
+    Added,
+    Removed,
+    Modified,
+}
+
+/// 单项差异；`path` 形如 `mes.url`、`serial.devices[scanner_1].baud_rate`
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChange {
+    pub path: String,
+    pub kind: ChangeKind,
+    pub old: Option<Value>,
+    pub new: Option<Value>,
+}
+
+/// `import_config_bundle` 的返回值
+#[derive(Debug, Clone, Serialize)]
+pub struct ImportReport {
+    pub manifest: BundleManifest,
+    pub mode: ImportMode,
+    /// 为 true 时仅预览差异，未写入任何配置
+    pub dry_run: bool,
+    pub changes: Vec<ConfigChange>,
+    pub warnings: Vec<String>,
+    /// 包内存在但当前构建不支持的文件
+    pub skipped: Vec<String>,
+}
+
+/// 导入计划：校验通过的目标配置与相对本机的差异
+#[derive(Debug)]
+pub struct ImportPlan {
+    /// 有变化的配置文件及其目标内容（已迁移到当前版本并通过校验）
+    pub targets: BTreeMap<ConfigFile, Value>,
+    pub changes: Vec<ConfigChange>,
+    pub warnings: Vec<String>,
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+// ─── 导出 ────────────────────────────────────────────────────────────────────
+
+/// 把各配置文件写入配置包，返回写入的 manifest
+pub fn export(
+    path: &Path,
+    files: &BTreeMap<ConfigFile, Value>,
+    source: String,
+) -> Result<BundleManifest, String> {
+    let manifest = BundleManifest {
+        format_version: BUNDLE_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at_ms: now_ms(),
+        source,
+        files: files
+            .iter()
+            .map(|(&kind, value)| BundleEntry {
+                kind,
+                file: kind.file_name().to_string(),
+                version: value.get("version").and_then(Value::as_u64).map(|v| v as u32),
+            })
+            .collect(),
+    };
+
+    let write = || -> zip::result::ZipResult<()> {
+        let mut zip = ZipWriter::new(File::create(path)?);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file(MANIFEST_NAME, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&manifest).map_err(std::io::Error::other)?)?;
+        for (kind, value) in files {
+            zip.start_file(kind.file_name(), options)?;
+            zip.write_all(&serde_json::to_vec_pretty(value).map_err(std::io::Error::other)?)?;
+        }
+        zip.finish()?;
+        Ok(())
+    };
+    write().map_err(|e| format!("写入配置包 {} 失败: {}", path.display(), e))?;
+    Ok(manifest)
+}
+
+// ─── 读取 ────────────────────────────────────────────────────────────────────
+
+/// 从配置包读取到的内容
+#[derive(Debug)]
+pub struct Bundle {
+    pub manifest: BundleManifest,
+    pub files: BTreeMap<ConfigFile, Value>,
+    /// 包内存在但当前构建不支持或无法识别的文件
+    pub skipped: Vec<String>,
+}
+
+fn read_entry<R: Read>(entry: R, name: &str) -> Result<Value, String> {
+    let mut text = String::new();
+    entry
+        .take(MAX_ENTRY_SIZE + 1)
+        .read_to_string(&mut text)
+        .map_err(|e| format!("读取 {} 失败: {}", name, e))?;
+    if text.len() as u64 > MAX_ENTRY_SIZE {
+        return Err(format!("{} 超过 {} 字节上限", name, MAX_ENTRY_SIZE));
+    }
+    serde_json::from_str(&text).map_err(|e| format!("{} 不是有效的 JSON: {}", name, e))
+}
+
+/// 读取并检查配置包：manifest 必须存在且版本不高于当前程序支持的版本
+pub fn read(path: &Path) -> Result<Bundle, String> {
+    let file = File::open(path).map_err(|e| format!("打开配置包 {} 失败: {}", path.display(), e))?;
+    let mut zip = ZipArchive::new(file).map_err(|e| format!("{} 不是有效的配置包: {}", path.display(), e))?;
+
+    let manifest: BundleManifest = {
+        let entry = zip
+            .by_name(MANIFEST_NAME)
+            .map_err(|_| format!("配置包缺少 {}", MANIFEST_NAME))?;
+        serde_json::from_value(read_entry(entry, MANIFEST_NAME)?)
+            .map_err(|e| format!("{} 格式错误: {}", MANIFEST_NAME, e))?
+    };
+    if manifest.format_version > BUNDLE_VERSION {
+        return Err(format!(
+            "配置包格式版本 {} 高于当前程序支持的版本 {}，请升级程序后再导入",
+            manifest.format_version, BUNDLE_VERSION
+        ));
+    }
+
+    let supported = ConfigFile::supported();
+    let mut files = BTreeMap::new();
+    let mut skipped = Vec::new();
+    for i in 0..zip.len() {
+        let entry = zip.by_index(i).map_err(|e| format!("读取配置包失败: {}", e))?;
+        let name = entry.name().to_string();
+        if name == MANIFEST_NAME {
+            continue;
+        }
+        match ConfigFile::from_file_name(&name).filter(|k| supported.contains(k)) {
+            Some(kind) => {
+                files.insert(kind, read_entry(entry, &name)?);
+            }
+            None => skipped.push(name),
+        }
+    }
+    for entry in &manifest.files {
+        if !files.contains_key(&entry.kind) && !skipped.contains(&entry.file) {
+            return Err(format!("manifest 列出的 {} 不在配置包中", entry.file));
+        }
+    }
+    Ok(Bundle {
+        manifest,
+        files,
+        skipped,
+    })
+}
+
+// ─── 合并与差异 ──────────────────────────────────────────────────────────────
+
+/// 对象数组中用作标识的键：元素都带有该字符串字段时按它合并与比较
+const ID_KEYS: [&str; 2] = ["device_id", "name"];
+
+fn id_key(items: &[Value]) -> Option<&'static str> {
+    ID_KEYS.into_iter().find(|key| {
+        !items.is_empty() && items.iter().all(|v| v.get(key).and_then(Value::as_str).is_some())
+    })
+}
+
+/// 两个数组共同的标识键；空数组视为与任意键兼容
+fn common_key(a: &[Value], b: &[Value]) -> Option<&'static str> {
+    match (id_key(a), id_key(b)) {
+        (Some(x), Some(y)) if x == y => Some(x),
+        (Some(x), None) if b.is_empty() => Some(x),
+        (None, Some(y)) if a.is_empty() => Some(y),
+        _ => None,
+    }
+}
+
+fn item_id<'a>(item: &'a Value, key: &str) -> &'a str {
+    item.get(key).and_then(Value::as_str).unwrap_or_default()
+}
+
+/// 合并：对象逐键递归；带标识的对象数组按标识整体覆盖同名元素、追加新元素；其余值直接覆盖
+fn merge(local: &mut Value, incoming: Value) {
+    match (local, incoming) {
+        (Value::Object(l), Value::Object(i)) => {
+            for (k, v) in i {
+                match l.get_mut(&k) {
+                    Some(slot) => merge(slot, v),
+                    None => {
+                        l.insert(k, v);
+                    }
+                }
+            }
+        }
+        (Value::Array(l), Value::Array(i)) if common_key(l, &i).is_some() => {
+            let key = common_key(l, &i).unwrap_or_default();
+            for item in i {
+                match l.iter_mut().find(|x| item_id(x, key) == item_id(&item, key)) {
+                    Some(slot) => *slot = item,
+                    None => l.push(item),
+                }
+            }
+        }
+        (slot, v) => *slot = v,
+    }
+}
+
+fn join(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// 计算叶子级差异；带标识的对象数组按标识对齐，其余数组整体比较
+fn diff(path: &str, old: &Value, new: &Value, out: &mut Vec<ConfigChange>) {
+    match (old, new) {
+        (Value::Object(o), Value::Object(n)) => {
+            for (k, ov) in o {
+                match n.get(k) {
+                    Some(nv) => diff(&join(path, k), ov, nv, out),
+                    None => out.push(change(join(path, k), Some(ov), None)),
+                }
+            }
+            for (k, nv) in n {
+                if !o.contains_key(k) {
+                    out.push(change(join(path, k), None, Some(nv)));
+                }
+            }
+        }
+        (Value::Array(o), Value::Array(n)) if o != n => match common_key(o, n) {
+            Some(key) => {
+                for ov in o {
+                    let p = format!("{}[{}]", path, item_id(ov, key));
+                    match n.iter().find(|nv| item_id(nv, key) == item_id(ov, key)) {
+                        Some(nv) => diff(&p, ov, nv, out),
+                        None => out.push(change(p, Some(ov), None)),
+                    }
+                }
+                for nv in n {
+                    if !o.iter().any(|ov| item_id(ov, key) == item_id(nv, key)) {
+                        out.push(change(format!("{}[{}]", path, item_id(nv, key)), None, Some(nv)));
+                    }
+                }
+            }
+            _ => out.push(change(path.to_string(), Some(old), Some(new))),
+        },
+        _ if old != new => out.push(change(path.to_string(), Some(old), Some(new))),
+        _ => {}
+    }
+}
+
+fn change(path: String, old: Option<&Value>, new: Option<&Value>) -> ConfigChange {
+    let kind = match (old, new) {
+        (None, _) => ChangeKind::Added,
+        (_, None) => ChangeKind::Removed,
+        _ => ChangeKind::Modified,
+    };
+    ConfigChange {
+        path,
+        kind,
+        old: old.cloned(),
+        new: new.cloned(),
+    }
+}
+
+// ─── 校验 ────────────────────────────────────────────────────────────────────
+
+/// 把包内配置升级到当前版本（仅串口配置有版本迁移）
+fn upgrade(kind: ConfigFile, value: Value, warnings: &mut Vec<String>) -> Result<Value, String> {
+    match kind {
+        #[cfg(feature = "serial")]
+        ConfigFile::Serial => {
+            use crate::serial::config::{migrate, CONFIG_VERSION};
+            let (value, from) = migrate(value)?;
+            if from < CONFIG_VERSION {
+                warnings.push(format!("包内串口配置已从版本 {} 迁移到 {}", from, CONFIG_VERSION));
+            } else if from > CONFIG_VERSION {
+                warnings.push(format!(
+                    "包内串口配置版本 {} 高于当前程序支持的版本 {}，未识别的字段将被忽略",
+                    from, CONFIG_VERSION
+                ));
+            }
+            Ok(value)
+        }
+        ConfigFile::App => {
+            let version = value.get("version").and_then(Value::as_u64).unwrap_or(1);
+            if version > super::CONFIG_VERSION as u64 {
+                warnings.push(format!(
+                    "包内应用配置版本 {} 高于当前程序支持的版本 {}，未识别的字段将被忽略",
+                    version,
+                    super::CONFIG_VERSION
+                ));
+            }
+            Ok(value)
+        }
+        _ => {
+            let _ = warnings;
+            Ok(value)
+        }
+    }
+}
+
+/// 反序列化为对应的配置类型并调用其 `validate()`，返回规范化（补全缺省字段）后的 JSON
+fn normalize(kind: ConfigFile, value: Value) -> Result<Value, String> {
+    fn check<T: Serialize + serde::de::DeserializeOwned>(
+        value: Value,
+        validate: impl Fn(&T) -> Result<(), String>,
+    ) -> Result<Value, String> {
+        let cfg: T = serde_json::from_value(value).map_err(|e| format!("格式错误: {}", e))?;
+        validate(&cfg)?;
+        serde_json::to_value(cfg).map_err(|e| e.to_string())
+    }
+    let result = match kind {
+        ConfigFile::App => check(value, AppConfig::validate),
+        #[cfg(feature = "logging")]
+        ConfigFile::Logging => check(value, crate::logging::config::LogConfig::validate),
+        #[cfg(feature = "serial")]
+        ConfigFile::Serial => check(value, crate::serial::config::SerialConfig::validate),
+        #[allow(unreachable_patterns)]
+        _ => Err("当前构建不支持该配置".to_string()),
+    };
+    result.map_err(|e| format!("{} 校验失败: {}", kind.file_name(), e))
+}
+
+/// 生成导入计划：升级 → 按模式合并 → 校验 → 与本机配置比较
+///
+/// 任一文件校验失败则整体返回错误，不产生部分导入。
+pub fn plan(
+    current: &BTreeMap<ConfigFile, Value>,
+    incoming: BTreeMap<ConfigFile, Value>,
+    mode: ImportMode,
+) -> Result<ImportPlan, String> {
+    let mut warnings = Vec::new();
+    let mut targets = BTreeMap::new();
+    let mut changes = Vec::new();
+    for (kind, value) in incoming {
+        let value = upgrade(kind, value, &mut warnings)?;
+        let local = current.get(&kind).cloned().unwrap_or(Value::Null);
+        let target = match mode {
+            ImportMode::Replace => value,
+            ImportMode::Merge => {
+                let mut merged = local.clone();
+                merge(&mut merged, value);
+                merged
+            }
+        };
+        let target = normalize(kind, target)?;
+        let before = changes.len();
+        diff(kind.prefix(), &local, &target, &mut changes);
+        if changes.len() > before {
+            targets.insert(kind, target);
+        }
+    }
+    Ok(ImportPlan {
+        targets,
+        changes,
+        warnings,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn paths(changes: &[ConfigChange]) -> Vec<(&str, ChangeKind)> {
+        changes.iter().map(|c| (c.path.as_str(), c.kind)).collect()
+    }
+
+    /// 本机应用配置：默认值上改了数据库地址
+    fn local_app() -> BTreeMap<ConfigFile, Value> {
+        let mut app = serde_json::to_value(AppConfig::default()).unwrap();
+        app["database"]["host"] = json!("10.0.0.1");
+        BTreeMap::from([(ConfigFile::App, app)])
+    }
+
+    #[test]
+    fn merge_overrides_fields_and_merges_items_by_id() {
+        let mut local = json!({
+            "mes": { "url": "http://a", "timeout_ms": 5000 },
+            "devices": [
+                { "device_id": "a", "baud_rate": 9600 },
+                { "device_id": "local_only", "baud_rate": 9600 },
+            ],
+            "tags": [1, 2],
+        });
+        merge(
+            &mut local,
+            json!({
+                "mes": { "url": "http://b" },
+                "devices": [
+                    { "device_id": "a", "baud_rate": 115200 },
+                    { "device_id": "new", "baud_rate": 9600 },
+                ],
+                "tags": [3],
+            }),
+        );
+        assert_eq!(
+            local,
+            json!({
+                "mes": { "url": "http://b", "timeout_ms": 5000 },
+                "devices": [
+                    { "device_id": "a", "baud_rate": 115200 },
+                    { "device_id": "local_only", "baud_rate": 9600 },
+                    { "device_id": "new", "baud_rate": 9600 },
+                ],
+                "tags": [3],
+            })
+        );
+    }
+
+    #[test]
+    fn diff_reports_leaf_changes_aligned_by_id() {
+        let old = json!({
+            "devices": [
+                { "device_id": "a", "baud_rate": 9600, "name": "A" },
+                { "device_id": "gone", "baud_rate": 9600 },
+            ],
+            "tags": [1, 2],
+            "same": true,
+        });
+        let new = json!({
+            "devices": [
+                { "device_id": "added", "baud_rate": 9600 },
+                { "device_id": "a", "baud_rate": 115200, "name": "A" },
+            ],
+            "tags": [1],
+            "same": true,
+            "extra": 1,
+        });
+        let mut changes = Vec::new();
+        diff("serial", &old, &new, &mut changes);
+        assert_eq!(
+            paths(&changes),
+            [
+                ("serial.devices[a].baud_rate", ChangeKind::Modified),
+                ("serial.devices[gone]", ChangeKind::Removed),
+                ("serial.devices[added]", ChangeKind::Added),
+                ("serial.tags", ChangeKind::Modified),
+                ("serial.extra", ChangeKind::Added),
+            ]
+        );
+        assert_eq!(changes[0].old, Some(json!(9600)));
+        assert_eq!(changes[0].new, Some(json!(115200)));
+    }
+
+    #[test]
+    fn plan_merge_keeps_local_fields() {
+        let incoming = BTreeMap::from([(ConfigFile::App, json!({ "mes": { "url": "http://m" } }))]);
+        let plan = plan(&local_app(), incoming, ImportMode::Merge).unwrap();
+        assert_eq!(paths(&plan.changes), [("mes.url", ChangeKind::Modified)]);
+        assert_eq!(plan.targets[&ConfigFile::App]["database"]["host"], "10.0.0.1");
+    }
+
+    #[test]
+    fn plan_replace_resets_fields_missing_from_bundle() {
+        let incoming = BTreeMap::from([(ConfigFile::App, json!({ "mes": { "url": "http://m" } }))]);
+        let plan = plan(&local_app(), incoming, ImportMode::Replace).unwrap();
+        let changed = paths(&plan.changes);
+        assert!(changed.contains(&("mes.url", ChangeKind::Modified)));
+        assert!(changed.contains(&("database.host", ChangeKind::Modified)));
+        let defaults = serde_json::to_value(AppConfig::default()).unwrap();
+        let target = &plan.targets[&ConfigFile::App];
+        assert_eq!(target["database"]["host"], defaults["database"]["host"]);
+    }
+
+    #[test]
+    fn plan_skips_unchanged_files_and_rejects_invalid_ones() {
+        let current = local_app();
+        let same = plan(&current, current.clone(), ImportMode::Replace).unwrap();
+        assert!(same.targets.is_empty() && same.changes.is_empty());
+
+        let invalid = BTreeMap::from([(ConfigFile::App, json!({ "mes": { "timeout_ms": 0 } }))]);
+        assert!(plan(&current, invalid, ImportMode::Merge).is_err());
+    }
+
+    #[test]
+    fn export_and_read_round_trip() {
+        let dir = std::env::temp_dir().join(format!("bundle-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("bundle.zip");
+        let files = local_app();
+
+        let manifest = export(&path, &files, "line / station".into()).unwrap();
+        let bundle = read(&path).unwrap();
+        assert_eq!(bundle.manifest.source, "line / station");
+        assert_eq!(bundle.manifest.files.len(), manifest.files.len());
+        assert_eq!(bundle.files, files);
+        assert!(bundle.skipped.is_empty());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
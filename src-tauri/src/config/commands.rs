@@ -0,0 +1,272 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use serde_json::{Map, Value};
+use tauri::{AppHandle, State};
+#[cfg(feature = "serial")]
+use tauri::Manager;
+
+use super::bundle::{self, BundleManifest, ImportMode, ImportReport};
+use super::{emit_changed, AppConfig, AppConfigState, ConfigFile, ConfigSection};
+
+/// 对应 feature 未启用时的错误
+#[cfg(not(all(feature = "logging", feature = "serial")))]
+fn feature_disabled(section: ConfigSection) -> String {
+    format!("{} 功能未启用", section.name())
+}
+
+/// 读取单个分区（`logging` 从配置文件读取，`serial` 取串口管理器当前生效的配置）
+fn read_section(
+    section: ConfigSection,
+    state: &AppConfigState,
+    #[cfg_attr(not(feature = "serial"), allow(unused_variables))] app: &AppHandle,
+) -> Result<Value, String> {
+    match section {
+        #[cfg(feature = "logging")]
+        ConfigSection::Logging => {
+            serde_json::to_value(crate::logging::config::LogConfig::load_or_default())
+                .map_err(|e| e.to_string())
+        }
+        #[cfg(feature = "serial")]
+        ConfigSection::Serial => {
+            let serial_state = app.state::<crate::serial::SerialState>();
+            serde_json::to_value(serial_state.manager.config()).map_err(|e| e.to_string())
+        }
+        #[cfg(not(feature = "logging"))]
+        ConfigSection::Logging => Err(feature_disabled(section)),
+        #[cfg(not(feature = "serial"))]
+        ConfigSection::Serial => Err(feature_disabled(section)),
+        _ => state.get(section),
+    }
+}
+
+/// 获取单个配置分区
+#[tauri::command]
+pub async fn get_config_section(
+    section: ConfigSection,
+    state: State<'_, AppConfigState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    log::debug!("get_config_section {}", section.name());
+    read_section(section, &state, &app)
+}
+
+/// 获取全部配置分区（键为分区名；未启用 feature 的分区省略）
+#[tauri::command]
+pub async fn get_app_config(
+    state: State<'_, AppConfigState>,
+    app: AppHandle,
+) -> Result<Map<String, Value>, String> {
+    let mut all = Map::new();
+    for section in ConfigSection::ALL {
+        match read_section(section, &state, &app) {
+            Ok(v) => {
+                all.insert(section.name().to_string(), v);
+            }
+            Err(e) => log::debug!("跳过 {} 配置: {}", section.name(), e),
+        }
+    }
+    Ok(all)
+}
+
+/// 校验并保存单个配置分区，成功后推送 `config:changed`，返回保存后的分区内容
+///
+/// `logging` / `serial` 分区分别转交 `update_log_config` / `update_serial_config`，
+/// 串口分区会按差异重启监听线程。
+#[tauri::command]
+pub async fn update_config_section(
+    section: ConfigSection,
+    value: Value,
+    state: State<'_, AppConfigState>,
+    app: AppHandle,
+) -> Result<Value, String> {
+    match section {
+        #[cfg(feature = "logging")]
+        ConfigSection::Logging => {
+            let config: crate::logging::config::LogConfig = serde_json::from_value(value)
+                .map_err(|e| format!("logging 配置格式错误: {}", e))?;
+            crate::logging::commands::update_log_config(config.clone(), app).await?;
+            serde_json::to_value(config).map_err(|e| e.to_string())
+        }
+        #[cfg(feature = "serial")]
+        ConfigSection::Serial => {
+            let config: crate::serial::config::SerialConfig = serde_json::from_value(value)
+                .map_err(|e| format!("serial 配置格式错误: {}", e))?;
+            let serial_state = app.state::<crate::serial::SerialState>();
+            crate::serial::commands::update_serial_config(config.clone(), serial_state, app.clone())
+                .await?;
+            serde_json::to_value(config).map_err(|e| e.to_string())
+        }
+        #[cfg(not(feature = "logging"))]
+        ConfigSection::Logging => Err(feature_disabled(section)),
+        #[cfg(not(feature = "serial"))]
+        ConfigSection::Serial => Err(feature_disabled(section)),
+        _ => {
+            let value = state.update(section, value)?;
+            log::info!("{} 配置已更新", section.name());
+            emit_changed(&app, section, &value);
+            Ok(value)
+        }
+    }
+}
+
+// ─── 配置包 ──────────────────────────────────────────────────────────────────
+
+/// 本机当前的各配置文件内容
+fn current_files(
+    state: &AppConfigState,
+    #[cfg_attr(not(feature = "serial"), allow(unused_variables))] app: &AppHandle,
+) -> Result<BTreeMap<ConfigFile, Value>, String> {
+    let mut files = BTreeMap::new();
+    for kind in ConfigFile::supported() {
+        let value = match kind {
+            ConfigFile::App => serde_json::to_value(state.snapshot()),
+            #[cfg(feature = "logging")]
+            ConfigFile::Logging => {
+                serde_json::to_value(crate::logging::config::LogConfig::load_or_default())
+            }
+            #[cfg(feature = "serial")]
+            ConfigFile::Serial => {
+                serde_json::to_value(app.state::<crate::serial::SerialState>().manager.config())
+            }
+            #[allow(unreachable_patterns)]
+            _ => continue,
+        };
+        files.insert(kind, value.map_err(|e| e.to_string())?);
+    }
+    Ok(files)
+}
+
+/// 导出本机全部配置为配置包（zip），用于克隆到新工位
+#[tauri::command]
+pub async fn export_config_bundle(
+    path: String,
+    state: State<'_, AppConfigState>,
+    app: AppHandle,
+) -> Result<BundleManifest, String> {
+    let files = current_files(&state, &app)?;
+    let line = state.snapshot().production_line;
+    let source = format!("{} / {}", line.production_line_name, line.workstation_name);
+    let manifest = bundle::export(&PathBuf::from(&path), &files, source)?;
+    log::info!("配置包已导出到 {}，包含 {} 个配置文件", path, manifest.files.len());
+    Ok(manifest)
+}
+
+/// 已反序列化、待写入的单个配置文件
+enum Staged {
+    App(Box<AppConfig>),
+    #[cfg(feature = "logging")]
+    Logging(crate::logging::config::LogConfig),
+    #[cfg(feature = "serial")]
+    Serial(crate::serial::config::SerialConfig),
+}
+
+impl Staged {
+    fn new(kind: ConfigFile, value: Value) -> Result<Self, String> {
+        let err = |e: serde_json::Error| format!("{} 格式错误: {}", kind.file_name(), e);
+        match kind {
+            ConfigFile::App => serde_json::from_value(value)
+                .map(|c| Staged::App(Box::new(c)))
+                .map_err(err),
+            #[cfg(feature = "logging")]
+            ConfigFile::Logging => {
+                serde_json::from_value(value).map(Staged::Logging).map_err(err)
+            }
+            #[cfg(feature = "serial")]
+            ConfigFile::Serial => serde_json::from_value(value).map(Staged::Serial).map_err(err),
+            #[allow(unreachable_patterns)]
+            _ => Err(format!("{} 功能未启用", kind.file_name())),
+        }
+    }
+
+    /// 写入并生效：推送 `config:changed`，日志配置立即应用，串口配置按差异重启监听线程
+    async fn apply(self, state: &AppConfigState, app: &AppHandle) -> Result<(), String> {
+        match self {
+            Staged::App(config) => {
+                let old = state.replace((*config).clone())?;
+                for section in ConfigSection::ALL {
+                    if let Some(value) = config.section(section) {
+                        if Some(&value) != old.section(section).as_ref() {
+                            emit_changed(app, section, &value);
+                        }
+                    }
+                }
+            }
+            #[cfg(feature = "logging")]
+            Staged::Logging(config) => {
+                crate::logging::commands::update_log_config(config, app.clone()).await?;
+            }
+            #[cfg(feature = "serial")]
+            Staged::Serial(config) => {
+                let serial_state = app.state::<crate::serial::SerialState>();
+                crate::serial::commands::update_serial_config(config, serial_state, app.clone())
+                    .await?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 导入配置包
+///
+/// 先按 `mode` 生成目标配置并逐一校验、反序列化，任一失败则不做任何修改。
+/// `dry_run` 为 true 时只返回差异；否则写入有变化的配置，推送 `config:changed`，
+/// 串口配置按差异重启监听线程。写入某个文件失败时，已写入的文件恢复为导入前的内容。
+#[tauri::command]
+pub async fn import_config_bundle(
+    path: String,
+    mode: ImportMode,
+    dry_run: Option<bool>,
+    state: State<'_, AppConfigState>,
+    app: AppHandle,
+) -> Result<ImportReport, String> {
+    let dry_run = dry_run.unwrap_or(false);
+    let bundle = bundle::read(&PathBuf::from(&path))?;
+    let current = current_files(&state, &app)?;
+    let plan = bundle::plan(&current, bundle.files, mode)?;
+    let report = ImportReport {
+        manifest: bundle.manifest,
+        mode,
+        dry_run,
+        changes: plan.changes,
+        warnings: plan.warnings,
+        skipped: bundle.skipped,
+    };
+    if dry_run {
+        return Ok(report);
+    }
+
+    // 先全部反序列化（包括回滚用的当前配置），再开始写入
+    let mut staged = Vec::new();
+    for (kind, value) in plan.targets {
+        let rollback = match current.get(&kind) {
+            Some(old) => Some(Staged::new(kind, old.clone())?),
+            None => None,
+        };
+        staged.push((kind, Staged::new(kind, value)?, rollback));
+    }
+
+    let mut applied: Vec<(ConfigFile, Staged)> = Vec::new();
+    for (kind, target, rollback) in staged {
+        if let Err(e) = target.apply(&state, &app).await {
+            for (done, old) in applied.into_iter().rev() {
+                if let Err(e) = old.apply(&state, &app).await {
+                    log::error!("回滚 {} 失败: {}", done.file_name(), e);
+                }
+            }
+            return Err(format!(
+                "导入 {} 失败，已恢复导入前的配置: {}",
+                kind.file_name(),
+                e
+            ));
+        }
+        applied.extend(rollback.map(|old| (kind, old)));
+    }
+    log::info!(
+        "已从 {} 导入配置包（{:?}），共 {} 处变更",
+        path,
+        mode,
+        report.changes.len()
+    );
+    Ok(report)
+}
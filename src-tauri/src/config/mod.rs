@@ -0,0 +1,330 @@
+//! 统一应用配置
+//!
+//! 所有设置按分区（`ConfigSection`）访问：
+//!
+//! - `general` / `productionLine` / `detection` / `storage` / `mes` / `database`
+//!   保存在 `app_config.json`（`AppConfig`），由 `AppConfigState` 统一管理
+//! - `logging` / `serial` 仍分别保存在 `log_config.json` / `serial_config.json`，
+//!   通过各自模块读写（修改串口分区会按差异重启监听线程）
+//!
+//! 任一分区更新成功后推送 `config:changed`，所有窗口据此刷新。
+//! 配置文件被外部修改时由 `watcher` 重新加载（见 `watcher::spawn_config_watcher`）。
+
+pub mod bundle;
+pub mod commands;
+pub mod sections;
+pub mod watcher;
+
+use std::io;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tauri::{AppHandle, Emitter};
+
+use crate::persist::{self, LoadOutcome};
+use sections::{
+    DatabaseConfig, DetectionConfig, GeneralConfig, MesConfig, ProductionLineConfig, StorageConfig,
+};
+
+/// 当前 `app_config.json` 格式版本
+pub const CONFIG_VERSION: u32 = 1;
+
+fn config_version() -> u32 {
+    CONFIG_VERSION
+}
+
+/// 配置分区
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigSection {
+    General,
+    ProductionLine,
+    Logging,
+    Serial,
+    Detection,
+    Storage,
+    Mes,
+    Database,
+}
+
+impl ConfigSection {
+    pub const ALL: [ConfigSection; 8] = [
+        ConfigSection::General,
+        ConfigSection::ProductionLine,
+        ConfigSection::Logging,
+        ConfigSection::Serial,
+        ConfigSection::Detection,
+        ConfigSection::Storage,
+        ConfigSection::Mes,
+        ConfigSection::Database,
+    ];
+
+    /// 与前端一致的分区名
+    pub fn name(self) -> &'static str {
+        match self {
+            ConfigSection::General => "general",
+            ConfigSection::ProductionLine => "productionLine",
+            ConfigSection::Logging => "logging",
+            ConfigSection::Serial => "serial",
+            ConfigSection::Detection => "detection",
+            ConfigSection::Storage => "storage",
+            ConfigSection::Mes => "mes",
+            ConfigSection::Database => "database",
+        }
+    }
+}
+
+/// 配置文件（配置包与文件监视按文件处理）
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigFile {
+    App,
+    Logging,
+    Serial,
+}
+
+impl ConfigFile {
+    pub fn path(self) -> PathBuf {
+        match self {
+            ConfigFile::App => AppConfig::config_path(),
+            #[cfg(feature = "logging")]
+            ConfigFile::Logging => crate::logging::config::LogConfig::config_path(),
+            #[cfg(feature = "serial")]
+            ConfigFile::Serial => crate::serial::config::SerialConfig::config_path(),
+            #[allow(unreachable_patterns)]
+            _ => AppConfig::config_path().with_file_name(self.file_name()),
+        }
+    }
+
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ConfigFile::App => "app_config.json",
+            ConfigFile::Logging => "log_config.json",
+            ConfigFile::Serial => "serial_config.json",
+        }
+    }
+
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        [ConfigFile::App, ConfigFile::Logging, ConfigFile::Serial]
+            .into_iter()
+            .find(|f| f.file_name() == name)
+    }
+
+    /// 当前构建支持的文件（对应 feature 未启用的配置不导出、不导入也不监视）
+    pub fn supported() -> Vec<ConfigFile> {
+        #[allow(unused_mut)]
+        let mut files = vec![ConfigFile::App];
+        #[cfg(feature = "logging")]
+        files.push(ConfigFile::Logging);
+        #[cfg(feature = "serial")]
+        files.push(ConfigFile::Serial);
+        files
+    }
+
+    /// 差异路径前缀；`app_config.json` 的顶层键即分区名，不加前缀
+    pub fn prefix(self) -> &'static str {
+        match self {
+            ConfigFile::App => "",
+            ConfigFile::Logging => "logging",
+            ConfigFile::Serial => "serial",
+        }
+    }
+}
+
+/// 保存在 `app_config.json` 中的分区
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct AppConfig {
+    #[serde(default = "config_version")]
+    pub version: u32,
+    pub general: GeneralConfig,
+    pub production_line: ProductionLineConfig,
+    pub detection: DetectionConfig,
+    pub storage: StorageConfig,
+    pub mes: MesConfig,
+    pub database: DatabaseConfig,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            general: GeneralConfig::default(),
+            production_line: ProductionLineConfig::default(),
+            detection: DetectionConfig::default(),
+            storage: StorageConfig::default(),
+            mes: MesConfig::default(),
+            database: DatabaseConfig::default(),
+        }
+    }
+}
+
+/// 反序列化并校验单个分区
+fn parse_section<T: serde::de::DeserializeOwned>(
+    section: ConfigSection,
+    value: Value,
+    validate: impl Fn(&T) -> Result<(), String>,
+) -> Result<T, String> {
+    let parsed: T = serde_json::from_value(value)
+        .map_err(|e| format!("{} 配置格式错误: {}", section.name(), e))?;
+    validate(&parsed).map_err(|e| format!("{} 配置无效: {}", section.name(), e))?;
+    Ok(parsed)
+}
+
+impl AppConfig {
+    /// 配置文件路径：`{config_dir}/app_config.json`（见 `paths`）
+    pub fn config_path() -> PathBuf {
+        crate::paths::config_file("app_config.json")
+    }
+
+    /// 从磁盘加载；缺失时返回默认配置，损坏时尝试从滚动备份恢复
+    pub fn load_or_default() -> Self {
+        let path = Self::config_path();
+        let parse = |s: &str| {
+            let cfg: AppConfig = serde_json::from_str(s).map_err(|e| e.to_string())?;
+            cfg.validate()?;
+            Ok(cfg)
+        };
+        match persist::load(&path, parse) {
+            LoadOutcome::Missing => AppConfig::default(),
+            LoadOutcome::Loaded(cfg) => cfg,
+            LoadOutcome::Restored { value, backup, error, .. } => {
+                log::warn!("{}，已从备份 {} 恢复应用配置", error, backup.display());
+                value
+            }
+            LoadOutcome::Failed { error, .. } => {
+                log::error!("{}，使用默认应用配置", error);
+                AppConfig::default()
+            }
+        }
+    }
+
+    /// 原子写入磁盘并保留滚动备份
+    pub fn save(&self) -> io::Result<()> {
+        persist::save_json(&Self::config_path(), self)
+    }
+
+    /// 校验所有分区
+    pub fn validate(&self) -> Result<(), String> {
+        self.general.validate()?;
+        self.production_line.validate()?;
+        self.detection.validate()?;
+        self.storage.validate()?;
+        self.mes.validate()?;
+        self.database.validate()
+    }
+
+    /// 读取分区；`logging` / `serial` 不在本文件中，返回 None
+    pub fn section(&self, section: ConfigSection) -> Option<Value> {
+        let value = match section {
+            ConfigSection::General => serde_json::to_value(&self.general),
+            ConfigSection::ProductionLine => serde_json::to_value(&self.production_line),
+            ConfigSection::Detection => serde_json::to_value(&self.detection),
+            ConfigSection::Storage => serde_json::to_value(&self.storage),
+            ConfigSection::Mes => serde_json::to_value(&self.mes),
+            ConfigSection::Database => serde_json::to_value(&self.database),
+            ConfigSection::Logging | ConfigSection::Serial => return None,
+        };
+        value.ok()
+    }
+
+    /// 校验并替换分区，返回规范化后的值（补全缺省字段）
+    pub fn set_section(&mut self, section: ConfigSection, value: Value) -> Result<Value, String> {
+        match section {
+            ConfigSection::General => {
+                self.general = parse_section(section, value, GeneralConfig::validate)?
+            }
+            ConfigSection::ProductionLine => {
+                self.production_line = parse_section(section, value, ProductionLineConfig::validate)?
+            }
+            ConfigSection::Detection => {
+                self.detection = parse_section(section, value, DetectionConfig::validate)?
+            }
+            ConfigSection::Storage => {
+                self.storage = parse_section(section, value, StorageConfig::validate)?
+            }
+            ConfigSection::Mes => self.mes = parse_section(section, value, MesConfig::validate)?,
+            ConfigSection::Database => {
+                self.database = parse_section(section, value, DatabaseConfig::validate)?
+            }
+            ConfigSection::Logging | ConfigSection::Serial => {
+                return Err(format!("{} 配置不保存在 app_config.json 中", section.name()))
+            }
+        }
+        self.section(section)
+            .ok_or_else(|| format!("序列化 {} 配置失败", section.name()))
+    }
+}
+
+/// 由 Tauri 管理的全局应用配置
+pub struct AppConfigState {
+    config: Mutex<AppConfig>,
+}
+
+impl AppConfigState {
+    pub fn load() -> Self {
+        Self {
+            config: Mutex::new(AppConfig::load_or_default()),
+        }
+    }
+
+    /// 当前配置快照
+    pub fn snapshot(&self) -> AppConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// 读取 `app_config.json` 中的分区
+    pub fn get(&self, section: ConfigSection) -> Result<Value, String> {
+        self.config
+            .lock()
+            .unwrap()
+            .section(section)
+            .ok_or_else(|| format!("{} 配置不保存在 app_config.json 中", section.name()))
+    }
+
+    /// 校验、持久化后再替换内存中的分区；保存失败时内存保持不变
+    pub fn update(&self, section: ConfigSection, value: Value) -> Result<Value, String> {
+        let mut guard = self.config.lock().unwrap();
+        let mut next = guard.clone();
+        let normalized = next.set_section(section, value)?;
+        next.save().map_err(|e| format!("保存应用配置失败: {}", e))?;
+        *guard = next;
+        Ok(normalized)
+    }
+    /// 仅替换内存中的配置（文件已由外部修改，无需写回），返回替换前的配置
+    pub fn set(&self, config: AppConfig) -> AppConfig {
+        std::mem::replace(&mut *self.config.lock().unwrap(), config)
+    }
+
+    /// 整体替换（导入配置包时使用），返回替换前的配置
+    pub fn replace(&self, config: AppConfig) -> Result<AppConfig, String> {
+        config.validate()?;
+        let mut guard = self.config.lock().unwrap();
+        config.save().map_err(|e| format!("保存应用配置失败: {}", e))?;
+        Ok(std::mem::replace(&mut *guard, config))
+    }
+}
+
+/// `config:changed` 事件 payload
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChangedPayload {
+    pub section: ConfigSection,
+    /// 更新后的完整分区内容
+    pub value: Value,
+}
+
+/// 推送 `config:changed`，通知所有窗口分区已更新
+pub fn emit_changed<T: Serialize>(app: &AppHandle, section: ConfigSection, value: &T) {
+    let value = match serde_json::to_value(value) {
+        Ok(v) => v,
+        Err(e) => {
+            log::warn!("序列化 {} 配置失败: {}", section.name(), e);
+            return;
+        }
+    };
+    if let Err(e) = app.emit("config:changed", ConfigChangedPayload { section, value }) {
+        log::warn!("推送 config:changed 失败: {}", e);
+    }
+}
@@ -0,0 +1,205 @@
+//! 应用配置的各个分区
+//!
+//! 字段缺省时使用 `Default`，旧版本配置文件新增分区或字段不会导致解析失败。
+
+use serde::{Deserialize, Serialize};
+
+/// 通用设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GeneralConfig {
+    /// 界面语言，如 "zh-CN"
+    pub language: String,
+    /// 主题："light" | "dark" | "auto"
+    pub theme: String,
+}
+
+impl Default for GeneralConfig {
+    fn default() -> Self {
+        Self {
+            language: "zh-CN".into(),
+            theme: "light".into(),
+        }
+    }
+}
+
+impl GeneralConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.language.trim().is_empty() {
+            return Err("language 不能为空".into());
+        }
+        if !["light", "dark", "auto"].contains(&self.theme.as_str()) {
+            return Err(format!("theme 必须为 light / dark / auto，当前为 {}", self.theme));
+        }
+        Ok(())
+    }
+}
+
+/// 产线与工位信息（界面顶栏显示，上报 MES 时携带）
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ProductionLineConfig {
+    pub production_line_name: String,
+    pub workstation_name: String,
+}
+
+impl Default for ProductionLineConfig {
+    fn default() -> Self {
+        Self {
+            production_line_name: "冰箱总装产线#1".into(),
+            workstation_name: "工位：外观检测工位1".into(),
+        }
+    }
+}
+
+impl ProductionLineConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.production_line_name.trim().is_empty() {
+            return Err("production_line_name 不能为空".into());
+        }
+        if self.workstation_name.trim().is_empty() {
+            return Err("workstation_name 不能为空".into());
+        }
+        Ok(())
+    }
+}
+
+/// 外观检测设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DetectionConfig {
+    /// 推理后端名称，如 "mock"、"onnx"
+    pub backend: String,
+    /// 模型文件路径；mock 后端不需要
+    pub model_path: Option<String>,
+    /// 判定为缺陷的置信度阈值，0.0 ~ 1.0
+    pub confidence_threshold: f32,
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        Self {
+            backend: "mock".into(),
+            model_path: None,
+            confidence_threshold: 0.5,
+        }
+    }
+}
+
+impl DetectionConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.backend.trim().is_empty() {
+            return Err("backend 不能为空".into());
+        }
+        if !(0.0..=1.0).contains(&self.confidence_threshold) {
+            return Err("confidence_threshold 必须在 0.0 ~ 1.0 之间".into());
+        }
+        if self.backend != "mock" && self.model_path.as_deref().is_none_or(|p| p.trim().is_empty()) {
+            return Err(format!("后端 {} 需要配置 model_path", self.backend));
+        }
+        Ok(())
+    }
+}
+
+/// 图片与检测结果的存储设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct StorageConfig {
+    /// 存储根目录
+    pub path: String,
+    /// 保留天数；0 表示不自动清理
+    pub retention_days: u32,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        Self {
+            path: "E:/data/storage".into(),
+            retention_days: 0,
+        }
+    }
+}
+
+impl StorageConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.path.trim().is_empty() {
+            return Err("path 不能为空".into());
+        }
+        Ok(())
+    }
+}
+
+/// MES 系统集成设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct MesConfig {
+    pub enabled: bool,
+    /// MES 接口地址，如 "http://mes.example/api"
+    pub url: String,
+    /// 访问令牌（敏感信息）
+    pub token: String,
+    /// 请求超时（毫秒）
+    pub timeout_ms: u64,
+}
+
+impl Default for MesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            url: String::new(),
+            token: String::new(),
+            timeout_ms: 5000,
+        }
+    }
+}
+
+impl MesConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.enabled && !(self.url.starts_with("http://") || self.url.starts_with("https://")) {
+            return Err("启用 MES 时 url 必须以 http:// 或 https:// 开头".into());
+        }
+        if self.timeout_ms == 0 {
+            return Err("timeout_ms 不能为 0".into());
+        }
+        Ok(())
+    }
+}
+
+/// 数据库连接设置
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct DatabaseConfig {
+    pub host: String,
+    pub port: u16,
+    pub user: String,
+    /// 密码（敏感信息）
+    pub password: String,
+    pub database: String,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self {
+            host: "127.0.0.1".into(),
+            port: 5432,
+            user: "sa".into(),
+            password: String::new(),
+            database: "easydb".into(),
+        }
+    }
+}
+
+impl DatabaseConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.host.trim().is_empty() {
+            return Err("host 不能为空".into());
+        }
+        if self.port == 0 {
+            return Err("port 不能为 0".into());
+        }
+        if self.database.trim().is_empty() {
+            return Err("database 不能为空".into());
+        }
+        Ok(())
+    }
+}
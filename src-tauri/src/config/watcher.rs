@@ -0,0 +1,179 @@
+//! 配置文件监视
+//!
+//! 技术员可能通过远程桌面直接编辑 `serial_config.json` / `log_config.json` /
+//! `app_config.json`。后台线程轮询文件的修改时间与大小，发现外部修改后：
+//!
+//! 1. 等待文件在一个轮询周期内不再变化（避免读到编辑器写了一半的内容）
+//! 2. 解析并用对应的 `validate()` 校验；失败则推送 `config:reload-error`，继续使用当前配置
+//! 3. 应用新配置：串口只启停/重启有变化的设备，日志更新运行时过滤，应用配置更新内存
+//! 4. 推送 `config:changed` 与 `config:reloaded`
+//!
+//! 本进程通过 `persist` 写入的内容不视为外部修改（见 `persist::is_own_write`）。
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::{emit_changed, AppConfig, AppConfigState, ConfigFile, ConfigSection};
+use crate::persist;
+
+/// 轮询间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 文件指纹：修改时间与大小；文件不存在为 None
+type Stamp = Option<(SystemTime, u64)>;
+
+fn stamp(path: &Path) -> Stamp {
+    let meta = fs::metadata(path).ok()?;
+    Some((meta.modified().ok()?, meta.len()))
+}
+
+/// `config:reloaded` / `config:reload-error` 事件 payload
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigReloadPayload {
+    pub file: ConfigFile,
+    pub path: String,
+    pub message: String,
+}
+
+/// 单个文件的监视状态
+struct Watched {
+    /// 上一次轮询看到的指纹
+    seen: Stamp,
+    /// 上一次处理过的指纹
+    handled: Stamp,
+}
+
+/// 启动后台线程监视配置文件的外部修改
+pub fn spawn_config_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut watched: BTreeMap<ConfigFile, Watched> = ConfigFile::supported()
+            .into_iter()
+            .map(|f| {
+                let s = stamp(&f.path());
+                (f, Watched { seen: s, handled: s })
+            })
+            .collect();
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            for (&file, w) in watched.iter_mut() {
+                let path = file.path();
+                let current = stamp(&path);
+                let stable = current == w.seen;
+                w.seen = current;
+                if !stable || current == w.handled {
+                    continue;
+                }
+                w.handled = current;
+                // 文件被删除：保持当前配置，下次保存时重新创建
+                let Ok(bytes) = fs::read(&path) else {
+                    continue;
+                };
+                if persist::is_own_write(&path, &bytes) {
+                    continue;
+                }
+                let payload = |message: String| ConfigReloadPayload {
+                    file,
+                    path: path.display().to_string(),
+                    message,
+                };
+                match reload(file, &bytes, &app) {
+                    Ok(message) => {
+                        log::info!("{} 被外部修改，已重新加载: {}", path.display(), message);
+                        let _ = app.emit("config:reloaded", payload(message));
+                    }
+                    Err(e) => {
+                        let message = format!("{} 被外部修改但无法应用，继续使用当前配置: {}", path.display(), e);
+                        log::error!("{}", message);
+                        let _ = app.emit("config:reload-error", payload(message));
+                    }
+                }
+            }
+        }
+    });
+}
+
+/// 解析、校验并应用修改后的文件，返回给用户的说明
+fn reload(file: ConfigFile, bytes: &[u8], app: &AppHandle) -> Result<String, String> {
+    let text = std::str::from_utf8(bytes).map_err(|e| format!("不是有效的 UTF-8: {}", e))?;
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|e| format!("JSON 解析失败: {}", e))?;
+    match file {
+        ConfigFile::App => reload_app(value, app),
+        #[cfg(feature = "logging")]
+        ConfigFile::Logging => reload_logging(value, app),
+        #[cfg(feature = "serial")]
+        ConfigFile::Serial => reload_serial(value, app),
+        #[allow(unreachable_patterns)]
+        _ => Err("当前构建不支持该配置".into()),
+    }
+}
+
+fn reload_app(value: serde_json::Value, app: &AppHandle) -> Result<String, String> {
+    let config: AppConfig = serde_json::from_value(value).map_err(|e| format!("格式错误: {}", e))?;
+    config.validate()?;
+    let state = app
+        .try_state::<AppConfigState>()
+        .ok_or("应用配置尚未初始化")?;
+    let old = state.set(config.clone());
+    let mut changed = Vec::new();
+    for section in ConfigSection::ALL {
+        if let Some(value) = config.section(section) {
+            if Some(&value) != old.section(section).as_ref() {
+                emit_changed(app, section, &value);
+                changed.push(section.name());
+            }
+        }
+    }
+    Ok(if changed.is_empty() {
+        "内容无变化".into()
+    } else {
+        format!("已更新分区: {}", changed.join(", "))
+    })
+}
+
+#[cfg(feature = "logging")]
+fn reload_logging(value: serde_json::Value, app: &AppHandle) -> Result<String, String> {
+    use crate::logging::config::LogConfig;
+
+    let config: LogConfig = serde_json::from_value(value).map_err(|e| format!("格式错误: {}", e))?;
+    config.validate()?;
+    let result = crate::logging::runtime::apply(&config);
+    emit_changed(app, ConfigSection::Logging, &config);
+    Ok(if result.restart_required {
+        format!(
+            "日志级别为 {}，{} 需重启后生效",
+            result.level,
+            result.restart_fields.join(", ")
+        )
+    } else {
+        format!("日志级别为 {}", result.level)
+    })
+}
+
+#[cfg(feature = "serial")]
+fn reload_serial(value: serde_json::Value, app: &AppHandle) -> Result<String, String> {
+    use crate::serial::config::{migrate, SerialConfig};
+
+    let (value, _) = migrate(value)?;
+    let config: SerialConfig = serde_json::from_value(value).map_err(|e| format!("格式错误: {}", e))?;
+    config.validate()?;
+    let state = app
+        .try_state::<crate::serial::SerialState>()
+        .ok_or("串口模块尚未初始化")?;
+    let result = state.manager.apply_config(&config, app.clone());
+    state.config_warnings.lock().unwrap().clear();
+    emit_changed(app, ConfigSection::Serial, &config);
+    let mut summary = format!(
+        "启动 {:?}，停止 {:?}，重启 {:?}",
+        result.started, result.stopped, result.restarted
+    );
+    if !result.stuck.is_empty() {
+        summary.push_str(&format!("，未能按时停止 {:?}", result.stuck));
+    }
+    Ok(summary)
+}
@@ -0,0 +1,87 @@
+use std::path::PathBuf;
+
+use tauri::{AppHandle, Manager, State};
+
+use super::{AppInfo, DiagnosticsReport, Snapshot};
+use crate::config::AppConfigState;
+
+/// 编译时启用的功能
+fn enabled_features() -> Vec<String> {
+    [
+        ("logging", cfg!(feature = "logging")),
+        ("single-instance", cfg!(feature = "single-instance")),
+        ("tray", cfg!(feature = "tray")),
+        ("detection", cfg!(feature = "detection")),
+        ("serial", cfg!(feature = "serial")),
+    ]
+    .into_iter()
+    .filter(|(_, enabled)| *enabled)
+    .map(|(name, _)| name.to_string())
+    .collect()
+}
+
+/// 收集各模块的运行状态（需在命令线程中访问托管状态）
+fn collect(app: &AppHandle, state: &AppConfigState) -> Snapshot {
+    let info = app.package_info();
+    let mut snapshot = Snapshot::new(
+        AppInfo {
+            name: info.name.clone(),
+            version: info.version.to_string(),
+            tauri_version: tauri::VERSION.to_string(),
+            features: enabled_features(),
+        },
+        &state.snapshot(),
+    );
+
+    match crate::paths::log_dir(app) {
+        Ok(dir) => snapshot.log_dir = Some(dir),
+        Err(e) => snapshot.report.warnings.push(e),
+    }
+
+    #[cfg(feature = "logging")]
+    snapshot.add("recent_errors.json", &crate::logging::recent::snapshot());
+
+    #[cfg(feature = "detection")]
+    if let Some(detection) = app.try_state::<crate::detection::DetectionState>() {
+        snapshot.report.detection = Some(super::DetectionInfo {
+            backend: detection.backend.name().to_string(),
+            ready: detection.backend.is_ready(),
+        });
+    }
+
+    #[cfg(feature = "serial")]
+    {
+        match crate::serial::ports::list_ports() {
+            Ok(ports) => snapshot.add("serial/ports.json", &ports),
+            Err(e) => snapshot.report.warnings.push(e),
+        }
+        if let Some(serial) = app.try_state::<crate::serial::SerialState>() {
+            snapshot.add("serial/stats.json", &serial.manager.stats());
+        }
+    }
+
+    snapshot
+}
+
+/// 导出诊断包（zip），用于售后工单
+///
+/// 包含日志文件、配置文件、程序与系统版本、串口枚举与统计、检测后端状态和最近的错误，
+/// 密钥类字段已脱敏。返回包内清单。
+#[tauri::command]
+pub async fn export_diagnostics(
+    path: String,
+    app: AppHandle,
+    state: State<'_, AppConfigState>,
+) -> Result<DiagnosticsReport, String> {
+    let snapshot = collect(&app, &state);
+    let target = PathBuf::from(&path);
+    let report = tauri::async_runtime::spawn_blocking(move || super::export(&target, snapshot))
+        .await
+        .map_err(|e| e.to_string())??;
+    log::info!(
+        "诊断包已导出到 {}，包含 {} 个文件",
+        path,
+        report.files.len()
+    );
+    Ok(report)
+}
@@ -0,0 +1,435 @@
+//! 诊断包导出（售后工单）
+//!
+//! 诊断包是一个 zip 文件：
+//!
+//! - `diagnostics.json`：`DiagnosticsReport`，程序与系统版本、检测后端状态、包内文件清单
+//! - `logs/`：日志目录下的当前文件与轮转归档（`.log.gz` 归档解压后存放）
+//! - `config/`：各配置文件及其损坏时保留的 `.bak`
+//! - `recent_errors.json`：内存中最近的警告与错误（logging feature）
+//! - `serial/ports.json`、`serial/stats.json`：串口枚举与各设备统计（serial feature）
+//!
+//! 打包前会脱敏：JSON 中键名像密钥的字段（token、password 等）替换为 `***`，
+//! 当前配置里的 MES token 与数据库密码在日志、JSON 条目的字符串值（如最近错误的消息）
+//! 和无法解析的配置文本中出现时同样替换。
+
+pub mod commands;
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::Serialize;
+use serde_json::Value;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+use crate::config::{AppConfig, ConfigFile};
+use crate::gz;
+use crate::persist;
+
+/// 当前诊断包格式版本
+pub const DIAGNOSTICS_VERSION: u32 = 1;
+
+const REPORT_NAME: &str = "diagnostics.json";
+
+/// 替换密钥时使用的占位符
+const REDACTED: &str = "***";
+
+/// 键名包含这些片段（不区分大小写）的 JSON 字段视为密钥
+const SECRET_KEYS: &[&str] = &["password", "passwd", "token", "secret", "api_key", "apikey"];
+
+/// 短于此长度的密钥值不做文本替换，避免把日志中常见的短字符串全部抹掉
+const MIN_SECRET_LEN: usize = 4;
+
+/// 程序信息
+#[derive(Debug, Clone, Serialize)]
+pub struct AppInfo {
+    pub name: String,
+    pub version: String,
+    pub tauri_version: String,
+    /// 编译时启用的功能
+    pub features: Vec<String>,
+}
+
+/// 操作系统信息
+#[derive(Debug, Clone, Serialize)]
+pub struct SystemInfo {
+    pub os: String,
+    pub family: String,
+    pub arch: String,
+    /// 发行版 / 系统版本，获取失败时为 None
+    pub os_version: Option<String>,
+}
+
+/// 检测后端状态
+#[derive(Debug, Clone, Serialize)]
+pub struct DetectionInfo {
+    pub backend: String,
+    pub ready: bool,
+}
+
+/// `diagnostics.json` 的内容，也是 `export_diagnostics` 的返回值
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticsReport {
+    pub format_version: u32,
+    pub created_at_ms: u64,
+    pub app: AppInfo,
+    pub system: SystemInfo,
+    /// 未启用 detection feature 时为 None
+    pub detection: Option<DetectionInfo>,
+    /// 包内文件（不含 `diagnostics.json` 本身）
+    pub files: Vec<String>,
+    /// 收集过程中跳过的内容及原因
+    pub warnings: Vec<String>,
+}
+
+/// 打包所需的全部输入，由命令在持有各状态时收集
+pub struct Snapshot {
+    pub report: DiagnosticsReport,
+    /// 直接写入的 JSON 条目（包内路径 → 内容）
+    pub entries: Vec<(String, Value)>,
+    pub log_dir: Option<PathBuf>,
+    /// 需要从文本中抹去的密钥值
+    pub secrets: Vec<String>,
+}
+
+impl Snapshot {
+    pub fn new(app: AppInfo, config: &AppConfig) -> Self {
+        let mut secrets: Vec<String> = [&config.mes.token, &config.database.password]
+            .into_iter()
+            .filter(|s| s.len() >= MIN_SECRET_LEN)
+            .cloned()
+            .collect();
+        // 先替换较长的值，避免一个密钥是另一个的子串时留下残片
+        secrets.sort_by_key(|s| std::cmp::Reverse(s.len()));
+        Self {
+            report: DiagnosticsReport {
+                format_version: DIAGNOSTICS_VERSION,
+                created_at_ms: now_ms(),
+                app,
+                system: SystemInfo::current(),
+                detection: None,
+                files: Vec::new(),
+                warnings: Vec::new(),
+            },
+            entries: Vec::new(),
+            log_dir: None,
+            secrets,
+        }
+    }
+
+    /// 添加一个 JSON 条目
+    pub fn add<T: Serialize>(&mut self, name: &str, value: &T) {
+        match serde_json::to_value(value) {
+            Ok(v) => self.entries.push((name.to_string(), v)),
+            Err(e) => self.report.warnings.push(format!("{}: {}", name, e)),
+        }
+    }
+}
+
+fn now_ms() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl SystemInfo {
+    fn current() -> Self {
+        Self {
+            os: std::env::consts::OS.to_string(),
+            family: std::env::consts::FAMILY.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            os_version: os_version(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn os_version() -> Option<String> {
+    let text = fs::read_to_string("/etc/os-release").ok()?;
+    text.lines()
+        .find_map(|l| l.strip_prefix("PRETTY_NAME="))
+        .map(|v| v.trim_matches('"').to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn os_version() -> Option<String> {
+    let out = std::process::Command::new("cmd").args(["/C", "ver"]).output().ok()?;
+    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (!text.is_empty()).then_some(text)
+}
+
+#[cfg(target_os = "macos")]
+fn os_version() -> Option<String> {
+    let out = std::process::Command::new("sw_vers").arg("-productVersion").output().ok()?;
+    let text = String::from_utf8_lossy(&out.stdout).trim().to_string();
+    (!text.is_empty()).then(|| format!("macOS {}", text))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+fn os_version() -> Option<String> {
+    None
+}
+
+// ─── 脱敏 ────────────────────────────────────────────────────────────────────
+
+fn is_secret_key(key: &str) -> bool {
+    let key = key.to_ascii_lowercase();
+    SECRET_KEYS.iter().any(|k| key.contains(k))
+}
+
+/// 把键名像密钥的非空字符串字段替换为占位符（递归）
+pub fn redact_value(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                match v {
+                    Value::String(s) if is_secret_key(k) => {
+                        if !s.is_empty() {
+                            *s = REDACTED.to_string();
+                        }
+                    }
+                    _ => redact_value(v),
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(redact_value),
+        _ => {}
+    }
+}
+
+/// 把文本中出现的密钥值替换为占位符
+pub fn redact_text(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .fold(text.to_string(), |t, s| t.replace(s.as_str(), REDACTED))
+}
+
+/// 对 JSON 中所有字符串值做 `redact_text`（递归），处理键名不像密钥但内容含密钥的字段
+fn redact_strings(value: &mut Value, secrets: &[String]) {
+    match value {
+        Value::String(s) => *s = redact_text(s, secrets),
+        Value::Object(map) => map.values_mut().for_each(|v| redact_strings(v, secrets)),
+        Value::Array(items) => items.iter_mut().for_each(|v| redact_strings(v, secrets)),
+        _ => {}
+    }
+}
+
+// ─── 收集文件 ────────────────────────────────────────────────────────────────
+
+/// 日志目录下的日志文件（当前文件与轮转归档），按文件名排序
+fn log_files(dir: &Path) -> Vec<PathBuf> {
+    let Ok(read) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<PathBuf> = read
+        .flatten()
+        .map(|e| e.path())
+        .filter(|p| {
+            let name = file_name(p);
+            p.is_file() && (name.ends_with(".log") || name.ends_with(".log.gz"))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// 各配置文件及其 `.bak`（存在时）
+fn config_files() -> Vec<PathBuf> {
+    ConfigFile::supported()
+        .into_iter()
+        .flat_map(|kind| {
+            let path = kind.path();
+            [persist::sibling(&path, "bak"), path]
+        })
+        .filter(|p| p.is_file())
+        .collect()
+}
+
+/// 读取配置文件并脱敏；能解析为 JSON 时按键名处理，否则按已知密钥值替换文本
+fn redacted_config(path: &Path, secrets: &[String]) -> std::io::Result<Vec<u8>> {
+    let bytes = fs::read(path)?;
+    match serde_json::from_slice::<Value>(&bytes) {
+        Ok(mut value) => {
+            redact_value(&mut value);
+            serde_json::to_vec_pretty(&value).map_err(std::io::Error::other)
+        }
+        Err(_) => Ok(redact_text(&String::from_utf8_lossy(&bytes), secrets).into_bytes()),
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// ─── 写入 ────────────────────────────────────────────────────────────────────
+
+/// 写出诊断包，返回最终的报告
+///
+/// 单个日志或配置文件读取失败只记入 `warnings`，不影响其余内容。
+pub fn export(path: &Path, snapshot: Snapshot) -> Result<DiagnosticsReport, String> {
+    let Snapshot {
+        mut report,
+        mut entries,
+        log_dir,
+        secrets,
+    } = snapshot;
+    for (_, value) in entries.iter_mut() {
+        redact_value(value);
+        redact_strings(value, &secrets);
+    }
+
+    let mut write = || -> zip::result::ZipResult<()> {
+        let mut zip = ZipWriter::new(File::create(path)?);
+        let options = SimpleFileOptions::default()
+            .compression_method(CompressionMethod::Deflated)
+            .large_file(true);
+
+        for (name, value) in &entries {
+            zip.start_file(name.as_str(), options)?;
+            zip.write_all(&serde_json::to_vec_pretty(value).map_err(std::io::Error::other)?)?;
+            report.files.push(name.clone());
+        }
+
+        for file in config_files() {
+            let name = format!("config/{}", file_name(&file));
+            match redacted_config(&file, &secrets) {
+                Ok(bytes) => {
+                    zip.start_file(name.as_str(), options)?;
+                    zip.write_all(&bytes)?;
+                    report.files.push(name);
+                }
+                Err(e) => report.warnings.push(format!("{}: {}", file.display(), e)),
+            }
+        }
+
+        let logs = log_dir.as_deref().map(log_files).unwrap_or_default();
+        for file in logs {
+            let plain = file_name(&file);
+            let name = format!("logs/{}", plain.strip_suffix(".gz").unwrap_or(&plain));
+            // `.gz` 归档需要解压才能脱敏
+            match gz::read_maybe_gz(&file) {
+                Ok(bytes) => {
+                    let text = redact_text(&String::from_utf8_lossy(&bytes), &secrets);
+                    zip.start_file(name.as_str(), options)?;
+                    zip.write_all(text.as_bytes())?;
+                    report.files.push(name);
+                }
+                Err(e) => report.warnings.push(format!("{}: {}", file.display(), e)),
+            }
+        }
+
+        zip.start_file(REPORT_NAME, options)?;
+        zip.write_all(&serde_json::to_vec_pretty(&report).map_err(std::io::Error::other)?)?;
+        zip.finish()?;
+        Ok(())
+    };
+    write().map_err(|e| format!("写入诊断包 {} 失败: {}", path.display(), e))?;
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn read_entry(path: &Path, name: &str) -> String {
+        let mut zip = zip::ZipArchive::new(File::open(path).unwrap()).unwrap();
+        let mut text = String::new();
+        zip.by_name(name).unwrap().read_to_string(&mut text).unwrap();
+        text
+    }
+
+    fn app_info() -> AppInfo {
+        AppInfo {
+            name: "app".into(),
+            version: "1.0.0".into(),
+            tauri_version: "2".into(),
+            features: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn redact_value_replaces_secret_keys_recursively() {
+        let mut value = serde_json::json!({
+            "mes": { "url": "http://mes", "Token": "abc", "apiKey": "" },
+            "database": { "user": "sa", "DB_PASSWORD": "pw", "port": 1433 },
+            "devices": [{ "client_secret": "s1", "name": "token reader" }],
+            "token_ttl": 30,
+        });
+        redact_value(&mut value);
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "mes": { "url": "http://mes", "Token": "***", "apiKey": "" },
+                "database": { "user": "sa", "DB_PASSWORD": "***", "port": 1433 },
+                "devices": [{ "client_secret": "***", "name": "token reader" }],
+                "token_ttl": 30,
+            })
+        );
+    }
+
+    #[test]
+    fn redact_text_replaces_longest_secrets_first() {
+        let mut config = AppConfig::default();
+        config.mes.token = "abcd".into();
+        config.database.password = "xxabcdxx".into();
+        let secrets = Snapshot::new(app_info(), &config).secrets;
+        assert_eq!(secrets, ["xxabcdxx", "abcd"]);
+        assert_eq!(
+            redact_text("pw=xxabcdxx token=abcd abc", &secrets),
+            "pw=*** token=*** abc"
+        );
+        assert_eq!(redact_text("nothing here", &[]), "nothing here");
+
+        config.mes.token = "abc".into();
+        assert_eq!(Snapshot::new(app_info(), &config).secrets, ["xxabcdxx"]);
+    }
+
+    #[test]
+    fn unparseable_config_is_redacted_as_text() {
+        let dir = std::env::temp_dir().join(format!("diagnostics-config-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let broken = dir.join("app_config.json");
+        fs::write(&broken, "{ \"mes\": { \"token\": \"mes-token-1234\" ").unwrap();
+        let valid = dir.join("log_config.json");
+        fs::write(&valid, r#"{ "password": "short" }"#).unwrap();
+
+        let secrets = vec!["mes-token-1234".to_string()];
+        let text = String::from_utf8(redacted_config(&broken, &secrets).unwrap()).unwrap();
+        assert_eq!(text, "{ \"mes\": { \"token\": \"***\" ");
+        let text = String::from_utf8(redacted_config(&valid, &secrets).unwrap()).unwrap();
+        let _ = fs::remove_dir_all(&dir);
+        assert!(text.contains("\"***\"") && !text.contains("short"), "{}", text);
+    }
+
+    #[test]
+    fn secrets_in_entry_messages_are_redacted() {
+        let mut config = AppConfig::default();
+        config.mes.token = "mes-token-1234".into();
+        config.database.password = "db-secret".into();
+        let mut snapshot = Snapshot::new(app_info(), &config);
+        snapshot.add(
+            "recent_errors.json",
+            &serde_json::json!([
+                { "level": "ERROR", "message": "MES 登录失败: token=mes-token-1234" },
+                { "level": "WARN", "message": "连接 db://user:db-secret@host 失败", "token": "x" },
+            ]),
+        );
+
+        let name = format!("diagnostics-test-{}.zip", std::process::id());
+        let path = std::env::temp_dir().join(name);
+        let report = export(&path, snapshot).unwrap();
+        assert!(report.files.contains(&"recent_errors.json".to_string()));
+        let text = read_entry(&path, "recent_errors.json");
+        let _ = fs::remove_file(&path);
+        assert!(!text.contains("mes-token-1234"), "{}", text);
+        assert!(!text.contains("db-secret"), "{}", text);
+        assert!(text.contains("token=***"));
+        assert!(!text.contains("\"x\""));
+    }
+}
@@ -0,0 +1,18 @@
+//! 读取可能经 gzip 压缩的文件（`retention` 归档后的日志），日志查看与诊断导出共用
+
+use std::fs;
+use std::io::{self, Read};
+use std::path::Path;
+
+use flate2::read::GzDecoder;
+
+/// 读取文件内容，`.gz` 文件自动解压
+pub fn read_maybe_gz(path: &Path) -> io::Result<Vec<u8>> {
+    let bytes = fs::read(path)?;
+    if path.extension().is_some_and(|ext| ext == "gz") {
+        let mut out = Vec::new();
+        GzDecoder::new(bytes.as_slice()).read_to_end(&mut out)?;
+        return Ok(out);
+    }
+    Ok(bytes)
+}
@@ -1,5 +1,15 @@
 use tauri::Manager;
 
+mod paths;
+
+mod persist;
+
+mod gz;
+
+mod config;
+
+mod diagnostics;
+
 #[cfg(feature = "logging")]
 mod logging;
 
@@ -16,6 +26,10 @@ mod serial;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
+    let context = tauri::generate_context!();
+    // 配置目录须在加载任何配置（包括下面的日志配置）之前确定
+    paths::init(&context.config().identifier);
+
     let builder = tauri::Builder::default();
 
     #[cfg(feature = "logging")]
@@ -31,6 +45,8 @@ pub fn run() {
         }
     }));
 
+    let builder = builder.manage(config::AppConfigState::load());
+
     #[cfg(feature = "detection")]
     let builder = builder.manage(detection::DetectionState::with_default_backend());
 
@@ -40,28 +56,54 @@ pub fn run() {
     builder
         .setup(|app| {
             #[cfg(feature = "logging")]
-            if let Ok(log_dir) = app.path().app_log_dir() {
+            if let Ok(log_dir) = paths::log_dir(app.handle()) {
                 println!("=====================================");
+                println!("配置文件目录: {}", paths::config_dir().display());
                 println!("日志文件目录: {}", log_dir.display());
                 println!("=====================================");
             }
 
+            #[cfg(feature = "logging")]
+            logging::viewer::attach_stream(app.handle().clone());
+
+            // 插件 attach 时把全局级别设成了 Trace，恢复为配置的级别
+            #[cfg(feature = "logging")]
+            logging::runtime::sync_max_level();
+
+            // 归档压缩与按时间 / 总大小清理
+            #[cfg(feature = "logging")]
+            logging::retention::spawn_sweeper(app.handle().clone());
+
             log::info!("Application started successfully");
 
             // 启动串口监听（需要 AppHandle，必须在 setup 内）
             #[cfg(feature = "serial")]
             {
-                let cfg = serial::config::SerialConfig::load_or_default();
+                let (cfg, warnings) = serial::config::SerialConfig::load_and_report(app.handle());
                 let state = app.state::<serial::SerialState>();
+                *state.config_warnings.lock().unwrap() = warnings;
                 state.manager.start_all(&cfg, app.handle().clone());
+                serial::manager::spawn_stats_reporter(&state.manager, app.handle().clone());
+                serial::ports::spawn_port_watcher(app.handle().clone());
                 log::info!("串口监听已初始化，共 {} 个设备", cfg.devices.len());
             }
 
+            // 配置文件被外部修改时自动重新加载（在串口监听启动之后，避免与启动竞争）
+            config::watcher::spawn_config_watcher(app.handle().clone());
+
             Ok(())
         })
         .plugin(tauri_plugin_opener::init())
         .plugin(tauri_plugin_process::init())
         .invoke_handler(tauri::generate_handler![
+            // --- 应用配置命令 ---
+            crate::config::commands::get_config_section,
+            crate::config::commands::get_app_config,
+            crate::config::commands::update_config_section,
+            crate::config::commands::export_config_bundle,
+            crate::config::commands::import_config_bundle,
+            // --- 诊断包 ---
+            crate::diagnostics::commands::export_diagnostics,
             // --- 日志管理命令（仅 logging feature）---
             #[cfg(feature = "logging")]
             crate::logging::commands::get_log_config,
@@ -69,6 +111,16 @@ pub fn run() {
             crate::logging::commands::update_log_config,
             #[cfg(feature = "logging")]
             crate::logging::commands::reset_log_config,
+            #[cfg(feature = "logging")]
+            crate::logging::commands::get_log_dir,
+            #[cfg(feature = "logging")]
+            crate::logging::commands::list_log_files,
+            #[cfg(feature = "logging")]
+            crate::logging::commands::read_log_page,
+            #[cfg(feature = "logging")]
+            crate::logging::commands::search_logs,
+            #[cfg(feature = "logging")]
+            crate::logging::commands::set_log_stream,
             // --- 检测命令（仅 detection feature）---
             #[cfg(feature = "detection")]
             crate::detection::commands::detect_image,
@@ -88,10 +140,72 @@ pub fn run() {
             #[cfg(feature = "serial")]
             crate::serial::commands::stop_serial_listeners,
             #[cfg(feature = "serial")]
+            crate::serial::commands::start_serial_device,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::stop_serial_device,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::restart_serial_device,
+            #[cfg(feature = "serial")]
             crate::serial::commands::send_serial_command,
             #[cfg(feature = "serial")]
+            crate::serial::commands::send_serial_payload,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::encode_serial_payload,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::execute_device_command,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::serial_transact,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::start_serial_capture,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::stop_serial_capture,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::replay_serial_capture,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::stop_serial_replay,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::get_serial_stats,
+            #[cfg(feature = "serial")]
             crate::serial::commands::list_available_ports,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::modbus_read_coils,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::modbus_read_discrete_inputs,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::modbus_read_holding_registers,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::modbus_read_input_registers,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::modbus_write_single_coil,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::modbus_write_single_register,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::modbus_write_multiple_coils,
+            #[cfg(feature = "serial")]
+            crate::serial::commands::modbus_write_multiple_registers,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(context)
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            if let tauri::RunEvent::Exit = event {
+                on_exit(app);
+            }
+        });
+}
+
+/// 应用退出前的清理：停止串口监听线程，释放端口
+fn on_exit(app: &tauri::AppHandle) {
+    #[cfg(feature = "serial")]
+    if let Some(state) = app.try_state::<serial::SerialState>() {
+        state.manager.stop_replay();
+        let stuck = state.manager.stop_all();
+        if stuck.is_empty() {
+            log::info!("串口监听线程已全部停止");
+        } else {
+            log::error!("退出时以下串口监听线程未能停止: {}", stuck.join(", "));
+        }
+    }
+
+    #[cfg(not(feature = "serial"))]
+    let _ = app;
 }
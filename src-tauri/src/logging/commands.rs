@@ -1,5 +1,10 @@
-use tauri::command;
+use std::path::PathBuf;
+
+use tauri::{command, AppHandle};
+use crate::config::{emit_changed, ConfigSection};
 use crate::logging::config::LogConfig;
+use crate::logging::runtime::{self, LogApplyResult};
+use crate::logging::viewer::{self, LogFileInfo, LogPage, LogQuery, LogSearchResult};
 
 /// 获取当前日志配置
 #[command]
@@ -9,8 +14,10 @@ pub async fn get_log_config() -> Result<LogConfig, String> {
 }
 
 /// 更新日志配置
+///
+/// 级别与 stdout 开关立即生效；返回值说明是否还有字段需要重启后才生效。
 #[command]
-pub async fn update_log_config(config: LogConfig) -> Result<(), String> {
+pub async fn update_log_config(config: LogConfig, app: AppHandle) -> Result<LogApplyResult, String> {
     // 验证配置
     if let Err(e) = config.validate() {
        log::error!("Invalid log config: {}", e);
@@ -20,18 +27,75 @@ pub async fn update_log_config(config: LogConfig) -> Result<(), String> {
     // 保存配置到磁盘
     config.save()
         .map_err(|e| format!("Failed to save log config: {}", e))?;
+    let result = runtime::apply(&config);
     log::info!("Log config updated  {:?}", config);
-    Ok(())
+    emit_changed(&app, ConfigSection::Logging, &config);
+    Ok(result)
 }
 
 /// 重置日志配置为默认值
 #[command]
-pub async fn reset_log_config() -> Result<LogConfig, String> {
+pub async fn reset_log_config(app: AppHandle) -> Result<LogConfig, String> {
     let default_config = LogConfig::default();
     
     // 保存默认配置到磁盘
     default_config.save()
         .map_err(|e| format!("Failed to save default log config: {}", e))?;
+    runtime::apply(&default_config);
     log::info!("Log config reset to default");
+    emit_changed(&app, ConfigSection::Logging, &default_config);
     Ok(default_config)
-}
\ No newline at end of file
+}
+
+// ─── 日志查看 ────────────────────────────────────────────────────────────────
+
+/// 每页默认条数
+const DEFAULT_PAGE_SIZE: usize = 200;
+
+fn log_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    crate::paths::log_dir(app)
+}
+
+/// 日志目录路径（前端可用 opener 打开）
+#[command]
+pub async fn get_log_dir(app: AppHandle) -> Result<String, String> {
+    Ok(log_dir(&app)?.display().to_string())
+}
+
+/// 列出当前日志文件与轮转归档
+#[command]
+pub async fn list_log_files(app: AppHandle) -> Result<Vec<LogFileInfo>, String> {
+    viewer::list_files(&log_dir(&app)?)
+}
+
+/// 读取一页解析后的日志；`offset` 为从最新一条往前跳过的条数
+#[command]
+pub async fn read_log_page(
+    file: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    app: AppHandle,
+) -> Result<LogPage, String> {
+    let dir = log_dir(&app)?;
+    tauri::async_runtime::spawn_blocking(move || {
+        viewer::read_page(&dir, &file, offset.unwrap_or(0), limit.unwrap_or(DEFAULT_PAGE_SIZE))
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+/// 按文本、级别、时间范围跨文件搜索日志
+#[command]
+pub async fn search_logs(query: LogQuery, app: AppHandle) -> Result<LogSearchResult, String> {
+    let dir = log_dir(&app)?;
+    tauri::async_runtime::spawn_blocking(move || viewer::search(&dir, &query))
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+/// 开启或关闭 `log:entry` 实时推送
+#[command]
+pub async fn set_log_stream(enabled: bool) -> Result<(), String> {
+    viewer::set_stream(enabled);
+    Ok(())
+}
@@ -1,9 +1,23 @@
-use dirs;
+use log::LevelFilter;
 use serde::{Deserialize, Serialize};
-use std::fs;
+use std::collections::BTreeMap;
 use std::io;
 use std::path::PathBuf;
 
+use crate::paths;
+use crate::persist::{self, LoadOutcome};
+
+/// 日志文件的行格式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum LogFormat {
+    /// `[日期][时间][target][LEVEL] 消息`
+    #[default]
+    Text,
+    /// 每行一个 JSON 对象，便于日志采集系统解析
+    Json,
+}
+
 /// 应用端日志配置（简洁可扩展）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LogConfig {
@@ -15,6 +29,26 @@ pub struct LogConfig {
     pub level: String,
     /// 是否同时输出到 stdout（便于开发/容器环境）。
     pub to_stdout: bool,
+    /// 按模块（log target 前缀）单独设置的级别，例如
+    /// `{"easydesktopapp_lib::serial": "Trace", "tao": "Warn"}`；最长前缀优先。
+    #[serde(default)]
+    pub module_levels: BTreeMap<String, String>,
+    /// 行格式："text" 或 "json"
+    #[serde(default)]
+    pub format: LogFormat,
+    /// 归档保留天数，超过后删除。None 表示不按时间清理。
+    #[serde(default)]
+    pub max_age_days: Option<u32>,
+    /// 日志目录总大小上限（字节，含当前文件），超出时从最旧的归档开始删除。None 表示不限制。
+    #[serde(default)]
+    pub max_total_size: Option<u64>,
+    /// 是否把轮转出的归档压缩为 `.log.gz`
+    #[serde(default = "default_compress_archives")]
+    pub compress_archives: bool,
+}
+
+fn default_compress_archives() -> bool {
+    true
 }
 
 impl Default for LogConfig {
@@ -24,53 +58,60 @@ impl Default for LogConfig {
             keep_files: 10,                        // 保留最近 10 个归档
             level: "Info".into(),
             to_stdout: true,
+            module_levels: BTreeMap::new(),
+            format: LogFormat::Text,
+            max_age_days: None,
+            max_total_size: None,
+            compress_archives: true,
         }
     }
 }
 
 impl LogConfig {
-    /// 返回配置文件路径：使用系统配置目录（dirs::config_dir）下以 crate 名称为目录的 log_config.json
+    /// 配置文件路径：`{config_dir}/log_config.json`（见 `paths`）
     pub fn config_path() -> PathBuf {
-        // 优先使用平台配置目录，否则回退到当前目录
-        let mut base = dirs::config_dir().unwrap_or_else(|| std::env::current_dir().unwrap());
-        // 使用 Cargo 包名作为子目录（通常等于 product name）
-        println!("cargo pkg name: {}", env!("CARGO_PKG_NAME"));
-        base.push(env!("CARGO_PKG_NAME"));
-        // 创建目录（调用方可保证目录存在或在 save 中创建）
-        base.push("log_config.json");
-        println!("log config path: {}", base.display());
-        base
+        paths::config_file("log_config.json")
     }
 
-    /// 从磁盘加载配置，若不存在或解析错误则返回默认配置
+    /// 从磁盘加载配置；损坏时尝试从滚动备份恢复，仍失败则返回默认配置
+    ///
+    /// 加载发生在日志系统初始化之前，因此异常只能输出到 stderr。
     pub fn load_or_default() -> Self {
         let path = Self::config_path();
-        match fs::read_to_string(&path) {
-            Ok(s) => match serde_json::from_str::<LogConfig>(&s) {
-                Ok(cfg) => cfg,
-                Err(e) => {
-                    eprintln!(
-                        "failed to parse log config {}, using default: {}",
-                        path.display(),
-                        e
-                    );
-                    LogConfig::default()
-                }
-            },
-            Err(_) => LogConfig::default(),
+        let parse = |s: &str| serde_json::from_str::<LogConfig>(s).map_err(|e| e.to_string());
+        match persist::load(&path, parse) {
+            LoadOutcome::Missing => LogConfig::default(),
+            LoadOutcome::Loaded(cfg) => cfg,
+            LoadOutcome::Restored { value, backup, error, .. } => {
+                eprintln!("{}, restored log config from {}", error, backup.display());
+                value
+            }
+            LoadOutcome::Failed { error, .. } => {
+                eprintln!("{}, using default log config", error);
+                LogConfig::default()
+            }
         }
     }
 
-    /// 保存配置到磁盘（会创建父目录）
+    /// 原子写入磁盘并保留滚动备份（见 `persist::save_json`）
     pub fn save(&self) -> io::Result<()> {
-        let path = Self::config_path();
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
-        }
-        let s = serde_json::to_string_pretty(self)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, format!("serialize error: {}", e)))?;
-        fs::write(path, s)?;
-        Ok(())
+        persist::save_json(&Self::config_path(), self)
+    }
+
+    /// 解析后的日志级别；无法识别时为 Info
+    pub fn level_filter(&self) -> LevelFilter {
+        self.level.parse().unwrap_or(LevelFilter::Info)
+    }
+
+    /// 解析后的模块级别，按模块名长度降序（最长前缀优先匹配）
+    pub fn module_filters(&self) -> Vec<(String, LevelFilter)> {
+        let mut filters: Vec<(String, LevelFilter)> = self
+            .module_levels
+            .iter()
+            .filter_map(|(m, l)| Some((m.clone(), l.parse().ok()?)))
+            .collect();
+        filters.sort_by_key(|(m, _)| std::cmp::Reverse(m.len()));
+        filters
     }
 
     /// 简单校验配置（例如确保 keep_files 不等于 0）
@@ -83,7 +124,24 @@ impl LogConfig {
                 return Err("max_file_size must be > 0".into());
             }
         }
-        // level 的合法性由上层 parse 为 LevelFilter 时决定（这里不严格检查）
+        if self.max_age_days == Some(0) {
+            return Err("max_age_days must be >= 1".into());
+        }
+        if self.max_total_size == Some(0) {
+            return Err("max_total_size must be > 0".into());
+        }
+        // level 会在运行中直接应用，无法识别时拒绝而不是静默回退
+        if self.level.parse::<LevelFilter>().is_err() {
+            return Err(format!("invalid level: {}", self.level));
+        }
+        for (module, level) in &self.module_levels {
+            if module.is_empty() || module.contains(char::is_whitespace) {
+                return Err(format!("invalid module name: {:?}", module));
+            }
+            if level.parse::<LevelFilter>().is_err() {
+                return Err(format!("invalid level for {}: {}", module, level));
+            }
+        }
         Ok(())
     }
 }
@@ -0,0 +1,174 @@
+//! 日志行格式
+//!
+//! - text：与 `tauri_plugin_log` 默认格式一致的 `[日期][时间][target][LEVEL] 消息`（本地时间），
+//!   带结构化字段时以 ` key=value` 追加在消息后
+//! - json：每行一个对象，字段为 timestamp（RFC 3339，本地时间带时区偏移）、level、target、module、
+//!   file、line、thread、message 以及结构化字段 kv，例如
+//!   `log::info!(device_id = "scanner_1", sn = code; "RX: {}", code)`
+//!
+//! 格式按 `runtime::format()` 在每条日志时选择，修改配置后立即生效。
+//! 时间按 `TIMEZONE`（本地时间，无法获取时区时退回 UTC）取得，与日志文件轮转命名一致。
+
+use std::fmt::Arguments;
+
+use fern::FormatCallback;
+use log::kv::{self, Key, Value, VisitSource};
+use log::Record;
+use serde_json::{json, Map};
+use tauri_plugin_log::TimezoneStrategy;
+use time::macros::format_description;
+use time::OffsetDateTime;
+
+use super::config::LogFormat;
+use super::runtime;
+
+/// 日志时间所用的时区策略，同时传给插件用于轮转文件命名
+pub const TIMEZONE: TimezoneStrategy = TimezoneStrategy::UseLocal;
+
+/// 按 `TIMEZONE` 取当前时间
+pub fn now() -> OffsetDateTime {
+    TIMEZONE.get_now()
+}
+
+/// dispatch 的格式化回调
+pub fn format(out: FormatCallback, message: &Arguments, record: &Record) {
+    match runtime::format() {
+        LogFormat::Text => text(out, message, record),
+        LogFormat::Json => json_line(out, message, record),
+    }
+}
+
+fn text(out: FormatCallback, message: &Arguments, record: &Record) {
+    let now = now()
+        .format(format_description!("[[[year]-[month]-[day]][[[hour]:[minute]:[second]]"))
+        .unwrap_or_default();
+    let mut kv = String::new();
+    for (k, v) in collect_kv(record) {
+        match v.as_str() {
+            Some(s) => kv.push_str(&format!(" {}={}", k, s)),
+            None => kv.push_str(&format!(" {}={}", k, v)),
+        }
+    }
+    out.finish(format_args!(
+        "{}[{}][{}] {}{}",
+        now,
+        record.target(),
+        record.level(),
+        message,
+        kv
+    ))
+}
+
+fn json_line(out: FormatCallback, message: &Arguments, record: &Record) {
+    let now = now()
+        .format(format_description!(
+            "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3][offset_hour sign:mandatory]:[offset_minute]"
+        ))
+        .unwrap_or_default();
+    let kv: Map<String, serde_json::Value> = collect_kv(record).into_iter().collect();
+    let line = json!({
+        "timestamp": now,
+        "level": record.level().as_str(),
+        "target": record.target(),
+        "module": record.module_path(),
+        "file": record.file(),
+        "line": record.line(),
+        "thread": std::thread::current().name(),
+        "message": message.to_string(),
+        "kv": kv,
+    });
+    out.finish(format_args!("{}", line))
+}
+
+/// 收集记录中的结构化字段，数值与布尔保持原类型
+fn collect_kv(record: &Record) -> Vec<(String, serde_json::Value)> {
+    struct Collect(Vec<(String, serde_json::Value)>);
+
+    impl<'kvs> VisitSource<'kvs> for Collect {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+            let v = if let Some(b) = value.to_bool() {
+                json!(b)
+            } else if let Some(n) = value.to_i64() {
+                json!(n)
+            } else if let Some(n) = value.to_u64() {
+                json!(n)
+            } else if let Some(n) = value.to_f64() {
+                json!(n)
+            } else {
+                json!(value.to_string())
+            };
+            self.0.push((key.to_string(), v));
+            Ok(())
+        }
+    }
+
+    let mut collect = Collect(Vec::new());
+    let _ = record.key_values().visit(&mut collect);
+    collect.0
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use log::{Level, Log};
+
+    use super::*;
+
+    /// 用指定的格式化函数输出一条记录，返回格式化后的行
+    fn render(format: fn(FormatCallback, &Arguments, &Record), record: &Record) -> String {
+        let lines = Arc::new(Mutex::new(Vec::new()));
+        let sink = lines.clone();
+        let (_, logger) = fern::Dispatch::new()
+            .format(format)
+            .chain(fern::Output::call(move |r| sink.lock().unwrap().push(r.args().to_string())))
+            .into_log();
+        logger.log(record);
+        let line = lines.lock().unwrap().pop();
+        line.unwrap()
+    }
+
+    #[test]
+    fn text_appends_kv_to_message() {
+        let kv: &[(&str, &str)] = &[("device_id", "scanner_1"), ("sn", "A 1")];
+        let line = render(
+            text,
+            &Record::builder()
+                .args(format_args!("RX: ok"))
+                .level(Level::Warn)
+                .target("app::serial")
+                .key_values(&kv)
+                .build(),
+        );
+        let (time, rest) = line.split_at(22);
+        assert!(time.starts_with('[') && time.ends_with(']'), "{line}");
+        assert_eq!(rest, "[app::serial][WARN] RX: ok device_id=scanner_1 sn=A 1");
+    }
+
+    #[test]
+    fn json_line_keeps_fields_and_kv_types() {
+        let kv: &[(&str, i64)] = &[("baud", 9600)];
+        let line = render(
+            json_line,
+            &Record::builder()
+                .args(format_args!("hello\nworld"))
+                .level(Level::Info)
+                .target("app")
+                .module_path(Some("app::serial"))
+                .file(Some("serial.rs"))
+                .line(Some(3))
+                .key_values(&kv)
+                .build(),
+        );
+        assert!(!line.contains('\n'));
+        let v: serde_json::Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(v["level"], "INFO");
+        assert_eq!(v["target"], "app");
+        assert_eq!(v["module"], "app::serial");
+        assert_eq!((v["file"].as_str(), v["line"].as_u64()), (Some("serial.rs"), Some(3)));
+        assert_eq!(v["message"], "hello\nworld");
+        assert_eq!(v["kv"], json!({ "baud": 9600 }));
+        let ts = v["timestamp"].as_str().unwrap();
+        assert_eq!((ts.len(), &ts[10..11]), (29, "T"), "{ts}");
+    }
+}
@@ -2,34 +2,51 @@ use log::LevelFilter;
 use tauri::{Runtime, plugin::TauriPlugin};
 use tauri_plugin_log::{Builder as LogBuilder, Target, RotationStrategy, TargetKind};
 use super::config::LogConfig;
+use super::format;
+use super::runtime;
+use super::recent;
+use super::viewer;
 
 /// 从配置构建日志插件
 fn build_log_plugin_from_config<R: Runtime>(cfg: &LogConfig) -> TauriPlugin<R> {
-    // 解析LevelFilter
-    let level = cfg.level.parse::<LevelFilter>().unwrap_or(LevelFilter::Info);
+    // 级别与 stdout 开关由 runtime 在运行中过滤，插件本身放行所有级别
+    runtime::init(cfg);
 
     // 解析max_size
     let default_max_size = 10u128 * 1024 * 1024; // 10 MB
     let max_size = cfg.max_file_size.unwrap_or(default_max_size);
 
     // 构建目标列表
-    let mut targets = vec![
-        // 总是写入日志文件
-        Target::new(TargetKind::LogDir { file_name: None })
-    ];
-
-    // 根据配置决定是否输出到stdout
-    if cfg.to_stdout {
-        targets.push(Target::new(TargetKind::Stdout));
-    }
-    
+    // 总是写入日志文件；指定了数据目录时写到 `{data_dir}/logs`
+    let file_target = match crate::paths::log_dir_override() {
+        Some(dir) => TargetKind::Folder { path: dir.to_path_buf(), file_name: None },
+        None => TargetKind::LogDir { file_name: None },
+    };
+    let mut targets = vec![Target::new(file_target)];
+
+    // stdout 目标始终注册，是否输出由运行时开关决定
+    targets.push(Target::new(TargetKind::Stdout).filter(|_| runtime::stdout_enabled()));
+
+    // 日志查看器的实时推送（未开启时直接返回）
+    targets.push(Target::new(TargetKind::Dispatch(
+        fern::Dispatch::new().chain(fern::Output::call(viewer::publish)),
+    )));
+
+    // 最近的警告与错误，供诊断包使用
+    targets.push(Target::new(TargetKind::Dispatch(
+        fern::Dispatch::new()
+            .level(LevelFilter::Warn)
+            .chain(fern::Output::call(recent::push)),
+    )));
+
     // 构建日志器
     let mut builder = LogBuilder::new()
-        .level(level)
+        .level(LevelFilter::Trace)
+        .filter(runtime::enabled)
         .max_file_size(max_size)
         .targets(targets)
-        .timezone_strategy(tauri_plugin_log::TimezoneStrategy::UseLocal);
-        // .formatter(formatter);
+        .format(format::format)
+        .timezone_strategy(format::TIMEZONE);
 
     // 设置日志轮转策略：保留指定数量的归档文件
     if cfg.keep_files > 0 {
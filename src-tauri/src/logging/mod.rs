@@ -1,3 +1,8 @@
 pub mod config;
 pub mod logs;
-pub mod commands;
\ No newline at end of file
+pub mod commands;
+pub mod runtime;
+pub mod viewer;
+pub mod format;
+pub mod recent;
+pub mod retention;
\ No newline at end of file
@@ -0,0 +1,29 @@
+//! 最近的警告与错误
+//!
+//! 在内存中保留最近 `CAPACITY` 条 WARN / ERROR 日志，导出诊断包时一并打包，
+//! 不必再从（可能已轮转的）日志文件里翻找。由 `logs.rs` 中的 dispatch 目标写入。
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use super::viewer::{self, LogEntry};
+
+/// 保留的最大条数
+const CAPACITY: usize = 200;
+
+static RECENT: Mutex<VecDeque<LogEntry>> = Mutex::new(VecDeque::new());
+
+/// dispatch 目标的回调：记录一条警告或错误
+pub fn push(record: &log::Record) {
+    let entry = viewer::entry_from_record(record);
+    let mut recent = RECENT.lock().unwrap();
+    if recent.len() == CAPACITY {
+        recent.pop_front();
+    }
+    recent.push_back(entry);
+}
+
+/// 按时间顺序返回当前保留的条目
+pub fn snapshot() -> Vec<LogEntry> {
+    RECENT.lock().unwrap().iter().cloned().collect()
+}
@@ -0,0 +1,315 @@
+//! 日志保留与归档压缩
+//!
+//! `RotationStrategy::KeepSome` 只按数量删除未压缩的 `.log` 归档。清理任务在启动时和之后
+//! 每 `SWEEP_INTERVAL` 运行一次，对日志目录中的归档依次执行：
+//!
+//! 1. 把新轮转出的 `.log` 归档压缩为 `.log.gz`（保留原修改时间）
+//! 2. 删除修改时间早于 `max_age_days` 的归档
+//! 3. 归档数超过 `keep_files` 时删除最旧的（压缩后的归档插件不再计数，由这里兜底）
+//! 4. 目录总大小（含当前文件）超过 `max_total_size` 时从最旧的归档开始删除
+//!
+//! 正在写入的当前文件从不改动。策略取自 `runtime::retention()`，修改配置后下一轮生效。
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use tauri::AppHandle;
+
+use super::config::LogConfig;
+use super::runtime;
+use super::viewer;
+
+/// 定期清理的间隔
+const SWEEP_INTERVAL: Duration = Duration::from_secs(10 * 60);
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// 保留策略（`LogConfig` 中与清理相关的字段）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Retention {
+    pub keep_files: usize,
+    pub max_age_days: Option<u32>,
+    pub max_total_size: Option<u64>,
+    pub compress: bool,
+}
+
+impl Retention {
+    /// 与 `LogConfig::default()` 一致
+    pub const DEFAULT: Retention = Retention {
+        keep_files: 10,
+        max_age_days: None,
+        max_total_size: None,
+        compress: true,
+    };
+
+    pub fn from_config(cfg: &LogConfig) -> Self {
+        Self {
+            keep_files: cfg.keep_files,
+            max_age_days: cfg.max_age_days,
+            max_total_size: cfg.max_total_size,
+            compress: cfg.compress_archives,
+        }
+    }
+}
+
+/// 一次清理的结果
+#[derive(Debug, Default)]
+pub struct SweepReport {
+    pub compressed: Vec<String>,
+    pub deleted: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+struct Archive {
+    path: PathBuf,
+    name: String,
+    size: u64,
+    modified: SystemTime,
+}
+
+fn scan(dir: &Path) -> io::Result<(Vec<Archive>, u64)> {
+    let mut archives = Vec::new();
+    let mut current_size = 0;
+    for entry in fs::read_dir(dir)?.flatten() {
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Ok(meta) = entry.metadata() else {
+            continue;
+        };
+        if !meta.is_file() {
+            continue;
+        }
+        if viewer::is_archive(&name) {
+            archives.push(Archive {
+                path: entry.path(),
+                name,
+                size: meta.len(),
+                modified: meta.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            });
+        } else if name.ends_with(".log") {
+            current_size += meta.len();
+        }
+    }
+    // 从新到旧
+    archives.sort_by(|a, b| b.modified.cmp(&a.modified).then_with(|| b.name.cmp(&a.name)));
+    Ok((archives, current_size))
+}
+
+/// 压缩为同目录下的 `{name}.gz`，先写临时文件再改名，成功后删除原文件
+fn compress(archive: &Archive) -> io::Result<Archive> {
+    let target = PathBuf::from(format!("{}.gz", archive.path.display()));
+    let tmp = PathBuf::from(format!("{}.tmp", target.display()));
+    let write = || -> io::Result<()> {
+        let mut input = BufReader::new(File::open(&archive.path)?);
+        let mut encoder = GzEncoder::new(BufWriter::new(File::create(&tmp)?), Compression::default());
+        io::copy(&mut input, &mut encoder)?;
+        let mut out = encoder.finish()?;
+        out.flush()?;
+        let file = out.into_inner().map_err(|e| e.into_error())?;
+        // 保留原修改时间，按时间清理时仍以轮转时刻计算
+        file.set_modified(archive.modified)?;
+        file.sync_all()
+    };
+    if let Err(e) = write() {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    fs::rename(&tmp, &target)?;
+    fs::remove_file(&archive.path)?;
+    Ok(Archive {
+        size: fs::metadata(&target)?.len(),
+        name: format!("{}.gz", archive.name),
+        path: target,
+        modified: archive.modified,
+    })
+}
+
+/// 对日志目录执行一次清理
+pub fn sweep(dir: &Path, policy: &Retention, now: SystemTime) -> SweepReport {
+    let mut report = SweepReport::default();
+    let (archives, current_size) = match scan(dir) {
+        Ok(v) => v,
+        Err(e) => {
+            report.errors.push(format!("读取日志目录 {} 失败: {}", dir.display(), e));
+            return report;
+        }
+    };
+
+    let mut kept = Vec::with_capacity(archives.len());
+    for archive in archives {
+        let expired = policy
+            .max_age_days
+            .and_then(|days| now.checked_sub(DAY * days))
+            .is_some_and(|cutoff| archive.modified < cutoff);
+        let over_count = kept.len() >= policy.keep_files;
+        if expired || over_count {
+            delete(archive, &mut report);
+            continue;
+        }
+        if policy.compress && archive.name.ends_with(".log") {
+            match compress(&archive) {
+                Ok(compressed) => {
+                    report.compressed.push(archive.name);
+                    kept.push(compressed);
+                }
+                Err(e) => {
+                    report.errors.push(format!("压缩 {} 失败: {}", archive.name, e));
+                    kept.push(archive);
+                }
+            }
+            continue;
+        }
+        kept.push(archive);
+    }
+
+    if let Some(cap) = policy.max_total_size {
+        let mut total = current_size + kept.iter().map(|a| a.size).sum::<u64>();
+        while total > cap {
+            let Some(oldest) = kept.pop() else {
+                break;
+            };
+            total -= oldest.size;
+            delete(oldest, &mut report);
+        }
+    }
+    report
+}
+
+fn delete(archive: Archive, report: &mut SweepReport) {
+    match fs::remove_file(&archive.path) {
+        Ok(()) => report.deleted.push(archive.name),
+        Err(e) => report.errors.push(format!("删除 {} 失败: {}", archive.name, e)),
+    }
+}
+
+/// 启动清理线程：立即执行一次，之后按 `SWEEP_INTERVAL` 定期执行
+pub fn spawn_sweeper(app: AppHandle) {
+    let dir = match crate::paths::log_dir(&app) {
+        Ok(dir) => dir,
+        Err(e) => {
+            log::warn!("{}，日志清理未启动", e);
+            return;
+        }
+    };
+    let spawned = std::thread::Builder::new()
+        .name("log-retention".into())
+        .spawn(move || loop {
+            let report = sweep(&dir, &runtime::retention(), SystemTime::now());
+            if !report.compressed.is_empty() || !report.deleted.is_empty() {
+                log::info!(
+                    "日志清理：压缩 {} 个归档，删除 {} 个归档",
+                    report.compressed.len(),
+                    report.deleted.len()
+                );
+            }
+            for e in &report.errors {
+                log::warn!("日志清理：{}", e);
+            }
+            std::thread::sleep(SWEEP_INTERVAL);
+        });
+    if let Err(e) = spawned {
+        log::warn!("日志清理线程启动失败: {}", e);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 在 `now` 之前 `age_days` 天写入一个日志文件
+    fn write_log(dir: &Path, name: &str, content: &str, now: SystemTime, age_days: u32) {
+        let path = dir.join(name);
+        fs::write(&path, content).unwrap();
+        let file = File::options().write(true).open(&path).unwrap();
+        file.set_modified(now - DAY * age_days - Duration::from_secs(60)).unwrap();
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let name = format!("log-retention-{}-{}", name, std::process::id());
+        let dir = std::env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn policy() -> Retention {
+        Retention {
+            compress: false,
+            ..Retention::DEFAULT
+        }
+    }
+
+    #[test]
+    fn compress_keeps_content_and_modified_time() {
+        let dir = temp_dir("compress");
+        let now = SystemTime::now();
+        let line = "[2024-01-03][00:00:00][app][INFO] hello\n";
+        write_log(&dir, "app.log", line, now, 0);
+        write_log(&dir, "app_2024-01-03_00-00-00.log", line, now, 3);
+
+        let report = sweep(&dir, &Retention::DEFAULT, now);
+        assert_eq!(report.compressed, ["app_2024-01-03_00-00-00.log"]);
+        assert!(report.deleted.is_empty() && report.errors.is_empty());
+        let gz = dir.join("app_2024-01-03_00-00-00.log.gz");
+        assert!(!dir.join("app_2024-01-03_00-00-00.log").exists());
+        assert_eq!(crate::gz::read_maybe_gz(&gz).unwrap(), line.as_bytes());
+        let modified = fs::metadata(&gz).unwrap().modified().unwrap();
+        assert!(now.duration_since(modified).unwrap() > DAY * 3);
+        assert_eq!(fs::read_to_string(dir.join("app.log")).unwrap(), line);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn expired_and_surplus_archives_are_deleted() {
+        let dir = temp_dir("count");
+        let now = SystemTime::now();
+        write_log(&dir, "app.log", "current", now, 200);
+        write_log(&dir, "app_2024-01-05_00-00-00.log", "a", now, 1);
+        write_log(&dir, "app_2024-01-04_00-00-00.log.gz", "b", now, 2);
+        write_log(&dir, "app_2024-01-03_00-00-00.log", "c", now, 3);
+        write_log(&dir, "app_2024-01-01_00-00-00.log", "d", now, 100);
+
+        let expire = Retention {
+            max_age_days: Some(90),
+            ..policy()
+        };
+        assert_eq!(sweep(&dir, &expire, now).deleted, ["app_2024-01-01_00-00-00.log"]);
+        let keep_two = Retention {
+            keep_files: 2,
+            ..expire
+        };
+        assert_eq!(sweep(&dir, &keep_two, now).deleted, ["app_2024-01-03_00-00-00.log"]);
+        // 当前文件再旧也不删除
+        assert!(dir.join("app.log").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn total_size_cap_deletes_oldest_first() {
+        let dir = temp_dir("size");
+        let now = SystemTime::now();
+        let kb = "x".repeat(1000);
+        write_log(&dir, "app.log", &kb, now, 0);
+        write_log(&dir, "app_2024-01-05_00-00-00.log", &kb, now, 1);
+        write_log(&dir, "app_2024-01-04_00-00-00.log", &kb, now, 2);
+        write_log(&dir, "app_2024-01-03_00-00-00.log", &kb, now, 3);
+
+        let cap = Retention {
+            max_total_size: Some(2500),
+            ..policy()
+        };
+        let report = sweep(&dir, &cap, now);
+        assert_eq!(
+            report.deleted,
+            ["app_2024-01-03_00-00-00.log", "app_2024-01-04_00-00-00.log"]
+        );
+        // 只剩当前文件也超出上限时不再删除
+        let report = sweep(&dir, &Retention { max_total_size: Some(10), ..cap }, now);
+        assert_eq!(report.deleted, ["app_2024-01-05_00-00-00.log"]);
+        assert!(dir.join("app.log").exists());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
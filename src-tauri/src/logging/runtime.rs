@@ -0,0 +1,128 @@
+//! 日志运行时设置
+//!
+//! `tauri_plugin_log` 的 dispatch 在启动时构建后无法替换，因此插件以 `Trace` 级别构建，
+//! 实际过滤交给这里的可修改设置：全局级别、按模块的级别、是否输出到 stdout、行格式，
+//! 以及清理任务使用的保留策略。
+//! `apply` 修改设置并同步 `log::set_max_level`，无需重启即可生效；
+//! 文件大小、归档数量等在构建 dispatch 时确定的字段仍需重启，由 `LogApplyResult` 报告。
+
+use std::sync::{OnceLock, RwLock};
+
+use log::{LevelFilter, Metadata};
+use serde::Serialize;
+
+use super::config::{LogConfig, LogFormat};
+use super::retention::Retention;
+
+struct Settings {
+    level: LevelFilter,
+    /// 按模块名长度降序
+    modules: Vec<(String, LevelFilter)>,
+    to_stdout: bool,
+    format: LogFormat,
+    retention: Retention,
+}
+
+impl Settings {
+    /// 记录所属模块的级别：`target` 等于模块名或以 `模块名::` 开头时匹配，否则用全局级别
+    fn level_for(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .find(|(m, _)| {
+                target.strip_prefix(m.as_str()).is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+            })
+            .map_or(self.level, |(_, l)| *l)
+    }
+
+    /// 所有级别中最详细的一个，作为 `log::max_level`
+    fn max_level(&self) -> LevelFilter {
+        self.modules.iter().map(|(_, l)| *l).fold(self.level, Ord::max)
+    }
+}
+
+static SETTINGS: RwLock<Settings> = RwLock::new(Settings {
+    level: LevelFilter::Info,
+    modules: Vec::new(),
+    to_stdout: true,
+    format: LogFormat::Text,
+    retention: Retention::DEFAULT,
+});
+
+/// 构建日志插件时使用的配置，用于判断哪些修改需要重启
+static BUILT: OnceLock<LogConfig> = OnceLock::new();
+
+/// `apply` 的结果
+#[derive(Debug, Clone, Serialize)]
+pub struct LogApplyResult {
+    /// 当前生效的全局级别
+    pub level: String,
+    /// 是否仍有字段需要重启后才生效
+    pub restart_required: bool,
+    /// 需要重启才生效的字段名
+    pub restart_fields: Vec<String>,
+}
+
+/// 记录构建插件时的配置并应用运行时设置（`logs::init` 调用）
+pub fn init(cfg: &LogConfig) {
+    let _ = BUILT.set(cfg.clone());
+    apply(cfg);
+}
+
+/// 应用可热更新的字段，返回生效情况
+pub fn apply(cfg: &LogConfig) -> LogApplyResult {
+    let level = cfg.level_filter();
+    let max_level = {
+        let mut s = SETTINGS.write().unwrap();
+        s.level = level;
+        s.modules = cfg.module_filters();
+        s.to_stdout = cfg.to_stdout;
+        s.format = cfg.format;
+        s.retention = Retention::from_config(cfg);
+        s.max_level()
+    };
+    log::set_max_level(max_level);
+
+    let restart_fields: Vec<String> = match BUILT.get() {
+        Some(built) => {
+            let mut fields = Vec::new();
+            if built.max_file_size != cfg.max_file_size {
+                fields.push("max_file_size".to_string());
+            }
+            if built.keep_files != cfg.keep_files {
+                fields.push("keep_files".to_string());
+            }
+            fields
+        }
+        None => Vec::new(),
+    };
+    LogApplyResult {
+        level: level.to_string(),
+        restart_required: !restart_fields.is_empty(),
+        restart_fields,
+    }
+}
+
+/// 日志插件 attach 时会把全局级别设为 dispatch 的级别（Trace），setup 中调用以恢复
+pub fn sync_max_level() {
+    log::set_max_level(SETTINGS.read().unwrap().max_level());
+}
+
+/// 全局过滤：记录级别不高于其模块（或全局）的设置
+pub fn enabled(metadata: &Metadata) -> bool {
+    metadata.level() <= SETTINGS.read().unwrap().level_for(metadata.target())
+}
+
+/// stdout 目标的过滤
+pub fn stdout_enabled() -> bool {
+    SETTINGS.read().unwrap().to_stdout
+}
+
+/// 当前行格式
+pub fn format() -> LogFormat {
+    SETTINGS.read().unwrap().format
+}
+
+/// 当前保留策略
+pub fn retention() -> Retention {
+    SETTINGS.read().unwrap().retention
+}
@@ -0,0 +1,475 @@
+//! 日志查看器后端
+//!
+//! 日志文件由 `tauri_plugin_log` 写在日志目录（见 `paths::log_dir`）下：当前文件为 `{name}.log`，
+//! 按 `RotationStrategy::KeepSome` 轮转出的归档为 `{name}_{YYYY-MM-DD_HH-MM-SS}.log`，
+//! 清理任务（见 `retention.rs`）会把归档压缩为 `.log.gz`，读取时透明解压。
+//! 每行格式为 `[日期][时间][target][LEVEL] 消息`（本地时间），不以 `[` 开头的行视为上一条的续行；
+//! `format` 为 json 时每行是一个对象（见 `format.rs`），两种格式可在同一文件中混排。
+//!
+//! 另外注册一个 dispatch 目标（见 `logs.rs`），在前端开启后把每条新日志推送为 `log:entry`。
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use time::macros::format_description;
+
+use crate::gz;
+
+/// 搜索默认返回的最大条数
+const DEFAULT_SEARCH_LIMIT: usize = 500;
+
+/// 单个日志文件信息
+#[derive(Debug, Clone, Serialize)]
+pub struct LogFileInfo {
+    pub name: String,
+    pub size: u64,
+    pub modified_ms: u64,
+    /// 是否为正在写入的当前文件
+    pub current: bool,
+}
+
+/// 解析后的一条日志
+#[derive(Debug, Clone, Serialize)]
+pub struct LogEntry {
+    pub file: String,
+    /// 该条日志在文件中的起始行号（从 1 开始）
+    pub line: usize,
+    /// `YYYY-MM-DD HH:MM:SS`（本地时间）
+    pub timestamp: String,
+    pub level: String,
+    pub target: String,
+    pub message: String,
+}
+
+/// `read_log_page` 的返回值
+#[derive(Debug, Clone, Serialize)]
+pub struct LogPage {
+    /// 按时间顺序排列
+    pub entries: Vec<LogEntry>,
+    /// 文件中的总条数
+    pub total: usize,
+}
+
+/// 搜索条件；各条件同时满足
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LogQuery {
+    /// 在消息与 target 中查找的文本（不区分大小写）
+    pub text: Option<String>,
+    /// 只保留这些级别（如 `["ERROR", "WARN"]`）；为空表示全部
+    pub levels: Vec<String>,
+    /// 起始时间（含），`YYYY-MM-DD[ HH:MM:SS]`，按字符串前缀比较
+    pub from: Option<String>,
+    /// 结束时间（含），格式同 `from`
+    pub to: Option<String>,
+    /// 只搜索这些文件；为空表示全部
+    pub files: Vec<String>,
+    /// 最多返回条数，默认 500
+    pub limit: Option<usize>,
+}
+
+/// `search_logs` 的返回值
+#[derive(Debug, Clone, Serialize)]
+pub struct LogSearchResult {
+    /// 从新到旧排列
+    pub entries: Vec<LogEntry>,
+    /// 命中数超过 `limit` 被截断
+    pub truncated: bool,
+    pub files_searched: usize,
+}
+
+// ─── 文件 ────────────────────────────────────────────────────────────────────
+
+fn is_log_file(name: &str) -> bool {
+    name.ends_with(".log") || name.ends_with(".log.gz")
+}
+
+/// 是否为轮转归档：文件名以 `_YYYY-MM-DD_HH-MM-SS.log`（或压缩后的 `.log.gz`）结尾
+pub fn is_archive(name: &str) -> bool {
+    let name = name.strip_suffix(".gz").unwrap_or(name);
+    let Some(stem) = name.strip_suffix(".log") else {
+        return false;
+    };
+    let Some(stamp) = stem.len().checked_sub(20).and_then(|i| stem.get(i..)) else {
+        return false;
+    };
+    stamp.bytes().enumerate().all(|(i, b)| match i {
+        0 | 11 => b == b'_',
+        5 | 8 | 14 | 17 => b == b'-',
+        _ => b.is_ascii_digit(),
+    })
+}
+
+/// 列出日志目录下的日志文件：当前文件在前，归档按从新到旧
+pub fn list_files(dir: &Path) -> Result<Vec<LogFileInfo>, String> {
+    let read = fs::read_dir(dir).map_err(|e| format!("读取日志目录 {} 失败: {}", dir.display(), e))?;
+    let mut files: Vec<LogFileInfo> = read
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if !is_log_file(&name) {
+                return None;
+            }
+            let meta = entry.metadata().ok()?;
+            if !meta.is_file() {
+                return None;
+            }
+            let modified_ms = meta
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or(0);
+            Some(LogFileInfo {
+                current: !is_archive(&name),
+                name,
+                size: meta.len(),
+                modified_ms,
+            })
+        })
+        .collect();
+    // 归档名中的时间戳可按字符串排序
+    files.sort_by(|a, b| b.current.cmp(&a.current).then_with(|| b.name.cmp(&a.name)));
+    Ok(files)
+}
+
+/// 把前端传入的文件名解析为日志目录下的路径，拒绝目录穿越
+fn resolve(dir: &Path, name: &str) -> Result<PathBuf, String> {
+    if name.is_empty() || name.contains(['/', '\\']) || name.contains("..") || !is_log_file(name) {
+        return Err(format!("无效的日志文件名: {}", name));
+    }
+    Ok(dir.join(name))
+}
+
+// ─── 解析 ────────────────────────────────────────────────────────────────────
+
+/// 取出开头的 `[...]`，返回内容与剩余部分
+fn bracket(s: &str) -> Option<(&str, &str)> {
+    let rest = s.strip_prefix('[')?;
+    let end = rest.find(']')?;
+    Some((&rest[..end], &rest[end + 1..]))
+}
+
+/// 解析一行 `[日期][时间][target][LEVEL] 消息` 或 JSON 行
+fn parse_line(line: &str) -> Option<(String, String, String, String)> {
+    if line.starts_with('{') {
+        return parse_json_line(line);
+    }
+    let (date, rest) = bracket(line)?;
+    let (time, rest) = bracket(rest)?;
+    let (target, rest) = bracket(rest)?;
+    let (level, rest) = bracket(rest)?;
+    Some((
+        format!("{} {}", date, time),
+        level.to_string(),
+        target.to_string(),
+        rest.strip_prefix(' ').unwrap_or(rest).to_string(),
+    ))
+}
+
+/// JSON 行：时间统一为 `YYYY-MM-DD HH:MM:SS`，便于与文本行一起按时间过滤
+fn parse_json_line(line: &str) -> Option<(String, String, String, String)> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let field = |k: &str| value.get(k).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+    let timestamp = field("timestamp");
+    let timestamp = timestamp.get(..19).unwrap_or(&timestamp).replacen('T', " ", 1);
+    let mut message = field("message");
+    if let Some(kv) = value.get("kv").and_then(|v| v.as_object()) {
+        for (k, v) in kv {
+            match v.as_str() {
+                Some(s) => message.push_str(&format!(" {}={}", k, s)),
+                None => message.push_str(&format!(" {}={}", k, v)),
+            }
+        }
+    }
+    Some((timestamp, field("level"), field("target"), message))
+}
+
+/// 解析整个文件的日志条目（按时间顺序）
+pub fn parse_entries(file: &str, text: &str) -> Vec<LogEntry> {
+    let mut entries: Vec<LogEntry> = Vec::new();
+    for (i, line) in text.lines().enumerate() {
+        match parse_line(line) {
+            Some((timestamp, level, target, message)) => entries.push(LogEntry {
+                file: file.to_string(),
+                line: i + 1,
+                timestamp,
+                level,
+                target,
+                message,
+            }),
+            None => match entries.last_mut() {
+                Some(last) => {
+                    last.message.push('\n');
+                    last.message.push_str(line);
+                }
+                // 文件开头无法识别的行单独成条
+                None if !line.is_empty() => entries.push(LogEntry {
+                    file: file.to_string(),
+                    line: i + 1,
+                    timestamp: String::new(),
+                    level: String::new(),
+                    target: String::new(),
+                    message: line.to_string(),
+                }),
+                None => {}
+            },
+        }
+    }
+    entries
+}
+
+fn read_entries(dir: &Path, name: &str) -> Result<Vec<LogEntry>, String> {
+    let path = resolve(dir, name)?;
+    let bytes =
+        gz::read_maybe_gz(&path).map_err(|e| format!("读取 {} 失败: {}", path.display(), e))?;
+    Ok(parse_entries(name, &String::from_utf8_lossy(&bytes)))
+}
+
+/// 读取一页日志
+///
+/// `offset` 为从最新一条往前跳过的条数（0 即最后一页），返回的条目仍按时间顺序排列。
+pub fn read_page(dir: &Path, name: &str, offset: usize, limit: usize) -> Result<LogPage, String> {
+    let entries = read_entries(dir, name)?;
+    let total = entries.len();
+    let end = total.saturating_sub(offset);
+    let start = end.saturating_sub(limit);
+    Ok(LogPage {
+        entries: entries[start..end].to_vec(),
+        total,
+    })
+}
+
+impl LogQuery {
+    fn matches(&self, entry: &LogEntry, text: Option<&str>) -> bool {
+        if !self.levels.is_empty() && !self.levels.iter().any(|l| l.eq_ignore_ascii_case(&entry.level)) {
+            return false;
+        }
+        if let Some(from) = &self.from {
+            if entry.timestamp.as_str() < from.as_str() {
+                return false;
+            }
+        }
+        if let Some(to) = &self.to {
+            // `to` 可以只写日期：比较同长度前缀，使整天都包含在内
+            let n = to.len().min(entry.timestamp.len());
+            if entry.timestamp.get(..n).unwrap_or(&entry.timestamp) > to.as_str() {
+                return false;
+            }
+        }
+        match text {
+            Some(t) => {
+                entry.message.to_lowercase().contains(t) || entry.target.to_lowercase().contains(t)
+            }
+            None => true,
+        }
+    }
+}
+
+/// 跨当前文件与归档搜索，结果从新到旧
+pub fn search(dir: &Path, query: &LogQuery) -> Result<LogSearchResult, String> {
+    let limit = query.limit.unwrap_or(DEFAULT_SEARCH_LIMIT);
+    let text = query.text.as_deref().map(str::to_lowercase).filter(|t| !t.is_empty());
+    let files: Vec<String> = list_files(dir)?
+        .into_iter()
+        .map(|f| f.name)
+        .filter(|n| query.files.is_empty() || query.files.contains(n))
+        .collect();
+
+    let mut entries = Vec::new();
+    let mut truncated = false;
+    let mut files_searched = 0;
+    'files: for name in &files {
+        files_searched += 1;
+        for entry in read_entries(dir, name)?.into_iter().rev() {
+            if !query.matches(&entry, text.as_deref()) {
+                continue;
+            }
+            if entries.len() == limit {
+                truncated = true;
+                break 'files;
+            }
+            entries.push(entry);
+        }
+    }
+    Ok(LogSearchResult {
+        entries,
+        truncated,
+        files_searched,
+    })
+}
+
+// ─── 实时推送 ────────────────────────────────────────────────────────────────
+
+static STREAM_APP: OnceLock<AppHandle> = OnceLock::new();
+static STREAM_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// 记录推送所用的 AppHandle（setup 中调用）
+pub fn attach_stream(app: AppHandle) {
+    let _ = STREAM_APP.set(app);
+}
+
+/// 开启或关闭 `log:entry` 推送（查看器打开时开启）
+pub fn set_stream(enabled: bool) {
+    STREAM_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// 把 dispatch 目标收到的记录还原为 `LogEntry`
+///
+/// 收到的消息已经过 `format.rs` 格式化，按文件中的行解析回各字段。
+pub fn entry_from_record(record: &log::Record) -> LogEntry {
+    let line = record.args().to_string();
+    let (timestamp, level, target, message) = parse_line(&line).unwrap_or_else(|| {
+        let format = format_description!("[year]-[month]-[day] [hour]:[minute]:[second]");
+        (
+            super::format::now().format(&format).unwrap_or_default(),
+            record.level().to_string(),
+            record.target().to_string(),
+            line,
+        )
+    });
+    LogEntry {
+        file: String::new(),
+        line: 0,
+        timestamp,
+        level,
+        target,
+        message,
+    }
+}
+
+/// dispatch 目标的回调：把日志推送为 `log:entry`
+///
+/// 与插件的 Webview 目标一样在异步任务中 emit；`tauri` 自身的日志不推送，
+/// 避免 emit 过程中产生的日志再次触发推送。
+pub fn publish(record: &log::Record) {
+    if !STREAM_ENABLED.load(Ordering::Relaxed) || record.target().starts_with("tauri") {
+        return;
+    }
+    let Some(app) = STREAM_APP.get() else {
+        return;
+    };
+    let entry = entry_from_record(record);
+    let app = app.clone();
+    tauri::async_runtime::spawn(async move {
+        let _ = app.emit("log:entry", entry);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("log-viewer-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parse_text_lines_with_continuations() {
+        let text = "stray line\n\
+                    [2024-05-02][10:00:00][app::serial][INFO] opened COM3\n\
+                    [2024-05-02][10:00:01][app::serial][ERROR] boom\n  at line 2\n";
+        let entries = parse_entries("a.log", text);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].message, "stray line");
+        assert_eq!(entries[1].timestamp, "2024-05-02 10:00:00");
+        assert_eq!(entries[1].level, "INFO");
+        assert_eq!(entries[1].target, "app::serial");
+        assert_eq!(entries[1].message, "opened COM3");
+        assert_eq!((entries[2].line, entries[2].message.as_str()), (3, "boom\n  at line 2"));
+    }
+
+    #[test]
+    fn parse_json_lines_with_kv() {
+        let line = serde_json::json!({
+            "timestamp": "2024-05-02T10:00:00.123+08:00",
+            "level": "WARN",
+            "target": "app::serial",
+            "message": "reconnecting",
+            "kv": { "port": "COM3", "attempt": 2 },
+        })
+        .to_string();
+        let entries = parse_entries("a.log", &line);
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].timestamp, "2024-05-02 10:00:00");
+        assert_eq!(entries[0].level, "WARN");
+        assert_eq!(entries[0].message, "reconnecting attempt=2 port=COM3");
+    }
+
+    #[test]
+    fn resolve_rejects_path_traversal() {
+        let dir = Path::new("/logs");
+        assert_eq!(resolve(dir, "app.log").unwrap(), dir.join("app.log"));
+        assert!(resolve(dir, "app_2024-05-01_09-00-00.log.gz").is_ok());
+        let bad = ["", "../app.log", "..\\app.log", "sub/app.log", "/etc/app.log"];
+        for bad in bad.into_iter().chain(["..log", "app.txt"]) {
+            assert!(resolve(dir, bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn archives_are_recognized_by_timestamp_suffix() {
+        assert!(is_archive("app_2024-05-01_09-00-00.log"));
+        assert!(is_archive("app_2024-05-01_09-00-00.log.gz"));
+        assert!(!is_archive("app.log"));
+        assert!(!is_archive("app_2024-05-01.log"));
+    }
+
+    #[test]
+    fn list_page_and_search() {
+        let dir = temp_dir("search");
+        fs::write(
+            dir.join("app.log"),
+            "[2024-05-02][10:00:00][app::serial][INFO] opened COM3\n\
+             [2024-05-02][10:00:01][app::serial][ERROR] boom\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.join("app_2024-05-01_09-00-00.log"),
+            "[2024-05-01][09:00:00][tao][WARN] old warning\n",
+        )
+        .unwrap();
+        fs::write(dir.join("other.txt"), "x").unwrap();
+
+        let files = list_files(&dir).unwrap();
+        let names: Vec<_> = files.iter().map(|f| (f.name.as_str(), f.current)).collect();
+        assert_eq!(names, [("app.log", true), ("app_2024-05-01_09-00-00.log", false)]);
+
+        let page = read_page(&dir, "app.log", 0, 1).unwrap();
+        assert_eq!((page.total, page.entries[0].message.as_str()), (2, "boom"));
+        let page = read_page(&dir, "app.log", 1, 5).unwrap();
+        assert_eq!(page.entries[0].message, "opened COM3");
+
+        let query = |q: LogQuery| search(&dir, &q).unwrap();
+        let levels = query(LogQuery {
+            levels: vec!["warn".into(), "error".into()],
+            ..Default::default()
+        });
+        let messages: Vec<_> = levels.entries.iter().map(|e| e.message.as_str()).collect();
+        assert_eq!(messages, ["boom", "old warning"]);
+        let until = query(LogQuery {
+            to: Some("2024-05-01".into()),
+            ..Default::default()
+        });
+        assert_eq!(until.entries.len(), 1);
+        let text = query(LogQuery {
+            text: Some("com3".into()),
+            ..Default::default()
+        });
+        assert_eq!(text.entries.len(), 1);
+        let limited = query(LogQuery {
+            limit: Some(1),
+            ..Default::default()
+        });
+        assert!(limited.truncated);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
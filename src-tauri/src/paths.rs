@@ -0,0 +1,210 @@
+//! 应用目录解析
+//!
+//! 配置目录与数据目录各自按以下顺序确定：
+//!
+//! 1. 命令行参数 `--config-dir <dir>` / `--data-dir <dir>`（也可写成 `--config-dir=<dir>`）
+//! 2. 环境变量 `EASYDESKTOPAPP_CONFIG_DIR` / `EASYDESKTOPAPP_DATA_DIR`
+//! 3. Tauri 的 `app_config_dir` / `app_data_dir`，即 `{系统配置目录}/{identifier}` 与
+//!    `{系统数据目录}/{identifier}`
+//!
+//! 日志配置与应用配置在 Tauri 应用构建之前就要加载，此时还拿不到 `PathResolver`，
+//! 因此 `init` 用 `tauri.conf.json` 中的 identifier 按与 Tauri 相同的规则计算。
+//! 指定了数据目录（便携安装）时日志也写到 `{data_dir}/logs`，否则使用 Tauri 的 `app_log_dir`。
+//!
+//! 旧版本把配置放在 `{系统配置目录}/easydesktopapp` 下；使用默认配置目录时，
+//! 新目录中缺少的配置文件会从旧目录复制过来。
+
+use std::ffi::OsString;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tauri::{AppHandle, Manager, Runtime};
+
+/// 配置目录环境变量
+pub const CONFIG_DIR_ENV: &str = "EASYDESKTOPAPP_CONFIG_DIR";
+/// 数据目录环境变量
+pub const DATA_DIR_ENV: &str = "EASYDESKTOPAPP_DATA_DIR";
+
+const CONFIG_DIR_FLAG: &str = "--config-dir";
+const DATA_DIR_FLAG: &str = "--data-dir";
+
+/// 旧版本配置目录下需要迁移的文件
+const LEGACY_FILES: &[&str] = &["app_config.json", "log_config.json", "serial_config.json"];
+
+#[derive(Debug)]
+struct Paths {
+    config_dir: PathBuf,
+    data_dir: PathBuf,
+    /// 仅在指定了数据目录时设置，否则由 Tauri 决定
+    log_dir: Option<PathBuf>,
+    /// 配置目录是否由参数或环境变量指定
+    config_overridden: bool,
+}
+
+static PATHS: OnceLock<Paths> = OnceLock::new();
+
+/// 在 `run()` 开头调用，先于任何配置的加载
+pub fn init(identifier: &str) {
+    let paths = PATHS.get_or_init(|| resolve(identifier, &cli_args()));
+    if !paths.config_overridden {
+        migrate_legacy(&paths.config_dir);
+    }
+}
+
+/// 配置目录（`app_config.json`、`log_config.json`、`serial_config.json`）
+pub fn config_dir() -> &'static Path {
+    &paths().config_dir
+}
+
+/// 数据目录（串口抓包等运行数据）
+pub fn data_dir() -> &'static Path {
+    &paths().data_dir
+}
+
+/// 配置目录下的文件
+pub fn config_file(name: &str) -> PathBuf {
+    config_dir().join(name)
+}
+
+/// 指定了数据目录时的日志目录；None 表示使用 Tauri 的 `app_log_dir`
+pub fn log_dir_override() -> Option<&'static Path> {
+    paths().log_dir.as_deref()
+}
+
+/// 实际的日志目录
+pub fn log_dir<R: Runtime>(app: &AppHandle<R>) -> Result<PathBuf, String> {
+    match log_dir_override() {
+        Some(dir) => Ok(dir.to_path_buf()),
+        None => app
+            .path()
+            .app_log_dir()
+            .map_err(|e| format!("获取日志目录失败: {}", e)),
+    }
+}
+
+/// 未调用 `init` 时（如单独使用配置模块）以包名作为 identifier
+fn paths() -> &'static Paths {
+    PATHS.get_or_init(|| resolve(env!("CARGO_PKG_NAME"), &cli_args()))
+}
+
+fn cli_args() -> Vec<OsString> {
+    std::env::args_os().skip(1).collect()
+}
+
+fn resolve(identifier: &str, args: &[OsString]) -> Paths {
+    let config_override = override_of(CONFIG_DIR_FLAG, CONFIG_DIR_ENV, args);
+    let data_override = override_of(DATA_DIR_FLAG, DATA_DIR_ENV, args);
+    Paths {
+        config_overridden: config_override.is_some(),
+        config_dir: config_override.unwrap_or_else(|| platform_dir(dirs::config_dir(), identifier)),
+        log_dir: data_override.as_ref().map(|d| d.join("logs")),
+        data_dir: data_override.unwrap_or_else(|| platform_dir(dirs::data_dir(), identifier)),
+    }
+}
+
+/// 命令行参数优先，其次环境变量；空值视为未指定
+fn override_of(flag: &str, env: &str, args: &[OsString]) -> Option<PathBuf> {
+    let prefix = format!("{}=", flag);
+    let mut from_args = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let Some(s) = arg.to_str() else {
+            continue;
+        };
+        if s == flag {
+            from_args = args.next().map(PathBuf::from);
+        } else if let Some(v) = s.strip_prefix(&prefix) {
+            from_args = Some(PathBuf::from(v));
+        }
+    }
+    from_args
+        .or_else(|| std::env::var_os(env).map(PathBuf::from))
+        .filter(|p| !p.as_os_str().is_empty())
+}
+
+/// `{系统目录}/{identifier}`；系统目录不可用时退回可执行文件所在目录
+fn platform_dir(base: Option<PathBuf>, identifier: &str) -> PathBuf {
+    base.map(|d| d.join(identifier)).unwrap_or_else(|| {
+        std::env::current_exe()
+            .ok()
+            .and_then(|exe| exe.parent().map(Path::to_path_buf))
+            .unwrap_or_else(|| PathBuf::from("."))
+    })
+}
+
+/// 把旧配置目录中的配置复制到新目录（新目录已有的文件不覆盖）
+///
+/// 发生在日志系统初始化之前，因此只能输出到 stderr。
+fn migrate_legacy(config_dir: &Path) {
+    let Some(legacy) = dirs::config_dir().map(|d| d.join(env!("CARGO_PKG_NAME"))) else {
+        return;
+    };
+    if legacy == config_dir {
+        return;
+    }
+    for name in LEGACY_FILES {
+        let (from, to) = (legacy.join(name), config_dir.join(name));
+        if !from.is_file() || to.exists() {
+            continue;
+        }
+        let copy = fs::create_dir_all(config_dir).and_then(|_| fs::copy(&from, &to));
+        if let Err(e) = copy {
+            eprintln!("Failed to migrate {} to {}: {}", from.display(), to.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<OsString> {
+        list.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn cli_flag_takes_priority_over_env() {
+        const ENV: &str = "EASYDESKTOPAPP_PATHS_TEST_PRIORITY";
+        std::env::set_var(ENV, "/env/dir");
+        let of = |list: &[&str]| override_of("--dir", ENV, &args(list));
+        assert_eq!(of(&["--dir", "/cli/a"]), Some(PathBuf::from("/cli/a")));
+        assert_eq!(of(&["--dir=/cli/b", "--verbose"]), Some(PathBuf::from("/cli/b")));
+        // 重复指定时以最后一个为准
+        assert_eq!(of(&["--dir", "/cli/a", "--dir=/cli/c"]), Some(PathBuf::from("/cli/c")));
+        assert_eq!(of(&["--other", "/x"]), Some(PathBuf::from("/env/dir")));
+        assert_eq!(of(&[]), Some(PathBuf::from("/env/dir")));
+        std::env::remove_var(ENV);
+        assert_eq!(of(&[]), None);
+        // 缺少值的参数不算指定
+        assert_eq!(of(&["--dir"]), None);
+    }
+
+    #[test]
+    fn empty_values_count_as_unset() {
+        const ENV: &str = "EASYDESKTOPAPP_PATHS_TEST_EMPTY";
+        std::env::set_var(ENV, "");
+        assert_eq!(override_of("--dir", ENV, &args(&[])), None);
+        assert_eq!(override_of("--dir", ENV, &args(&["--dir="])), None);
+        std::env::remove_var(ENV);
+    }
+
+    #[test]
+    fn data_dir_override_moves_logs() {
+        let paths = resolve(
+            "com.test.app",
+            &args(&["--config-dir", "/portable/config", "--data-dir=/portable/data"]),
+        );
+        assert!(paths.config_overridden);
+        assert_eq!(paths.config_dir, Path::new("/portable/config"));
+        assert_eq!(paths.data_dir, Path::new("/portable/data"));
+        assert_eq!(paths.log_dir.as_deref(), Some(Path::new("/portable/data/logs")));
+    }
+
+    #[test]
+    fn platform_dir_appends_identifier() {
+        let base = Some(PathBuf::from("/base"));
+        assert_eq!(platform_dir(base, "com.test.app"), Path::new("/base/com.test.app"));
+        assert!(platform_dir(None, "com.test.app").is_absolute());
+    }
+}
@@ -0,0 +1,308 @@
+//! 配置文件的崩溃安全持久化
+//!
+//! 工位断电时正在写入的配置文件可能被截断。这里统一提供：
+//!
+//! - `save_json`：写临时文件 → fsync → rename 覆盖，写入前把旧文件轮转为
+//!   `{name}.1` … `{name}.{BACKUP_COUNT}` 滚动备份
+//! - `load`：主文件读取或解析失败时，把损坏的文件保留为 `{name}.bak`，
+//!   再按从新到旧的顺序尝试滚动备份，成功则用它恢复主文件
+//!
+//! `AppConfig`、`LogConfig` 与 `SerialConfig` 都通过这里读写。
+//! 本进程写入的内容会记录摘要（`is_own_write`），配置文件监视据此区分外部修改。
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::{self, File};
+use std::hash::{Hash, Hasher};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+
+use serde::Serialize;
+
+/// 保留的滚动备份数量
+pub const BACKUP_COUNT: usize = 3;
+
+/// 临时文件序号，保证并发保存同一文件时各自使用不同的临时文件
+static TMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+/// 各文件的保存锁：轮转备份与替换主文件须整体串行
+static SAVE_LOCKS: LazyLock<Mutex<HashMap<PathBuf, Arc<Mutex<()>>>>> =
+    LazyLock::new(Default::default);
+
+/// 本进程最近一次写入各文件的内容摘要
+static OWN_WRITES: LazyLock<Mutex<HashMap<PathBuf, u64>>> = LazyLock::new(Default::default);
+
+fn digest(contents: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    contents.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// 文件内容是否正是本进程最近一次写入的内容（即不是外部修改）
+pub fn is_own_write(path: &Path, contents: &[u8]) -> bool {
+    OWN_WRITES.lock().unwrap().get(path) == Some(&digest(contents))
+}
+
+/// `{name}.{suffix}`，与主文件同目录
+pub fn sibling(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// 第 `n` 个滚动备份（1 为最新）
+pub fn backup_path(path: &Path, n: usize) -> PathBuf {
+    sibling(path, &n.to_string())
+}
+
+/// 序列化为格式化 JSON 并原子写入，写入前轮转滚动备份
+pub fn save_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    let json = serde_json::to_string_pretty(value).map_err(io::Error::other)?;
+    save_atomic(path, json.as_bytes())
+}
+
+/// 原子写入：内容未变化时跳过；否则轮转备份后写临时文件、fsync、rename
+///
+/// 同一文件的并发保存按路径串行执行。
+pub fn save_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let lock = SAVE_LOCKS.lock().unwrap().entry(path.to_path_buf()).or_default().clone();
+    let _guard = lock.lock().unwrap();
+    if fs::read(path).is_ok_and(|old| old == contents) {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    rotate_backups(path)?;
+    replace_atomic(path, contents)
+}
+
+/// 把当前主文件复制为 `.1`，已有备份依次后移，超出 `BACKUP_COUNT` 的丢弃
+fn rotate_backups(path: &Path) -> io::Result<()> {
+    if !path.exists() {
+        return Ok(());
+    }
+    for n in (1..BACKUP_COUNT).rev() {
+        let from = backup_path(path, n);
+        if from.exists() {
+            fs::rename(&from, backup_path(path, n + 1))?;
+        }
+    }
+    // 复制而非移动：保证任意时刻主文件都存在
+    fs::copy(path, backup_path(path, 1))?;
+    Ok(())
+}
+
+/// 写临时文件并 fsync 后 rename 覆盖目标，再 fsync 目录使 rename 落盘
+///
+/// 临时文件名带进程号与序号（`{name}.{pid}.{seq}.tmp`），并发保存互不覆盖。
+fn replace_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let seq = TMP_SEQ.fetch_add(1, Ordering::Relaxed);
+    let tmp = sibling(path, &format!("{}.{}.tmp", std::process::id(), seq));
+    let written = File::create(&tmp).and_then(|mut file| {
+        file.write_all(contents)?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|_| fs::rename(&tmp, path)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(e);
+    }
+    OWN_WRITES.lock().unwrap().insert(path.to_path_buf(), digest(contents));
+    #[cfg(unix)]
+    if let Some(parent) = path.parent() {
+        File::open(parent)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// `load` 的结果
+#[derive(Debug)]
+pub enum LoadOutcome<T> {
+    /// 主文件不存在（首次运行或被手动删除；原子写入不会产生缺失的主文件，因此不从备份恢复）
+    Missing,
+    /// 主文件正常加载
+    Loaded(T),
+    /// 主文件不可用，已从滚动备份恢复（损坏的主文件保留在 `broken`）
+    Restored {
+        value: T,
+        backup: PathBuf,
+        broken: Option<PathBuf>,
+        error: String,
+    },
+    /// 主文件与所有备份都不可用；损坏的主文件保留在 `broken`
+    Failed {
+        broken: Option<PathBuf>,
+        error: String,
+    },
+}
+
+/// 读取并解析配置文件，失败时按从新到旧尝试滚动备份
+///
+/// `parse` 失败即视为文件损坏（调用方可在其中做版本迁移与语义校验）。
+pub fn load<T>(path: &Path, parse: impl Fn(&str) -> Result<T, String>) -> LoadOutcome<T> {
+    let (bytes, error) = match fs::read(path) {
+        Ok(bytes) => {
+            let parsed = std::str::from_utf8(&bytes)
+                .map_err(|e| format!("不是有效的 UTF-8: {}", e))
+                .and_then(&parse);
+            match parsed {
+                Ok(value) => return LoadOutcome::Loaded(value),
+                Err(e) => (Some(bytes), format!("解析 {} 失败: {}", path.display(), e)),
+            }
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return LoadOutcome::Missing,
+        Err(e) => (None, format!("读取 {} 失败: {}", path.display(), e)),
+    };
+
+    let broken = bytes.as_deref().and_then(|b| preserve_broken(path, b));
+
+    for n in 1..=BACKUP_COUNT {
+        let backup = backup_path(path, n);
+        let Ok(text) = fs::read_to_string(&backup) else {
+            continue;
+        };
+        let Ok(value) = parse(&text) else {
+            continue;
+        };
+        // 恢复时不轮转备份，避免损坏的主文件挤掉好的备份
+        if let Err(e) = replace_atomic(path, text.as_bytes()) {
+            log::warn!("用备份 {} 恢复 {} 失败: {}", backup.display(), path.display(), e);
+        }
+        return LoadOutcome::Restored {
+            value,
+            backup,
+            broken,
+            error,
+        };
+    }
+
+    LoadOutcome::Failed { broken, error }
+}
+
+/// 把无法解析的主文件保留为 `{name}.bak`；内容与已有 `.bak` 相同时跳过
+fn preserve_broken(path: &Path, bytes: &[u8]) -> Option<PathBuf> {
+    let bak = sibling(path, "bak");
+    if fs::read(&bak).is_ok_and(|old| old == bytes) {
+        return Some(bak);
+    }
+    match fs::write(&bak, bytes) {
+        Ok(()) => Some(bak),
+        Err(e) => {
+            log::warn!("备份损坏的配置文件 {} 失败: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_file(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("persist-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("config.json")
+    }
+
+    fn parse_number(text: &str) -> Result<u32, String> {
+        text.trim().parse().map_err(|e: std::num::ParseIntError| e.to_string())
+    }
+
+    fn read(path: &Path) -> String {
+        fs::read_to_string(path).unwrap()
+    }
+
+    #[test]
+    fn save_rotates_backups_up_to_backup_count() {
+        let path = temp_file("rotate");
+        for n in 1..=5 {
+            save_atomic(&path, n.to_string().as_bytes()).unwrap();
+        }
+        // 内容未变化时不轮转
+        save_atomic(&path, b"5").unwrap();
+
+        assert_eq!(read(&path), "5");
+        assert_eq!(read(&backup_path(&path, 1)), "4");
+        assert_eq!(read(&backup_path(&path, 2)), "3");
+        assert_eq!(read(&backup_path(&path, BACKUP_COUNT)), "2");
+        assert!(!backup_path(&path, BACKUP_COUNT + 1).exists());
+        assert!(is_own_write(&path, b"5"));
+        assert!(!is_own_write(&path, b"4"));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn concurrent_saves_leave_no_temp_files() {
+        let path = temp_file("concurrent");
+        let writers: Vec<_> = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for j in 0..20 {
+                        save_atomic(&path, format!("{i}-{j}").as_bytes()).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for w in writers {
+            w.join().unwrap();
+        }
+        let leftovers: Vec<_> = fs::read_dir(path.parent().unwrap())
+            .unwrap()
+            .map(|e| e.unwrap().file_name())
+            .filter(|n| n.to_string_lossy().ends_with(".tmp"))
+            .collect();
+        assert!(leftovers.is_empty(), "{:?}", leftovers);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn load_restores_from_newest_valid_backup() {
+        let path = temp_file("restore");
+        for n in ["1", "2", "3"] {
+            save_atomic(&path, n.as_bytes()).unwrap();
+        }
+        fs::write(backup_path(&path, 1), "broken backup").unwrap();
+        fs::write(&path, "broken").unwrap();
+
+        match load(&path, parse_number) {
+            LoadOutcome::Restored {
+                value,
+                backup,
+                broken,
+                ..
+            } => {
+                assert_eq!(value, 1);
+                assert_eq!(backup, backup_path(&path, 2));
+                assert_eq!(broken, Some(sibling(&path, "bak")));
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+        assert_eq!(read(&path), "1");
+        assert_eq!(read(&sibling(&path, "bak")), "broken");
+        // 恢复不轮转备份
+        assert_eq!(read(&backup_path(&path, 1)), "broken backup");
+        assert!(matches!(load(&path, parse_number), LoadOutcome::Loaded(1)));
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn load_fails_without_usable_backup() {
+        let path = temp_file("failed");
+        assert!(matches!(load(&path, parse_number), LoadOutcome::Missing));
+
+        fs::write(&path, "broken").unwrap();
+        fs::write(backup_path(&path, 1), "also broken").unwrap();
+        match load(&path, parse_number) {
+            LoadOutcome::Failed { broken, .. } => assert_eq!(broken, Some(sibling(&path, "bak"))),
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+        // 损坏的主文件保持原样，留给用户处理
+        assert_eq!(read(&path), "broken");
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+}
@@ -0,0 +1,196 @@
+//! 串口收发抓包
+//!
+//! 按设备把读到的原始字节（RX，分帧前）与写出的字节（TX）记录为 JSON Lines，
+//! 首行保存设备配置快照。回放时把 RX 重新送入分帧器与 `serial:data` 推送流程，
+//! 无需硬件即可在开发机上复现现场问题。
+
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use serde::{Deserialize, Serialize};
+
+use super::codec::{parse_hex, to_hex};
+use super::config::SerialDeviceConfig;
+
+/// 抓包文件中的一行
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum CaptureRecord {
+    /// 文件首行：抓包开始时的设备配置，回放时据此分帧
+    Header {
+        device: Box<SerialDeviceConfig>,
+        started_ms: u64,
+    },
+    /// 从设备读到的原始字节（十六进制，空格分隔）
+    Rx { ts_ms: u64, data: String },
+    /// 写往设备的字节（十六进制，空格分隔）
+    Tx { ts_ms: u64, data: String },
+}
+
+/// 抓包文件默认目录：`{data_dir}/captures`（见 `paths`）
+pub fn capture_dir() -> PathBuf {
+    crate::paths::data_dir().join("captures")
+}
+
+/// 为设备生成新的抓包文件路径：`{capture_dir}/{device_id}_{ms}.jsonl`
+pub fn default_capture_path(device_id: &str, now_ms: u64) -> PathBuf {
+    capture_dir().join(format!("{}_{}.jsonl", device_id, now_ms))
+}
+
+impl CaptureRecord {
+    /// RX/TX 记录的原始字节；Header 返回 None
+    pub fn bytes(&self) -> Option<Result<Vec<u8>, String>> {
+        match self {
+            CaptureRecord::Rx { data, .. } | CaptureRecord::Tx { data, .. } => Some(parse_hex(data)),
+            CaptureRecord::Header { .. } => None,
+        }
+    }
+}
+
+// ─── 录制 ────────────────────────────────────────────────────────────────────
+
+struct ActiveCapture {
+    path: PathBuf,
+    writer: BufWriter<File>,
+}
+
+/// 单个设备的抓包录制器，读线程与写路径共享；未开启录制时所有记录调用为空操作
+#[derive(Default)]
+pub struct Recorder {
+    active: Mutex<Option<ActiveCapture>>,
+}
+
+impl Recorder {
+    /// 开始录制到 `path`（已在录制时先结束旧文件）
+    pub fn start(&self, device: &SerialDeviceConfig, path: PathBuf, now_ms: u64) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut writer = BufWriter::new(File::create(&path)?);
+        write_record(
+            &mut writer,
+            &CaptureRecord::Header {
+                device: Box::new(device.clone()),
+                started_ms: now_ms,
+            },
+        )?;
+        log::info!("[抓包 {}] 开始录制: {}", device.device_id, path.display());
+        *self.active.lock().unwrap() = Some(ActiveCapture { path, writer });
+        Ok(())
+    }
+
+    /// 结束录制，返回抓包文件路径
+    pub fn stop(&self) -> Option<PathBuf> {
+        let mut active = self.active.lock().unwrap().take()?;
+        let _ = active.writer.flush();
+        Some(active.path)
+    }
+
+    pub fn record_rx(&self, ts_ms: u64, data: &[u8]) {
+        self.record(CaptureRecord::Rx { ts_ms, data: to_hex(data) });
+    }
+
+    pub fn record_tx(&self, ts_ms: u64, data: &[u8]) {
+        self.record(CaptureRecord::Tx { ts_ms, data: to_hex(data) });
+    }
+
+    fn record(&self, rec: CaptureRecord) {
+        let mut guard = self.active.lock().unwrap();
+        let Some(active) = guard.as_mut() else {
+            return;
+        };
+        // 写盘失败时停止录制，避免每帧都刷错误日志
+        if let Err(e) = write_record(&mut active.writer, &rec) {
+            log::error!("抓包写入失败，停止录制 {}: {}", active.path.display(), e);
+            *guard = None;
+        }
+    }
+}
+
+/// 每条记录单独 flush，进程异常退出时也能保留已抓到的数据
+fn write_record(writer: &mut BufWriter<File>, rec: &CaptureRecord) -> io::Result<()> {
+    serde_json::to_writer(&mut *writer, rec).map_err(io::Error::other)?;
+    writer.write_all(b"\n")?;
+    writer.flush()
+}
+
+// ─── 读取 ────────────────────────────────────────────────────────────────────
+
+/// 读取抓包文件，返回首行设备配置与其后的 RX/TX 记录
+pub fn read_capture(path: &Path) -> Result<(SerialDeviceConfig, Vec<CaptureRecord>), String> {
+    let file = File::open(path).map_err(|e| format!("打开抓包文件失败: {}", e))?;
+    let mut device = None;
+    let mut records = Vec::new();
+    for (i, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("读取抓包文件失败: {}", e))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let rec: CaptureRecord = serde_json::from_str(&line)
+            .map_err(|e| format!("抓包文件第 {} 行格式错误: {}", i + 1, e))?;
+        match rec {
+            CaptureRecord::Header { device: d, .. } if device.is_none() => device = Some(*d),
+            CaptureRecord::Header { .. } => {}
+            other => records.push(other),
+        }
+    }
+    let device = device.ok_or_else(|| "抓包文件缺少 header 记录".to_string())?;
+    device
+        .framing
+        .validate()
+        .map_err(|e| format!("抓包文件中的分帧配置无效: {}", e))?;
+    Ok((device, records))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn capture_file(name: &str, lines: &[serde_json::Value]) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("capture-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let text: Vec<String> = lines.iter().map(|l| l.to_string()).collect();
+        fs::write(&path, text.join("\n")).unwrap();
+        path
+    }
+
+    fn header(framing: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "type": "header",
+            "started_ms": 1,
+            "device": {
+                "device_id": "scanner",
+                "name": "scanner",
+                "role": "scanner",
+                "port": "COM3",
+                "baud_rate": 9600,
+                "data_bits": 8,
+                "stop_bits": 1,
+                "parity": "None",
+                "enabled": true,
+                "framing": framing,
+            },
+        })
+    }
+
+    #[test]
+    fn read_capture_rejects_invalid_framing() {
+        let rx = serde_json::json!({ "type": "rx", "ts_ms": 2, "data": "01 02" });
+        let path = capture_file(
+            "invalid.jsonl",
+            &[header(serde_json::json!({ "type": "lengthPrefixed", "length_size": 0 })), rx.clone()],
+        );
+        assert!(read_capture(&path).is_err());
+
+        let path = capture_file(
+            "valid.jsonl",
+            &[header(serde_json::json!({ "type": "lengthPrefixed", "length_size": 1 })), rx],
+        );
+        let (device, records) = read_capture(&path).unwrap();
+        assert_eq!(device.device_id, "scanner");
+        assert_eq!(records.len(), 1);
+    }
+}
@@ -0,0 +1,132 @@
+//! 串口协议常用校验算法
+
+use serde::{Deserialize, Serialize};
+
+/// 累加和：所有字节相加取低 8 位
+pub fn sum8(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |acc, &b| acc.wrapping_add(b))
+}
+
+/// BCC（Block Check Character）：所有字节按位异或
+pub fn bcc(data: &[u8]) -> u8 {
+    data.iter().fold(0u8, |acc, &b| acc ^ b)
+}
+
+/// CRC-16/MODBUS（多项式 0xA001 反射，初值 0xFFFF），帧内按低字节在前发送
+pub fn crc16_modbus(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &b in data {
+        crc ^= b as u16;
+        for _ in 0..8 {
+            if crc & 1 != 0 {
+                crc = (crc >> 1) ^ 0xA001;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+    crc
+}
+
+/// CRC-16/CCITT-FALSE（多项式 0x1021，初值 0xFFFF，不反射），帧内按高字节在前发送
+pub fn crc16_ccitt(data: &[u8]) -> u16 {
+    let mut crc = 0xFFFFu16;
+    for &b in data {
+        crc ^= (b as u16) << 8;
+        for _ in 0..8 {
+            if crc & 0x8000 != 0 {
+                crc = (crc << 1) ^ 0x1021;
+            } else {
+                crc <<= 1;
+            }
+        }
+    }
+    crc
+}
+
+/// 发送指令时自动追加的校验类型
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ChecksumKind {
+    /// 不追加校验
+    #[default]
+    None,
+    /// 1 字节累加和
+    Sum8,
+    /// 1 字节异或（BCC）
+    Xor,
+    /// 2 字节 CRC-16/MODBUS，低字节在前
+    Crc16Modbus,
+    /// 2 字节 CRC-16/CCITT-FALSE，高字节在前
+    Crc16Ccitt,
+}
+
+/// 设备级校验配置：`kind` 决定算法，`start` 为参与计算的起始偏移（跳过帧头，如 `AA 55`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ChecksumConfig {
+    pub kind: ChecksumKind,
+    pub start: usize,
+}
+
+impl ChecksumConfig {
+    /// 计算 `data[start..]` 的校验并追加到末尾；`start` 超出长度时按空数据计算
+    pub fn append(&self, data: &mut Vec<u8>) {
+        let body = data.get(self.start..).unwrap_or(&[]);
+        match self.kind {
+            ChecksumKind::None => {}
+            ChecksumKind::Sum8 => data.push(sum8(body)),
+            ChecksumKind::Xor => data.push(bcc(body)),
+            ChecksumKind::Crc16Modbus => {
+                let crc = crc16_modbus(body).to_le_bytes();
+                data.extend_from_slice(&crc);
+            }
+            ChecksumKind::Crc16Ccitt => {
+                let crc = crc16_ccitt(body).to_be_bytes();
+                data.extend_from_slice(&crc);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 各算法的标准校验值（输入 "123456789"）
+    #[test]
+    fn algorithms_match_check_values() {
+        assert_eq!(crc16_modbus(b"123456789"), 0x4B37);
+        assert_eq!(crc16_ccitt(b"123456789"), 0x29B1);
+        assert_eq!(sum8(&[0xFF, 0x02]), 0x01);
+        assert_eq!(bcc(&[0x01, 0x02, 0x04]), 0x07);
+    }
+
+    #[test]
+    fn append_skips_header_and_uses_wire_byte_order() {
+        let mut data = vec![0xAA, 0x55, 0x01, 0x02];
+        ChecksumConfig { kind: ChecksumKind::Sum8, start: 2 }.append(&mut data);
+        assert_eq!(data, [0xAA, 0x55, 0x01, 0x02, 0x03]);
+
+        let mut data = b"123456789".to_vec();
+        ChecksumConfig { kind: ChecksumKind::Crc16Modbus, start: 0 }.append(&mut data);
+        assert_eq!(&data[9..], [0x37, 0x4B]);
+
+        let mut data = b"123456789".to_vec();
+        ChecksumConfig { kind: ChecksumKind::Crc16Ccitt, start: 0 }.append(&mut data);
+        assert_eq!(&data[9..], [0x29, 0xB1]);
+
+        // start 超出长度时按空数据计算
+        let mut data = vec![0x01];
+        ChecksumConfig { kind: ChecksumKind::Xor, start: 5 }.append(&mut data);
+        assert_eq!(data, [0x01, 0x00]);
+    }
+
+    #[test]
+    fn config_deserializes_with_defaults() {
+        let cfg: ChecksumConfig = serde_json::from_str(r#"{ "kind": "crc16Modbus" }"#).unwrap();
+        assert_eq!(cfg, ChecksumConfig { kind: ChecksumKind::Crc16Modbus, start: 0 });
+        let cfg: ChecksumConfig = serde_json::from_str("{}").unwrap();
+        assert_eq!(cfg.kind, ChecksumKind::None);
+    }
+}
@@ -0,0 +1,167 @@
+//! 串口负载的文本编码
+//!
+//! 发送方向把前端输入的字符串解析为字节，接收方向把帧渲染为 `serial:data` 的 `data_str`，
+//! 两个方向共用同一组编码：
+//!
+//! - `text`：UTF-8 原样（接收时去除首尾空白，非 UTF-8 为 None）
+//! - `hex`：十六进制字节，如 `"AA 55 01 FF"`，也接受 `"AA5501FF"`、`"0xAA,0x55"`
+//! - `escaped`：ASCII 加 C 风格转义，如 `"SET 1\r\n"`、`"\x02DATA\x03"`
+
+use serde::{Deserialize, Serialize};
+
+/// 负载编码
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum Encoding {
+    #[default]
+    Text,
+    Hex,
+    Escaped,
+}
+
+impl Encoding {
+    /// 把字符串按本编码解析为字节
+    pub fn parse(self, s: &str) -> Result<Vec<u8>, String> {
+        match self {
+            Encoding::Text => Ok(s.as_bytes().to_vec()),
+            Encoding::Hex => parse_hex(s),
+            Encoding::Escaped => parse_escaped(s),
+        }
+    }
+
+    /// 把字节渲染为字符串；`text` 编码下非 UTF-8 或全空白时为 None
+    pub fn format(self, data: &[u8]) -> Option<String> {
+        match self {
+            Encoding::Text => std::str::from_utf8(data)
+                .ok()
+                .map(|s| s.trim().to_string())
+                .filter(|s| !s.is_empty()),
+            Encoding::Hex => Some(to_hex(data)),
+            Encoding::Escaped => Some(to_escaped(data)),
+        }
+    }
+}
+
+/// 大写十六进制，空格分隔：`AA 55 01`
+pub fn to_hex(data: &[u8]) -> String {
+    data.iter()
+        .map(|b| format!("{b:02X}"))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// 解析十六进制字节串；分隔符可为空白、逗号或省略，单个字节可带 `0x` 前缀
+pub fn parse_hex(s: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::new();
+    for token in s.split(|c: char| c.is_whitespace() || c == ',').filter(|t| !t.is_empty()) {
+        let digits = token
+            .strip_prefix("0x")
+            .or_else(|| token.strip_prefix("0X"))
+            .unwrap_or(token);
+        if digits.is_empty() || digits.len() % 2 != 0 || !digits.is_ascii() {
+            return Err(format!("非法十六进制字节: {}", token));
+        }
+        for i in (0..digits.len()).step_by(2) {
+            let byte = u8::from_str_radix(&digits[i..i + 2], 16)
+                .map_err(|_| format!("非法十六进制字节: {}", token))?;
+            out.push(byte);
+        }
+    }
+    Ok(out)
+}
+
+/// 解析 C 风格转义：`\r \n \t \0 \\ \" \' \xNN`；其余字符按 UTF-8 编码
+pub fn parse_escaped(s: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buf = [0u8; 4];
+            out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            continue;
+        }
+        let esc = chars.next().ok_or("转义序列不完整: 末尾的 \\")?;
+        let byte = match esc {
+            'r' => b'\r',
+            'n' => b'\n',
+            't' => b'\t',
+            '0' => 0,
+            '\\' => b'\\',
+            '"' => b'"',
+            '\'' => b'\'',
+            'x' => {
+                let hex: String = chars.by_ref().take(2).collect();
+                if hex.len() != 2 {
+                    return Err(format!("转义序列不完整: \\x{}", hex));
+                }
+                u8::from_str_radix(&hex, 16).map_err(|_| format!("非法转义序列: \\x{}", hex))?
+            }
+            other => return Err(format!("不支持的转义序列: \\{}", other)),
+        };
+        out.push(byte);
+    }
+    Ok(out)
+}
+
+/// 可打印 ASCII 原样输出，其余字节转义为 `\r \n \t \0 \\ \xNN`
+pub fn to_escaped(data: &[u8]) -> String {
+    let mut out = String::with_capacity(data.len());
+    for &b in data {
+        match b {
+            b'\r' => out.push_str("\\r"),
+            b'\n' => out.push_str("\\n"),
+            b'\t' => out.push_str("\\t"),
+            0 => out.push_str("\\0"),
+            b'\\' => out.push_str("\\\\"),
+            0x20..=0x7E => out.push(b as char),
+            _ => out.push_str(&format!("\\x{b:02X}")),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_hex_accepts_common_separators() {
+        let expected = [0xAA, 0x55, 0x01, 0xFF];
+        assert_eq!(parse_hex("AA 55 01 FF").unwrap(), expected);
+        assert_eq!(parse_hex("aa5501ff").unwrap(), expected);
+        assert_eq!(parse_hex("0xAA,0x55, 0X01 FF").unwrap(), expected);
+        assert!(parse_hex("").unwrap().is_empty());
+    }
+
+    #[test]
+    fn parse_hex_rejects_malformed_bytes() {
+        for bad in ["A", "AA5", "GG", "0x", "é1"] {
+            assert!(parse_hex(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn escaped_round_trips() {
+        assert_eq!(parse_escaped("SET 1\\r\\n").unwrap(), b"SET 1\r\n");
+        assert_eq!(parse_escaped("\\x02D\\x03").unwrap(), [0x02, b'D', 0x03]);
+        let data = b"\x02A\r\n\t\0\\\xff";
+        assert_eq!(to_escaped(data), "\\x02A\\r\\n\\t\\0\\\\\\xFF");
+        assert_eq!(parse_escaped(&to_escaped(data)).unwrap(), data);
+    }
+
+    #[test]
+    fn parse_escaped_rejects_incomplete_sequences() {
+        for bad in ["\\x2", "a\\", "\\q", "\\xZZ"] {
+            assert!(parse_escaped(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn format_text_trims_and_rejects_non_utf8() {
+        assert_eq!(Encoding::Text.format(b" SN001\r\n"), Some("SN001".into()));
+        assert_eq!(Encoding::Text.format(b"\r\n"), None);
+        assert_eq!(Encoding::Text.format(&[0xFF, 0xFE]), None);
+        assert_eq!(Encoding::Hex.format(&[0xAA, 0x01]), Some("AA 01".into()));
+        assert_eq!(Encoding::Escaped.format(b"A\r"), Some("A\\r".into()));
+    }
+}
@@ -1,43 +1,91 @@
-use tauri::{AppHandle, State};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
+use tauri::{AppHandle, Emitter, State};
+
+use serde::Serialize;
+
+use crate::config::{emit_changed, ConfigSection};
+
+use super::codec::Encoding;
 use super::config::SerialConfig;
+use super::manager::{ConfigApplyResult, SerialManager};
+use super::modbus::{self, ModbusError, ModbusMaster};
+use super::ports::{self, PortInfo};
+use super::stats::DeviceStats;
+use super::transact::{Expect, TransactError};
 use super::SerialState;
 
 /// 获取当前串口配置
+///
+/// 返回管理器正在使用的配置；磁盘文件只在启动时加载一次。
+/// 启动加载时的解析失败、版本迁移等警告在此重新推送 `serial:config-warning`，
+/// 直到配置被重新保存或应用。
 #[tauri::command]
-pub async fn get_serial_config() -> Result<SerialConfig, String> {
+pub async fn get_serial_config(
+    state: State<'_, SerialState>,
+    app: AppHandle,
+) -> Result<SerialConfig, String> {
     log::debug!("get_serial_config");
-    Ok(SerialConfig::load_or_default())
+    for w in state.config_warnings.lock().unwrap().iter() {
+        let _ = app.emit("serial:config-warning", w);
+    }
+    Ok(state.manager.config())
 }
 
-/// 保存串口配置并重启所有监听线程
+/// 保存串口配置并按差异调整监听线程
 ///
-/// 调用后后端会：校验 → 持久化 → 重启线程。
+/// 调用后后端会：校验 → 持久化 → 与运行中的配置比对，只启停/重启有变化的设备。
 /// 前端只需调用一次，无需额外触发 `start_serial_listeners`。
 #[tauri::command]
 pub async fn update_serial_config(
     config: SerialConfig,
     state: State<'_, SerialState>,
     app: AppHandle,
-) -> Result<(), String> {
+) -> Result<ConfigApplyResult, String> {
     config.validate()?;
     config
         .save()
         .map_err(|e| format!("保存配置失败: {}", e))?;
     log::info!("串口配置已更新，共 {} 个设备", config.devices.len());
-    state.manager.restart_all(&config, app);
-    Ok(())
+    state.config_warnings.lock().unwrap().clear();
+    emit_changed(&app, ConfigSection::Serial, &config);
+    let manager = state.manager.clone();
+    let result = tauri::async_runtime::spawn_blocking(move || manager.apply_config(&config, app))
+        .await
+        .map_err(|e| e.to_string())?;
+    log::info!(
+        "串口配置已应用: 启动 {:?}，停止 {:?}，重启 {:?}",
+        result.started,
+        result.stopped,
+        result.restarted
+    );
+    if !result.stuck.is_empty() {
+        log::warn!("以下设备的旧监听线程未能按时退出，暂未重新启动: {:?}", result.stuck);
+    }
+    Ok(result)
 }
 
 /// 重置串口配置为空（停止所有监听）
 #[tauri::command]
-pub async fn reset_serial_config(state: State<'_, SerialState>) -> Result<SerialConfig, String> {
-    state.manager.stop_all();
+pub async fn reset_serial_config(
+    state: State<'_, SerialState>,
+    app: AppHandle,
+) -> Result<SerialConfig, String> {
     let empty = SerialConfig::default();
+    let manager = state.manager.clone();
+    let (config, handle) = (empty.clone(), app.clone());
+    tauri::async_runtime::spawn_blocking(move || manager.apply_config(&config, handle))
+        .await
+        .map_err(|e| e.to_string())?;
     empty
         .save()
         .map_err(|e| format!("保存配置失败: {}", e))?;
     log::info!("串口配置已重置");
+    state.config_warnings.lock().unwrap().clear();
+    emit_changed(&app, ConfigSection::Serial, &empty);
     Ok(empty)
 }
 
@@ -49,7 +97,7 @@ pub async fn start_serial_listeners(
     state: State<'_, SerialState>,
     app: AppHandle,
 ) -> Result<(), String> {
-    let config = SerialConfig::load_or_default();
+    let config = state.manager.config();
     state.manager.start_all(&config, app);
     log::info!("串口监听已启动");
     Ok(())
@@ -58,11 +106,72 @@ pub async fn start_serial_listeners(
 /// 手动停止所有监听线程
 #[tauri::command]
 pub async fn stop_serial_listeners(state: State<'_, SerialState>) -> Result<(), String> {
-    state.manager.stop_all();
+    let manager = state.manager.clone();
+    let stuck = tauri::async_runtime::spawn_blocking(move || manager.stop_all())
+        .await
+        .map_err(|e| e.to_string())?;
+    if !stuck.is_empty() {
+        return Err(format!("以下设备的监听线程未能按时退出: {}", stuck.join(", ")));
+    }
     log::info!("串口监听已停止");
     Ok(())
 }
 
+/// 按已保存的配置启动单个设备（不检查 `enabled`，已在运行时返回错误）
+#[tauri::command]
+pub async fn start_serial_device(
+    device_id: String,
+    state: State<'_, SerialState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let config = state.manager.config();
+    let device = config
+        .find_device(&device_id)
+        .ok_or_else(|| format!("设备 {} 不存在", device_id))?;
+    state.manager.start_device(device, app)?;
+    log::info!("串口设备 {} 已启动", device_id);
+    Ok(())
+}
+
+/// 停止单个设备并等待其释放端口，其余设备不受影响
+///
+/// 监听线程未能在 `STOP_TIMEOUT` 内退出时返回错误，可稍后重试。
+#[tauri::command]
+pub async fn stop_serial_device(device_id: String, state: State<'_, SerialState>) -> Result<(), String> {
+    let manager = state.manager.clone();
+    let id = device_id.clone();
+    let stopped = tauri::async_runtime::spawn_blocking(move || manager.stop_device(&id))
+        .await
+        .map_err(|e| e.to_string())??;
+    if !stopped {
+        return Err(format!("设备 {} 未在运行", device_id));
+    }
+    log::info!("串口设备 {} 已停止", device_id);
+    Ok(())
+}
+
+/// 按已保存的配置重启单个设备（未运行时直接启动）
+///
+/// 旧线程未能按时退出时不重新打开端口，返回错误。
+#[tauri::command]
+pub async fn restart_serial_device(
+    device_id: String,
+    state: State<'_, SerialState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let config = state.manager.config();
+    let device = config
+        .find_device(&device_id)
+        .ok_or_else(|| format!("设备 {} 不存在", device_id))?
+        .clone();
+    let manager = state.manager.clone();
+    tauri::async_runtime::spawn_blocking(move || manager.restart_device(&device, app))
+        .await
+        .map_err(|e| e.to_string())??;
+    log::info!("串口设备 {} 已重启", device_id);
+    Ok(())
+}
+
 /// 向指定设备的串口写入字节数据
 ///
 /// 适用于向光源、机械臂等设备发送控制指令。
@@ -75,12 +184,349 @@ pub async fn send_serial_command(
     state.manager.send_to_device(&device_id, data)
 }
 
-/// 列出当前系统可用的串口端口名
+/// 按编码解析指令字符串，追加设备配置的校验后发送，返回实际写出的字节
+///
+/// `encoding`：`text`（默认）| `hex`（如 `"AA 55 01 FF"`）| `escaped`（如 `"SET 1\r\n"`）。
+#[tauri::command]
+pub async fn send_serial_payload(
+    device_id: String,
+    payload: String,
+    encoding: Option<Encoding>,
+    state: State<'_, SerialState>,
+) -> Result<Vec<u8>, String> {
+    let data = encode_for_device(&state.manager, &device_id, &payload, encoding)?;
+    state.manager.send_to_device(&device_id, data.clone())?;
+    Ok(data)
+}
+
+/// 预览 `send_serial_payload` 将写出的字节（解析 + 校验），不实际发送
+///
+/// 按设备运行中的配置计算校验，与实际发送一致；设备未运行时按已保存的配置计算。
+#[tauri::command]
+pub async fn encode_serial_payload(
+    device_id: String,
+    payload: String,
+    encoding: Option<Encoding>,
+    state: State<'_, SerialState>,
+) -> Result<Vec<u8>, String> {
+    encode_for_device(&state.manager, &device_id, &payload, encoding)
+}
+
+/// 按名称执行设备的指令模板：代入参数、校验取值范围、追加校验后发送，返回实际写出的字节
+///
+/// `args` 中未提供的参数使用模板声明的默认值；多余或越界的参数返回错误。
+/// 模板与校验取自设备运行中的配置。
+#[tauri::command]
+pub async fn execute_device_command(
+    device_id: String,
+    name: String,
+    args: Option<HashMap<String, i64>>,
+    state: State<'_, SerialState>,
+) -> Result<Vec<u8>, String> {
+    let device = state
+        .manager
+        .device_config(&device_id)
+        .ok_or_else(|| format!("设备 {} 不存在", device_id))?;
+    let data = device.render_command(&name, &args.unwrap_or_default())?;
+    state.manager.send_to_device(&device_id, data.clone())?;
+    log::info!("[串口 {}] 执行指令 {}", device.name, name);
+    Ok(data)
+}
+
+fn encode_for_device(
+    manager: &SerialManager,
+    device_id: &str,
+    payload: &str,
+    encoding: Option<Encoding>,
+) -> Result<Vec<u8>, String> {
+    let device = manager
+        .device_config(device_id)
+        .ok_or_else(|| format!("设备 {} 不存在", device_id))?;
+    device.encode_payload(payload, encoding.unwrap_or_default())
+}
+
+/// 开始录制指定设备的原始收发字节，返回抓包文件路径
+///
+/// `path` 缺省时写入 `{data_dir}/captures/{device_id}_{ms}.jsonl`（见 `paths`）。
+#[tauri::command]
+pub async fn start_serial_capture(
+    device_id: String,
+    path: Option<String>,
+    state: State<'_, SerialState>,
+) -> Result<String, String> {
+    let path = state
+        .manager
+        .start_capture(&device_id, path.map(PathBuf::from))?;
+    Ok(path.display().to_string())
+}
+
+/// 结束录制，返回抓包文件路径（未在录制时为 null）
+#[tauri::command]
+pub async fn stop_serial_capture(
+    device_id: String,
+    state: State<'_, SerialState>,
+) -> Result<Option<String>, String> {
+    let path = state.manager.stop_capture(&device_id)?;
+    if let Some(p) = &path {
+        log::info!("串口 {} 抓包已保存: {}", device_id, p.display());
+    }
+    Ok(path.map(|p| p.display().to_string()))
+}
+
+/// 回放抓包文件：RX 记录经分帧后照常推送 `serial:data`，无需连接硬件
+///
+/// - `device_id`：缺省时使用抓包文件头中的设备配置；指定时改用本机该设备的配置
+/// - `speed`：回放倍速，缺省 1.0；`0` 表示不等待、尽快回放
+#[tauri::command]
+pub async fn replay_serial_capture(
+    path: String,
+    device_id: Option<String>,
+    speed: Option<f64>,
+    state: State<'_, SerialState>,
+    app: AppHandle,
+) -> Result<(), String> {
+    let target = match device_id {
+        Some(id) => Some(
+            state
+                .manager
+                .config()
+                .find_device(&id)
+                .cloned()
+                .ok_or_else(|| format!("设备 {} 不存在", id))?,
+        ),
+        None => None,
+    };
+    state
+        .manager
+        .replay(Path::new(&path), target, speed.unwrap_or(1.0), app)
+}
+
+/// 停止正在进行的回放
+#[tauri::command]
+pub async fn stop_serial_replay(state: State<'_, SerialState>) -> Result<(), String> {
+    state.manager.stop_replay();
+    Ok(())
+}
+
+/// 获取所有运行中设备的统计快照（收发计数、错误次数、连接时长等）
+///
+/// 同样的数据每 2 秒通过 `serial:stats` 事件推送。
+#[tauri::command]
+pub async fn get_serial_stats(state: State<'_, SerialState>) -> Result<Vec<DeviceStats>, String> {
+    Ok(state.manager.stats())
+}
+
+/// `serial_transact` 返回的单条应答帧
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactFrame {
+    pub data: Vec<u8>,
+    /// 按设备的 `display` 编码渲染，与 `serial:data` 一致；文本解码失败时为 None
+    pub data_str: Option<String>,
+}
+
+/// `serial_transact` 的返回值
+#[derive(Debug, Clone, Serialize)]
+pub struct TransactResponse {
+    /// 匹配到的应答帧；`expect.type = "frames"` 时按到达顺序排列
+    pub frames: Vec<TransactFrame>,
+    /// 从写入到收齐应答的耗时（毫秒）
+    pub elapsed_ms: u64,
+}
+
+/// 写入指令并等待该设备的应答帧
+///
+/// 同一设备上的并发调用按顺序串行执行；超时返回 `{ kind: "timeout", timeout_ms }`。
+/// `expect` 缺省时返回写入后的下一帧。
+#[tauri::command]
+pub async fn serial_transact(
+    device_id: String,
+    data: Vec<u8>,
+    timeout_ms: u64,
+    expect: Option<Expect>,
+    state: State<'_, SerialState>,
+) -> Result<TransactResponse, TransactError> {
+    let (matcher, count) = expect.unwrap_or_default().into_matcher()?;
+    let manager: Arc<SerialManager> = state.manager.clone();
+    tauri::async_runtime::spawn_blocking(move || {
+        let started = std::time::Instant::now();
+        let frames = manager.transact_frames(
+            &device_id,
+            &data,
+            Duration::from_millis(timeout_ms),
+            matcher,
+            count,
+        )?;
+        let display = manager.display_encoding(&device_id).unwrap_or(Encoding::Text);
+        Ok(TransactResponse {
+            frames: frames
+                .into_iter()
+                .map(|data| TransactFrame {
+                    data_str: display.format(&data),
+                    data,
+                })
+                .collect(),
+            elapsed_ms: started.elapsed().as_millis() as u64,
+        })
+    })
+    .await
+    .map_err(|e| TransactError::Write { message: e.to_string() })?
+}
+
+/// 列出当前系统可用的串口及其 USB 信息（VID/PID、序列号、厂商、产品名）
 ///
-/// 前端用于填充端口选择下拉框。
+/// 前端用于填充端口选择下拉框；端口插拔时另有 `serial:ports-changed` 事件推送。
 #[tauri::command]
-pub async fn list_available_ports() -> Result<Vec<String>, String> {
-    serialport::available_ports()
-        .map(|ports| ports.into_iter().map(|p| p.port_name).collect())
-        .map_err(|e| format!("获取端口列表失败: {}", e))
+pub async fn list_available_ports() -> Result<Vec<PortInfo>, String> {
+    tauri::async_runtime::spawn_blocking(ports::list_ports)
+        .await
+        .map_err(|e| e.to_string())?
+}
+
+// ─── Modbus RTU 主站命令 ─────────────────────────────────────────────────────
+//
+// 事务需要阻塞等待应答，统一移交到 blocking 线程池执行，避免占用异步运行时。
+// `timeout_ms` 缺省为 1000ms。
+
+async fn with_modbus<T, F>(
+    state: State<'_, SerialState>,
+    device_id: String,
+    timeout_ms: Option<u64>,
+    f: F,
+) -> Result<T, ModbusError>
+where
+    T: Send + 'static,
+    F: FnOnce(&ModbusMaster) -> Result<T, ModbusError> + Send + 'static,
+{
+    let manager: Arc<SerialManager> = state.manager.clone();
+    let timeout = timeout_ms.map(Duration::from_millis).unwrap_or(modbus::DEFAULT_TIMEOUT);
+    tauri::async_runtime::spawn_blocking(move || {
+        f(&ModbusMaster::new(&manager, &device_id, timeout))
+    })
+    .await
+    .map_err(|e| ModbusError::Io { message: e.to_string() })?
+}
+
+/// 功能码 01：读线圈
+#[tauri::command]
+pub async fn modbus_read_coils(
+    device_id: String,
+    unit: u8,
+    addr: u16,
+    count: u16,
+    timeout_ms: Option<u64>,
+    state: State<'_, SerialState>,
+) -> Result<Vec<bool>, ModbusError> {
+    with_modbus(state, device_id, timeout_ms, move |m| m.read_coils(unit, addr, count)).await
+}
+
+/// 功能码 02：读离散输入
+#[tauri::command]
+pub async fn modbus_read_discrete_inputs(
+    device_id: String,
+    unit: u8,
+    addr: u16,
+    count: u16,
+    timeout_ms: Option<u64>,
+    state: State<'_, SerialState>,
+) -> Result<Vec<bool>, ModbusError> {
+    with_modbus(state, device_id, timeout_ms, move |m| {
+        m.read_discrete_inputs(unit, addr, count)
+    })
+    .await
+}
+
+/// 功能码 03：读保持寄存器
+#[tauri::command]
+pub async fn modbus_read_holding_registers(
+    device_id: String,
+    unit: u8,
+    addr: u16,
+    count: u16,
+    timeout_ms: Option<u64>,
+    state: State<'_, SerialState>,
+) -> Result<Vec<u16>, ModbusError> {
+    with_modbus(state, device_id, timeout_ms, move |m| {
+        m.read_holding_registers(unit, addr, count)
+    })
+    .await
+}
+
+/// 功能码 04：读输入寄存器
+#[tauri::command]
+pub async fn modbus_read_input_registers(
+    device_id: String,
+    unit: u8,
+    addr: u16,
+    count: u16,
+    timeout_ms: Option<u64>,
+    state: State<'_, SerialState>,
+) -> Result<Vec<u16>, ModbusError> {
+    with_modbus(state, device_id, timeout_ms, move |m| {
+        m.read_input_registers(unit, addr, count)
+    })
+    .await
+}
+
+/// 功能码 05：写单个线圈
+#[tauri::command]
+pub async fn modbus_write_single_coil(
+    device_id: String,
+    unit: u8,
+    addr: u16,
+    value: bool,
+    timeout_ms: Option<u64>,
+    state: State<'_, SerialState>,
+) -> Result<(), ModbusError> {
+    with_modbus(state, device_id, timeout_ms, move |m| {
+        m.write_single_coil(unit, addr, value)
+    })
+    .await
+}
+
+/// 功能码 06：写单个保持寄存器
+#[tauri::command]
+pub async fn modbus_write_single_register(
+    device_id: String,
+    unit: u8,
+    addr: u16,
+    value: u16,
+    timeout_ms: Option<u64>,
+    state: State<'_, SerialState>,
+) -> Result<(), ModbusError> {
+    with_modbus(state, device_id, timeout_ms, move |m| {
+        m.write_single_register(unit, addr, value)
+    })
+    .await
+}
+
+/// 功能码 15：写多个线圈
+#[tauri::command]
+pub async fn modbus_write_multiple_coils(
+    device_id: String,
+    unit: u8,
+    addr: u16,
+    values: Vec<bool>,
+    timeout_ms: Option<u64>,
+    state: State<'_, SerialState>,
+) -> Result<(), ModbusError> {
+    with_modbus(state, device_id, timeout_ms, move |m| {
+        m.write_multiple_coils(unit, addr, &values)
+    })
+    .await
+}
+
+/// 功能码 16：写多个保持寄存器
+#[tauri::command]
+pub async fn modbus_write_multiple_registers(
+    device_id: String,
+    unit: u8,
+    addr: u16,
+    values: Vec<u16>,
+    timeout_ms: Option<u64>,
+    state: State<'_, SerialState>,
+) -> Result<(), ModbusError> {
+    with_modbus(state, device_id, timeout_ms, move |m| {
+        m.write_multiple_registers(unit, addr, &values)
+    })
+    .await
 }
@@ -1,7 +1,19 @@
 use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::io;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::persist::{self, LoadOutcome};
+
+use super::checksum::ChecksumConfig;
+use super::codec::Encoding;
+use super::framing::FramingConfig;
+use super::net::{self, NetSpec};
+use super::sim::{self, SimSpec};
+use super::templates::CommandTemplate;
 
 /// 设备角色：决定收到数据后触发的事件类型
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
@@ -20,7 +32,7 @@ pub enum DeviceRole {
 }
 
 /// 单个串口设备的连接参数与角色配置
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct SerialDeviceConfig {
     /// 唯一标识，由前端生成，如 "scanner_0"、"light_a"
     pub device_id: String,
@@ -28,8 +40,13 @@ pub struct SerialDeviceConfig {
     pub name: String,
     /// 设备角色，决定数据路由逻辑
     pub role: DeviceRole,
-    /// 串口端口名，如 "COM3"（Windows）或 "/dev/ttyUSB0"（Linux）
+    /// 串口端口名，如 "COM3"（Windows）或 "/dev/ttyUSB0"（Linux）；
+    /// `sim://` 开头时为模拟设备（见 `sim` 模块），
+    /// `tcp://host:port` / `rfc2217://host:port` 为网络串口服务器（见 `net` 模块）
     pub port: String,
+    /// 绑定的 USB 序列号；设置后打开时按序列号查找实际端口，`port` 仅作显示与回退参考
+    #[serde(default)]
+    pub usb_serial: Option<String>,
     /// 波特率，常用值：9600 / 19200 / 38400 / 57600 / 115200
     pub baud_rate: u32,
     /// 数据位：5 | 6 | 7 | 8，默认 8
@@ -40,9 +57,92 @@ pub struct SerialDeviceConfig {
     pub parity: String,
     /// 是否启用（false 时不开启监听线程）
     pub enabled: bool,
+    /// 断线重连策略；旧配置文件缺省时使用默认值
+    #[serde(default)]
+    pub reconnect: ReconnectConfig,
+    /// 分帧方式；缺省为按 `\r` / `\n` 切分
+    #[serde(default)]
+    pub framing: FramingConfig,
+    /// 启动监听时自动抓包（记录原始收发字节，见 `capture` 模块）
+    #[serde(default)]
+    pub record: bool,
+    /// `send_serial_payload` 发送时自动追加的校验；缺省不追加
+    #[serde(default)]
+    pub checksum: ChecksumConfig,
+    /// `serial:data` 中 `data_str` 的渲染方式；缺省为 UTF-8 文本
+    #[serde(default)]
+    pub display: Encoding,
+    /// 命名指令模板（见 `templates` 模块），由 `execute_device_command` 调用
+    #[serde(default)]
+    pub command_templates: Vec<CommandTemplate>,
+}
+
+/// 断线重连策略：打开失败或读取出错后按指数退避重试
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct ReconnectConfig {
+    /// 是否启用自动重连（false 时出错即停止监听，与旧行为一致）
+    pub enabled: bool,
+    /// 首次重试前的等待时间（毫秒）
+    pub initial_delay_ms: u64,
+    /// 退避等待时间上限（毫秒）
+    pub max_delay_ms: u64,
+    /// 每次重试后等待时间的放大倍数，需 >= 1.0
+    pub multiplier: f64,
+    /// 最大连续重试次数；0 表示无限重试
+    pub max_attempts: u32,
+}
+
+impl Default for ReconnectConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            initial_delay_ms: 500,
+            max_delay_ms: 10_000,
+            multiplier: 2.0,
+            max_attempts: 0,
+        }
+    }
+}
+
+impl ReconnectConfig {
+    /// 第 `attempt` 次重试（从 1 开始）前应等待的时间
+    pub fn delay_for(&self, attempt: u32) -> Duration {
+        let exp = attempt.saturating_sub(1).min(32) as i32;
+        let ms = self.initial_delay_ms as f64 * self.multiplier.powi(exp);
+        Duration::from_millis(ms.min(self.max_delay_ms as f64) as u64)
+    }
+
+    /// 是否已用尽重试次数
+    pub fn exhausted(&self, attempt: u32) -> bool {
+        self.max_attempts > 0 && attempt >= self.max_attempts
+    }
 }
 
 impl SerialDeviceConfig {
+    /// 按 `encoding` 解析指令字符串并追加设备配置的校验
+    pub fn encode_payload(&self, payload: &str, encoding: Encoding) -> Result<Vec<u8>, String> {
+        self.with_checksum(encoding.parse(payload)?)
+    }
+
+    /// 按名称渲染指令模板并追加设备配置的校验
+    pub fn render_command(&self, name: &str, args: &HashMap<String, i64>) -> Result<Vec<u8>, String> {
+        let template = self
+            .command_templates
+            .iter()
+            .find(|t| t.name == name)
+            .ok_or_else(|| format!("设备 {} 没有名为 {} 的指令", self.name, name))?;
+        self.with_checksum(template.render(args)?)
+    }
+
+    fn with_checksum(&self, mut data: Vec<u8>) -> Result<Vec<u8>, String> {
+        if data.is_empty() {
+            return Err("指令内容为空".into());
+        }
+        self.checksum.append(&mut data);
+        Ok(data)
+    }
+
     /// 将 parity 字符串转换为 serialport crate 的枚举
     pub fn parity_value(&self) -> serialport::Parity {
         match self.parity.as_str() {
@@ -71,43 +171,147 @@ impl SerialDeviceConfig {
     }
 }
 
+/// 当前配置文件格式版本；格式变化时递增，并在 `MIGRATIONS` 中追加迁移步骤
+pub const CONFIG_VERSION: u32 = 2;
+
+fn config_version() -> u32 {
+    CONFIG_VERSION
+}
+
 /// 所有串口设备的配置集合
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SerialConfig {
+    /// 配置文件格式版本；缺省（前端提交或旧文件）视为当前版本，旧文件在迁移时补写
+    #[serde(default = "config_version")]
+    pub version: u32,
     pub devices: Vec<SerialDeviceConfig>,
 }
 
+impl Default for SerialConfig {
+    fn default() -> Self {
+        Self {
+            version: CONFIG_VERSION,
+            devices: Vec::new(),
+        }
+    }
+}
+
+/// 加载配置时的异常情况类型
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConfigWarningKind {
+    /// 文件无法读取（权限等）且无可用备份，使用空配置
+    ReadFailed,
+    /// 文件无法解析且无可用备份，已保留为 `.bak` 并使用空配置
+    ParseFailed,
+    /// 文件不可用，已从滚动备份恢复（损坏的原文件保留为 `.bak`）
+    Restored,
+    /// 旧版本文件已迁移到当前版本（原文件已备份）
+    Migrated,
+    /// 文件版本高于当前程序支持的版本，按当前格式尽力加载
+    NewerVersion,
+}
+
+/// `serial:config-warning` 事件：加载配置时出现的需要提示用户的情况
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigWarning {
+    pub kind: ConfigWarningKind,
+    pub message: String,
+    /// 原文件的备份路径（如有）
+    pub backup: Option<String>,
+}
+
+impl ConfigWarning {
+    fn new(kind: ConfigWarningKind, message: String, backup: Option<&Path>) -> Self {
+        Self {
+            kind,
+            message,
+            backup: backup.map(|p| p.display().to_string()),
+        }
+    }
+}
+
 impl SerialConfig {
-    /// 配置文件路径：`{config_dir}/easydesktopapp/serial_config.json`
+    /// 配置文件路径：`{config_dir}/serial_config.json`（见 `paths`）
     pub fn config_path() -> PathBuf {
-        let mut base = dirs::config_dir()
-            .unwrap_or_else(|| std::env::current_dir().unwrap());
-        base.push(env!("CARGO_PKG_NAME"));
-        base.push("serial_config.json");
-        base
-    }
-
-    /// 从磁盘加载配置；文件不存在或解析失败时返回空配置
-    pub fn load_or_default() -> Self {
-        let path = Self::config_path();
-        match fs::read_to_string(&path) {
-            Ok(s) => serde_json::from_str::<SerialConfig>(&s).unwrap_or_else(|e| {
-                eprintln!("serial_config.json 解析失败，使用空配置: {}", e);
-                SerialConfig::default()
-            }),
-            Err(_) => SerialConfig::default(),
+        crate::paths::config_file("serial_config.json")
+    }
+
+    /// 加载配置并推送 `serial:config-warning` 事件，同时返回警告
+    ///
+    /// 只在启动时调用一次（迁移与备份只发生一次）；之后的命令读取
+    /// `SerialManager::config`，不再访问磁盘。
+    pub fn load_and_report(app: &AppHandle) -> (Self, Vec<ConfigWarning>) {
+        let (cfg, warnings) = Self::load();
+        for w in &warnings {
+            log::warn!("{}", w.message);
+            let _ = app.emit("serial:config-warning", w);
         }
+        (cfg, warnings)
     }
 
-    /// 持久化配置到磁盘（自动创建父目录）
-    pub fn save(&self) -> io::Result<()> {
-        let path = Self::config_path();
-        if let Some(parent) = path.parent() {
-            fs::create_dir_all(parent)?;
+    /// 从磁盘加载配置，返回配置与加载过程中的警告
+    ///
+    /// - 文件不存在：空配置，无警告
+    /// - 读取或解析失败：原文件保留为 `serial_config.json.bak`，再尝试滚动备份
+    ///   （见 `persist::load`）；备份也不可用时返回空配置
+    /// - 旧版本：依次执行迁移，原文件备份为 `serial_config.json.v{N}.bak` 后写回新版本
+    pub fn load() -> (Self, Vec<ConfigWarning>) {
+        Self::load_from(&Self::config_path())
+    }
+
+    fn load_from(path: &Path) -> (Self, Vec<ConfigWarning>) {
+        let mut warnings = Vec::new();
+        let (cfg, from) = match persist::load(path, parse_versioned) {
+            LoadOutcome::Missing => return (Self::default(), warnings),
+            LoadOutcome::Loaded(loaded) => loaded,
+            LoadOutcome::Restored {
+                value,
+                backup,
+                broken,
+                error,
+            } => {
+                let msg = format!("{}，已从备份 {} 恢复串口配置", error, backup.display());
+                warnings.push(ConfigWarning::new(ConfigWarningKind::Restored, msg, broken.as_deref()));
+                value
+            }
+            LoadOutcome::Failed { broken, error } => {
+                let (kind, msg) = match &broken {
+                    Some(bak) => (
+                        ConfigWarningKind::ParseFailed,
+                        format!("{}，原文件已备份到 {}，使用空配置", error, bak.display()),
+                    ),
+                    None => (ConfigWarningKind::ReadFailed, format!("{}，使用空配置", error)),
+                };
+                warnings.push(ConfigWarning::new(kind, msg, broken.as_deref()));
+                return (Self::default(), warnings);
+            }
+        };
+
+        if from < CONFIG_VERSION {
+            let backup = persist::sibling(path, &format!("v{}.bak", from));
+            let result = fs::copy(path, &backup).and_then(|_| persist::save_json(path, &cfg));
+            let msg = match result {
+                Ok(()) => format!("串口配置已从版本 {} 迁移到 {}", from, CONFIG_VERSION),
+                Err(e) => format!(
+                    "串口配置已从版本 {} 迁移到 {}，但写回失败（本次按迁移结果运行）: {}",
+                    from, CONFIG_VERSION, e
+                ),
+            };
+            warnings.push(ConfigWarning::new(ConfigWarningKind::Migrated, msg, Some(&backup)));
+        } else if from > CONFIG_VERSION {
+            let msg = format!(
+                "串口配置文件版本 {} 高于当前程序支持的版本 {}，未识别的字段将被忽略",
+                from, CONFIG_VERSION
+            );
+            warnings.push(ConfigWarning::new(ConfigWarningKind::NewerVersion, msg, None));
         }
-        let s = serde_json::to_string_pretty(self)
-            .map_err(|e| io::Error::new(io::ErrorKind::Other, e.to_string()))?;
-        fs::write(path, s)
+        (cfg, warnings)
+    }
+
+    /// 原子写入磁盘并保留滚动备份（见 `persist::save_json`）
+    pub fn save(&self) -> io::Result<()> {
+        persist::save_json(&Self::config_path(), self)
     }
 
     /// 校验配置合法性
@@ -119,6 +323,17 @@ impl SerialConfig {
             if dev.port.is_empty() {
                 return Err(format!("设备 {} 的 port 不能为空", dev.name));
             }
+            if sim::is_sim_port(&dev.port) {
+                SimSpec::parse(&dev.port)
+                    .map_err(|e| format!("设备 {} 的模拟设备地址无效: {}", dev.name, e))?;
+            }
+            if net::is_net_port(&dev.port) {
+                NetSpec::parse(&dev.port)
+                    .map_err(|e| format!("设备 {} 的网络串口地址无效: {}", dev.name, e))?;
+            }
+            if dev.usb_serial.as_deref().is_some_and(|s| s.trim().is_empty()) {
+                return Err(format!("设备 {} 的 usb_serial 不能为空字符串", dev.name));
+            }
             if dev.baud_rate == 0 {
                 return Err(format!("设备 {} 的 baud_rate 不能为 0", dev.name));
             }
@@ -128,6 +343,30 @@ impl SerialConfig {
             if ![1u8, 2].contains(&dev.stop_bits) {
                 return Err(format!("设备 {} 的 stop_bits 必须为 1 或 2", dev.name));
             }
+            let rc = &dev.reconnect;
+            if rc.initial_delay_ms == 0 {
+                return Err(format!("设备 {} 的 reconnect.initial_delay_ms 不能为 0", dev.name));
+            }
+            if rc.max_delay_ms < rc.initial_delay_ms {
+                return Err(format!(
+                    "设备 {} 的 reconnect.max_delay_ms 不能小于 initial_delay_ms",
+                    dev.name
+                ));
+            }
+            if rc.multiplier.is_nan() || rc.multiplier < 1.0 {
+                return Err(format!("设备 {} 的 reconnect.multiplier 必须 >= 1.0", dev.name));
+            }
+            dev.framing
+                .validate()
+                .map_err(|e| format!("设备 {} 的分帧配置无效: {}", dev.name, e))?;
+            let mut names = HashSet::new();
+            for t in &dev.command_templates {
+                t.validate()
+                    .map_err(|e| format!("设备 {} 的指令模板无效: {}", dev.name, e))?;
+                if !names.insert(t.name.as_str()) {
+                    return Err(format!("设备 {} 的指令名 {} 重复", dev.name, t.name));
+                }
+            }
         }
         Ok(())
     }
@@ -137,3 +376,223 @@ impl SerialConfig {
         self.devices.iter().find(|d| d.device_id == device_id)
     }
 }
+
+/// 解析配置文本：迁移到当前版本后反序列化并校验，返回配置与文件原始版本号
+///
+/// 校验失败与解析失败同样处理（保留 `.bak` 并尝试滚动备份），避免非法参数进入读线程。
+fn parse_versioned(text: &str) -> Result<(SerialConfig, u32), String> {
+    let value = serde_json::from_str::<serde_json::Value>(text).map_err(|e| e.to_string())?;
+    let (value, from) = migrate(value)?;
+    let cfg = serde_json::from_value::<SerialConfig>(value).map_err(|e| e.to_string())?;
+    cfg.validate()?;
+    Ok((cfg, from))
+}
+
+// ─── 版本迁移 ────────────────────────────────────────────────────────────────
+
+/// 单步迁移：把版本 N 的 JSON 改写为版本 N + 1
+type Migration = fn(&mut serde_json::Value) -> Result<(), String>;
+
+/// 迁移链，下标 i 对应版本 i + 1 → i + 2
+const MIGRATIONS: [Migration; (CONFIG_VERSION - 1) as usize] = [migrate_v1_to_v2];
+
+/// 读取 JSON 中的版本号（无 `version` 字段的旧文件视为版本 1），依次执行迁移
+///
+/// 返回迁移后的 JSON 与原始版本号。导入配置包时也用它升级包内的旧版本配置。
+pub fn migrate(mut value: serde_json::Value) -> Result<(serde_json::Value, u32), String> {
+    let obj = value.as_object().ok_or("配置文件顶层应为对象")?;
+    let from = match obj.get("version") {
+        None => 1,
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|&v| v >= 1)
+            .ok_or_else(|| format!("version 字段无效: {}", v))?,
+    };
+    for version in from..CONFIG_VERSION {
+        MIGRATIONS[(version - 1) as usize](&mut value)
+            .map_err(|e| format!("从版本 {} 迁移失败: {}", version, e))?;
+    }
+    if from < CONFIG_VERSION {
+        value["version"] = CONFIG_VERSION.into();
+    }
+    Ok((value, from))
+}
+
+/// v1 → v2：引入 `version` 字段；旧版本前端可能写入小写的 parity（"none"/"odd"/"even"），
+/// 统一为 `parity_value` 识别的大小写，避免被静默当作无校验
+fn migrate_v1_to_v2(value: &mut serde_json::Value) -> Result<(), String> {
+    let Some(devices) = value.get_mut("devices").and_then(|d| d.as_array_mut()) else {
+        return Ok(());
+    };
+    for dev in devices {
+        if let Some(parity) = dev.get_mut("parity") {
+            let canonical = match parity.as_str().map(str::to_ascii_lowercase).as_deref() {
+                Some("odd") => "Odd",
+                Some("even") => "Even",
+                _ => "None",
+            };
+            *parity = canonical.into();
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config_with_framing(framing: serde_json::Value) -> String {
+        serde_json::json!({
+            "version": CONFIG_VERSION,
+            "devices": [{
+                "device_id": "light",
+                "name": "light",
+                "role": "light",
+                "port": "sim://light",
+                "baud_rate": 9600,
+                "data_bits": 8,
+                "stop_bits": 1,
+                "parity": "None",
+                "enabled": true,
+                "framing": framing,
+            }],
+        })
+        .to_string()
+    }
+
+    /// 基本字段之外合并 `extra` 的设备配置
+    fn device(extra: serde_json::Value) -> SerialDeviceConfig {
+        let mut json = serde_json::json!({
+            "device_id": "light",
+            "name": "light",
+            "role": "light",
+            "port": "sim://light",
+            "baud_rate": 9600,
+            "data_bits": 8,
+            "stop_bits": 1,
+            "parity": "None",
+            "enabled": true,
+        });
+        json.as_object_mut().unwrap().extend(extra.as_object().unwrap().clone());
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn encode_payload_appends_device_checksum() {
+        let dev = device(serde_json::json!({ "checksum": { "kind": "sum8", "start": 2 } }));
+        assert_eq!(
+            dev.encode_payload("AA 55 01 02", Encoding::Hex).unwrap(),
+            [0xAA, 0x55, 0x01, 0x02, 0x03]
+        );
+        let escaped = dev.encode_payload("\\x01\\x02AB", Encoding::Escaped).unwrap();
+        assert_eq!(escaped, [0x01, 0x02, b'A', b'B', 0x83]);
+        assert!(dev.encode_payload("", Encoding::Text).is_err());
+        assert!(dev.encode_payload("XYZ", Encoding::Hex).is_err());
+    }
+
+    #[test]
+    fn render_command_appends_device_checksum() {
+        let dev = device(serde_json::json!({
+            "checksum": { "kind": "sum8", "start": 2 },
+            "command_templates": [{
+                "name": "on",
+                "encoding": "hex",
+                "template": "AA 55 {channel}",
+                "params": [{ "name": "channel", "format": "hex8" }],
+            }],
+        }));
+        let args = HashMap::from([("channel".to_string(), 3)]);
+        assert_eq!(dev.render_command("on", &args).unwrap(), [0xAA, 0x55, 0x03, 0x03]);
+        assert!(dev.render_command("off", &args).is_err());
+    }
+
+    fn temp_config(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("serial-config-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir.join("serial_config.json")
+    }
+
+    /// 无 `version` 字段、parity 为小写的旧版文件
+    fn v1_config() -> String {
+        let mut json: serde_json::Value =
+            serde_json::from_str(&config_with_framing(serde_json::json!({ "type": "modbusRtu" })))
+                .unwrap();
+        json.as_object_mut().unwrap().remove("version");
+        json["devices"][0]["parity"] = "even".into();
+        json.to_string()
+    }
+
+    #[test]
+    fn migrate_upgrades_v1_and_keeps_current() {
+        let (value, from) = migrate(serde_json::from_str(&v1_config()).unwrap()).unwrap();
+        assert_eq!(from, 1);
+        assert_eq!(value["version"], CONFIG_VERSION);
+        assert_eq!(value["devices"][0]["parity"], "Even");
+
+        let current = serde_json::json!({ "version": CONFIG_VERSION, "devices": [] });
+        assert_eq!(migrate(current.clone()).unwrap(), (current, CONFIG_VERSION));
+        let newer = serde_json::json!({ "version": CONFIG_VERSION + 1, "devices": [] });
+        assert_eq!(migrate(newer).unwrap().1, CONFIG_VERSION + 1);
+    }
+
+    #[test]
+    fn migrate_rejects_invalid_version() {
+        for version in [serde_json::json!(0), serde_json::json!("2"), serde_json::json!(u64::MAX)] {
+            let value = serde_json::json!({ "version": version, "devices": [] });
+            assert!(migrate(value).is_err());
+        }
+        assert!(migrate(serde_json::json!([])).is_err());
+    }
+
+    #[test]
+    fn load_migrates_old_file_once_and_keeps_original() {
+        let path = temp_config("migrate");
+        let original = v1_config();
+        fs::write(&path, &original).unwrap();
+
+        let (cfg, warnings) = SerialConfig::load_from(&path);
+        assert_eq!(cfg.devices[0].parity, "Even");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].kind, ConfigWarningKind::Migrated);
+        assert_eq!(fs::read_to_string(persist::sibling(&path, "v1.bak")).unwrap(), original);
+
+        // 写回后再次加载不再迁移
+        let (_, warnings) = SerialConfig::load_from(&path);
+        assert!(warnings.is_empty());
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn load_keeps_broken_file_as_bak() {
+        let path = temp_config("broken");
+        assert!(SerialConfig::load_from(&path).1.is_empty());
+
+        fs::write(&path, "{\"devices\": [").unwrap();
+        let (cfg, warnings) = SerialConfig::load_from(&path);
+        assert!(cfg.devices.is_empty());
+        assert_eq!(warnings[0].kind, ConfigWarningKind::ParseFailed);
+        assert_eq!(fs::read_to_string(persist::sibling(&path, "bak")).unwrap(), "{\"devices\": [");
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn load_warns_about_newer_version() {
+        let path = temp_config("newer");
+        let text = serde_json::json!({ "version": CONFIG_VERSION + 1, "devices": [] }).to_string();
+        fs::write(&path, text).unwrap();
+        let (_, warnings) = SerialConfig::load_from(&path);
+        assert_eq!(warnings[0].kind, ConfigWarningKind::NewerVersion);
+        let _ = fs::remove_dir_all(path.parent().unwrap());
+    }
+
+    #[test]
+    fn parse_rejects_invalid_framing() {
+        let text = config_with_framing(serde_json::json!({ "type": "fixedLength", "length": 0 }));
+        assert!(parse_versioned(&text).is_err());
+        let text = config_with_framing(serde_json::json!({ "type": "fixedLength", "length": 8 }));
+        assert!(parse_versioned(&text).is_ok());
+    }
+}
@@ -0,0 +1,621 @@
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use super::checksum;
+
+/// 单帧解码结果：完整帧的字节，或无法恢复的帧错误描述（如校验失败）
+pub type FrameResult = Result<Vec<u8>, String>;
+
+/// 串口字节流分帧器
+///
+/// 读线程把每次 `read` 到的字节交给 `push`，读超时（无新数据）时调用 `on_idle`；
+/// 两者返回的每个元素对应一条 `serial:data` 事件。
+pub trait Framer: Send {
+    /// 输入新收到的字节，返回其中解出的完整帧
+    fn push(&mut self, bytes: &[u8], now: Instant) -> Vec<FrameResult>;
+
+    /// 读超时时调用，用于按空闲间隔或长度阈值冲刷缓冲区
+    fn on_idle(&mut self, _now: Instant) -> Vec<FrameResult> {
+        Vec::new()
+    }
+
+    /// 丢弃未完成的半帧（重连后调用）
+    fn reset(&mut self);
+}
+
+// ─── 分帧配置 ────────────────────────────────────────────────────────────────
+
+/// 分帧方式，按设备在 `SerialDeviceConfig.framing` 中选择
+///
+/// JSON 形如 `{ "type": "stxEtx", "stx": 2, "etx": 3, "bcc": true }`。
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum FramingConfig {
+    /// 按分隔符切分（扫码枪常用 `\r` / `\n`）；分隔符本身不包含在帧内
+    Delimiter {
+        /// 分隔符集合，任一字节出现即结束当前帧
+        delimiters: Vec<u8>,
+        /// 读超时时若缓冲区已达此长度则强制冲刷（适配无结束符协议）；0 表示不冲刷
+        #[serde(default)]
+        flush_len: usize,
+    },
+    /// 固定长度帧，如光源控制器的 8 字节应答
+    FixedLength { length: usize },
+    /// 长度前缀帧：`[header][length][body][trailer]`
+    LengthPrefixed {
+        /// 长度字段之前的固定头部字节数（如地址、命令字）
+        #[serde(default)]
+        length_offset: usize,
+        /// 长度字段字节数：1 或 2
+        length_size: usize,
+        /// 长度字段是否为大端序（仅 length_size = 2 时有意义）
+        #[serde(default = "default_true")]
+        big_endian: bool,
+        /// body 之后的固定尾部字节数（如校验和）
+        #[serde(default)]
+        trailer_len: usize,
+    },
+    /// STX/ETX 包围的帧，可选 ETX 后紧跟 1 字节 BCC；输出不含 STX/ETX/BCC
+    StxEtx {
+        #[serde(default = "default_stx")]
+        stx: u8,
+        #[serde(default = "default_etx")]
+        etx: u8,
+        /// 是否校验 ETX 之后的 BCC（对 STX 之后到 ETX 为止的字节异或）
+        #[serde(default)]
+        bcc: bool,
+    },
+    /// 字节间空闲超过 `gap_ms` 即视为一帧结束
+    IdleGap { gap_ms: u64 },
+    /// Modbus RTU 从站应答：按功能码推算帧长并校验 CRC16，输出含地址与 CRC 的整帧
+    ModbusRtu,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_stx() -> u8 {
+    0x02
+}
+
+fn default_etx() -> u8 {
+    0x03
+}
+
+/// 默认与旧版读循环一致：按 `\r` / `\n` 切分，超时时满 64 字节强制冲刷
+impl Default for FramingConfig {
+    fn default() -> Self {
+        FramingConfig::Delimiter {
+            delimiters: vec![b'\r', b'\n'],
+            flush_len: 64,
+        }
+    }
+}
+
+/// 单帧最大长度，超过后丢弃缓冲区以便重新同步
+const MAX_FRAME_LEN: usize = 4096;
+
+/// 端口默认读超时（决定 cancel flag 的响应速度）
+const DEFAULT_READ_TIMEOUT: Duration = Duration::from_millis(100);
+
+impl FramingConfig {
+    /// 校验参数合法性
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            FramingConfig::Delimiter { delimiters, .. } if delimiters.is_empty() => {
+                Err("分隔符分帧至少需要一个分隔符".into())
+            }
+            FramingConfig::FixedLength { length } if *length == 0 || *length > MAX_FRAME_LEN => {
+                Err(format!("固定长度必须在 1..={} 之间", MAX_FRAME_LEN))
+            }
+            FramingConfig::LengthPrefixed { length_size, .. } if ![1, 2].contains(length_size) => {
+                Err("length_size 必须为 1 或 2".into())
+            }
+            FramingConfig::StxEtx { stx, etx, .. } if stx == etx => {
+                Err("stx 与 etx 不能相同".into())
+            }
+            FramingConfig::IdleGap { gap_ms } if *gap_ms == 0 => {
+                Err("gap_ms 不能为 0".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// 端口读超时：空闲间隔分帧需要比间隔更细的轮询粒度
+    pub fn read_timeout(&self) -> Duration {
+        match self {
+            FramingConfig::IdleGap { gap_ms } => {
+                Duration::from_millis(*gap_ms).min(DEFAULT_READ_TIMEOUT)
+            }
+            _ => DEFAULT_READ_TIMEOUT,
+        }
+    }
+
+    /// 按配置构造分帧器；参数不合法时返回错误
+    ///
+    /// 配置可能来自未经 `validate` 的来源（手工编辑的文件、抓包头），
+    /// 非法参数会让分帧器死循环（`length = 0`）或越界 panic（`length_size = 0`）。
+    pub fn build(&self) -> Result<Box<dyn Framer>, String> {
+        self.validate()?;
+        Ok(match self.clone() {
+            FramingConfig::Delimiter { delimiters, flush_len } => Box::new(DelimiterFramer {
+                delimiters,
+                flush_len,
+                buf: Vec::new(),
+            }),
+            FramingConfig::FixedLength { length } => Box::new(FixedLengthFramer {
+                length,
+                buf: Vec::new(),
+            }),
+            FramingConfig::LengthPrefixed {
+                length_offset,
+                length_size,
+                big_endian,
+                trailer_len,
+            } => Box::new(LengthPrefixedFramer {
+                length_offset,
+                length_size,
+                big_endian,
+                trailer_len,
+                buf: Vec::new(),
+            }),
+            FramingConfig::StxEtx { stx, etx, bcc } => Box::new(StxEtxFramer {
+                stx,
+                etx,
+                bcc,
+                state: StxState::Idle,
+                buf: Vec::new(),
+            }),
+            FramingConfig::IdleGap { gap_ms } => Box::new(IdleGapFramer {
+                gap: Duration::from_millis(gap_ms),
+                last_rx: None,
+                buf: Vec::new(),
+            }),
+            FramingConfig::ModbusRtu => Box::new(ModbusRtuFramer { buf: Vec::new() }),
+        })
+    }
+}
+
+// ─── 分帧器实现 ──────────────────────────────────────────────────────────────
+
+struct DelimiterFramer {
+    delimiters: Vec<u8>,
+    flush_len: usize,
+    buf: Vec<u8>,
+}
+
+impl Framer for DelimiterFramer {
+    fn push(&mut self, bytes: &[u8], _now: Instant) -> Vec<FrameResult> {
+        let mut out = Vec::new();
+        for &byte in bytes {
+            if self.delimiters.contains(&byte) {
+                // 连续分隔符（如 \r\n）之间的空帧直接忽略
+                if !self.buf.is_empty() {
+                    out.push(Ok(std::mem::take(&mut self.buf)));
+                }
+            } else if self.buf.len() >= MAX_FRAME_LEN {
+                self.buf.clear();
+                out.push(Err(format!("超过 {} 字节仍未收到分隔符，已丢弃", MAX_FRAME_LEN)));
+            } else {
+                self.buf.push(byte);
+            }
+        }
+        out
+    }
+
+    fn on_idle(&mut self, _now: Instant) -> Vec<FrameResult> {
+        if self.flush_len > 0 && self.buf.len() >= self.flush_len {
+            vec![Ok(std::mem::take(&mut self.buf))]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn reset(&mut self) {
+        self.buf.clear();
+    }
+}
+
+struct FixedLengthFramer {
+    length: usize,
+    buf: Vec<u8>,
+}
+
+impl Framer for FixedLengthFramer {
+    fn push(&mut self, bytes: &[u8], _now: Instant) -> Vec<FrameResult> {
+        self.buf.extend_from_slice(bytes);
+        let mut out = Vec::new();
+        while self.buf.len() >= self.length {
+            let rest = self.buf.split_off(self.length);
+            out.push(Ok(std::mem::replace(&mut self.buf, rest)));
+        }
+        out
+    }
+
+    fn reset(&mut self) {
+        self.buf.clear();
+    }
+}
+
+struct LengthPrefixedFramer {
+    length_offset: usize,
+    length_size: usize,
+    big_endian: bool,
+    trailer_len: usize,
+    buf: Vec<u8>,
+}
+
+impl LengthPrefixedFramer {
+    /// 若缓冲区中已能读出长度字段，返回整帧长度
+    fn frame_len(&self) -> Option<usize> {
+        let start = self.length_offset;
+        let field = self.buf.get(start..start + self.length_size)?;
+        let body = match (self.length_size, self.big_endian) {
+            (1, _) => field[0] as usize,
+            (_, true) => u16::from_be_bytes([field[0], field[1]]) as usize,
+            (_, false) => u16::from_le_bytes([field[0], field[1]]) as usize,
+        };
+        Some(start + self.length_size + body + self.trailer_len)
+    }
+}
+
+impl Framer for LengthPrefixedFramer {
+    fn push(&mut self, bytes: &[u8], _now: Instant) -> Vec<FrameResult> {
+        self.buf.extend_from_slice(bytes);
+        let mut out = Vec::new();
+        while let Some(len) = self.frame_len() {
+            if len > MAX_FRAME_LEN {
+                self.buf.clear();
+                out.push(Err(format!("长度字段声明 {} 字节，超过上限，已丢弃", len)));
+                break;
+            }
+            if self.buf.len() < len {
+                break;
+            }
+            let rest = self.buf.split_off(len);
+            out.push(Ok(std::mem::replace(&mut self.buf, rest)));
+        }
+        out
+    }
+
+    fn reset(&mut self) {
+        self.buf.clear();
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum StxState {
+    /// 等待 STX，期间收到的字节被丢弃
+    Idle,
+    /// 已收到 STX，正在累积数据直到 ETX
+    Body,
+    /// 已收到 ETX，等待 BCC 字节
+    Checksum,
+}
+
+struct StxEtxFramer {
+    stx: u8,
+    etx: u8,
+    bcc: bool,
+    state: StxState,
+    buf: Vec<u8>,
+}
+
+impl Framer for StxEtxFramer {
+    fn push(&mut self, bytes: &[u8], _now: Instant) -> Vec<FrameResult> {
+        let mut out = Vec::new();
+        for &byte in bytes {
+            match self.state {
+                StxState::Idle => {
+                    if byte == self.stx {
+                        self.buf.clear();
+                        self.state = StxState::Body;
+                    }
+                }
+                StxState::Body => {
+                    if byte == self.stx {
+                        // 上一帧未结束又收到 STX：丢弃残帧重新开始
+                        self.buf.clear();
+                        out.push(Err("收到 STX 时上一帧未结束，已丢弃".into()));
+                    } else if byte == self.etx {
+                        if self.bcc {
+                            self.state = StxState::Checksum;
+                        } else {
+                            out.push(Ok(std::mem::take(&mut self.buf)));
+                            self.state = StxState::Idle;
+                        }
+                    } else if self.buf.len() >= MAX_FRAME_LEN {
+                        self.buf.clear();
+                        self.state = StxState::Idle;
+                        out.push(Err(format!("超过 {} 字节仍未收到 ETX，已丢弃", MAX_FRAME_LEN)));
+                    } else {
+                        self.buf.push(byte);
+                    }
+                }
+                StxState::Checksum => {
+                    let expected = checksum::bcc(&self.buf) ^ self.etx;
+                    let payload = std::mem::take(&mut self.buf);
+                    if byte == expected {
+                        out.push(Ok(payload));
+                    } else {
+                        out.push(Err(format!(
+                            "BCC 校验失败：期望 {:02X}，收到 {:02X}",
+                            expected, byte
+                        )));
+                    }
+                    self.state = StxState::Idle;
+                }
+            }
+        }
+        out
+    }
+
+    fn reset(&mut self) {
+        self.buf.clear();
+        self.state = StxState::Idle;
+    }
+}
+
+struct IdleGapFramer {
+    gap: Duration,
+    last_rx: Option<Instant>,
+    buf: Vec<u8>,
+}
+
+impl IdleGapFramer {
+    fn flush_if_idle(&mut self, now: Instant) -> Option<Vec<u8>> {
+        let last = self.last_rx?;
+        if !self.buf.is_empty() && now.duration_since(last) >= self.gap {
+            self.last_rx = None;
+            Some(std::mem::take(&mut self.buf))
+        } else {
+            None
+        }
+    }
+}
+
+impl Framer for IdleGapFramer {
+    fn push(&mut self, bytes: &[u8], now: Instant) -> Vec<FrameResult> {
+        // 新字节到达前若已超过间隔，先把旧数据作为一帧输出
+        let mut out: Vec<FrameResult> = self.flush_if_idle(now).map(Ok).into_iter().collect();
+        if self.buf.len() + bytes.len() > MAX_FRAME_LEN {
+            self.buf.clear();
+            out.push(Err(format!("超过 {} 字节仍无空闲间隔，已丢弃", MAX_FRAME_LEN)));
+        }
+        self.buf.extend_from_slice(bytes);
+        self.last_rx = Some(now);
+        out
+    }
+
+    fn on_idle(&mut self, now: Instant) -> Vec<FrameResult> {
+        self.flush_if_idle(now).map(Ok).into_iter().collect()
+    }
+
+    fn reset(&mut self) {
+        self.buf.clear();
+        self.last_rx = None;
+    }
+}
+
+struct ModbusRtuFramer {
+    buf: Vec<u8>,
+}
+
+impl ModbusRtuFramer {
+    /// 按功能码推算应答帧总长度；数据不足以判断时返回 Ok(None)
+    fn frame_len(&self) -> Result<Option<usize>, String> {
+        let Some(&fc) = self.buf.get(1) else {
+            return Ok(None);
+        };
+        match fc {
+            // 异常应答：地址 + 功能码|0x80 + 异常码 + CRC
+            _ if fc & 0x80 != 0 => Ok(Some(5)),
+            // 读类应答：地址 + 功能码 + 字节数 + 数据 + CRC
+            0x01..=0x04 => Ok(self.buf.get(2).map(|&n| 5 + n as usize)),
+            // 写类应答：回显地址与数量/值
+            0x05 | 0x06 | 0x0F | 0x10 => Ok(Some(8)),
+            _ => Err(format!("不支持的功能码 {:02X}", fc)),
+        }
+    }
+}
+
+impl Framer for ModbusRtuFramer {
+    fn push(&mut self, bytes: &[u8], _now: Instant) -> Vec<FrameResult> {
+        self.buf.extend_from_slice(bytes);
+        let mut out = Vec::new();
+        loop {
+            let len = match self.frame_len() {
+                Ok(Some(len)) if self.buf.len() >= len => len,
+                Ok(_) => break,
+                Err(e) => {
+                    self.buf.clear();
+                    out.push(Err(e));
+                    break;
+                }
+            };
+            let rest = self.buf.split_off(len);
+            let frame = std::mem::replace(&mut self.buf, rest);
+            let (body, crc) = frame.split_at(len - 2);
+            let expected = checksum::crc16_modbus(body);
+            if crc == expected.to_le_bytes() {
+                out.push(Ok(frame));
+            } else {
+                out.push(Err(format!(
+                    "CRC 校验失败：期望 {:04X}，收到 {:02X}{:02X}",
+                    expected, crc[1], crc[0]
+                )));
+            }
+        }
+        out
+    }
+
+    /// 读超时仍未收齐的残帧视为损坏（RTU 帧内不允许超过 1.5 字符的间隔）
+    fn on_idle(&mut self, _now: Instant) -> Vec<FrameResult> {
+        if self.buf.is_empty() {
+            return Vec::new();
+        }
+        let n = self.buf.len();
+        self.buf.clear();
+        vec![Err(format!("Modbus 应答不完整（{} 字节），已丢弃", n))]
+    }
+
+    fn reset(&mut self) {
+        self.buf.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn framing(json: serde_json::Value) -> FramingConfig {
+        serde_json::from_value(json).unwrap()
+    }
+
+    /// 按给定分块依次输入，收集全部结果
+    fn push_all(cfg: &FramingConfig, chunks: &[&[u8]]) -> Vec<FrameResult> {
+        let mut framer = cfg.build().unwrap();
+        let now = Instant::now();
+        chunks.iter().flat_map(|c| framer.push(c, now)).collect()
+    }
+
+    fn ok(frames: &[&[u8]]) -> Vec<FrameResult> {
+        frames.iter().map(|f| Ok(f.to_vec())).collect()
+    }
+
+    #[test]
+    fn delimiter_splits_across_chunks_and_skips_empty_frames() {
+        let cfg = FramingConfig::default();
+        assert_eq!(push_all(&cfg, &[b"SN0", b"01\r\n\r\nSN002\n"]), ok(&[b"SN001", b"SN002"]));
+    }
+
+    #[test]
+    fn delimiter_flushes_on_idle_at_flush_len() {
+        let cfg = framing(serde_json::json!({
+            "type": "delimiter", "delimiters": [13], "flush_len": 4,
+        }));
+        let mut framer = cfg.build().unwrap();
+        let now = Instant::now();
+        assert!(framer.push(b"abc", now).is_empty());
+        assert!(framer.on_idle(now).is_empty());
+        assert!(framer.push(b"d", now).is_empty());
+        assert_eq!(framer.on_idle(now), ok(&[b"abcd"]));
+    }
+
+    #[test]
+    fn fixed_length_emits_complete_frames() {
+        let cfg = framing(serde_json::json!({ "type": "fixedLength", "length": 3 }));
+        assert_eq!(push_all(&cfg, &[b"ab", b"cdefg", b"h"]), ok(&[b"abc", b"def"]));
+    }
+
+    #[test]
+    fn length_prefixed_reads_header_length_and_trailer() {
+        // [地址][长度 BE u16][body][1 字节校验]
+        let cfg = framing(serde_json::json!({
+            "type": "lengthPrefixed", "length_offset": 1, "length_size": 2, "trailer_len": 1,
+        }));
+        let frame: &[u8] = &[0x01, 0x00, 0x02, 0xAA, 0xBB, 0xCC];
+        assert_eq!(push_all(&cfg, &[&frame[..2], &frame[2..], &frame[..3]]), ok(&[frame]));
+
+        let le = framing(serde_json::json!({
+            "type": "lengthPrefixed", "length_size": 2, "big_endian": false,
+        }));
+        assert_eq!(push_all(&le, &[&[0x01, 0x00, 0x7F]]), ok(&[&[0x01, 0x00, 0x7F]]));
+    }
+
+    #[test]
+    fn length_prefixed_drops_oversized_frames() {
+        let cfg = framing(serde_json::json!({ "type": "lengthPrefixed", "length_size": 2 }));
+        let out = push_all(&cfg, &[&[0xFF, 0xFF, 0x00]]);
+        assert!(matches!(out.as_slice(), [Err(_)]));
+    }
+
+    #[test]
+    fn stx_etx_strips_markers_and_checks_bcc() {
+        let plain = framing(serde_json::json!({ "type": "stxEtx" }));
+        assert_eq!(push_all(&plain, &[b"xx\x02AB", b"C\x03"]), ok(&[b"ABC"]));
+
+        let with_bcc = framing(serde_json::json!({ "type": "stxEtx", "bcc": true }));
+        let bcc = checksum::bcc(b"AB") ^ 0x03;
+        assert_eq!(push_all(&with_bcc, &[b"\x02AB\x03", &[bcc]]), ok(&[b"AB"]));
+        let out = push_all(&with_bcc, &[b"\x02AB\x03", &[bcc ^ 0xFF]]);
+        assert!(matches!(out.as_slice(), [Err(_)]));
+    }
+
+    #[test]
+    fn stx_etx_restarts_on_unexpected_stx() {
+        let cfg = framing(serde_json::json!({ "type": "stxEtx" }));
+        let out = push_all(&cfg, &[b"\x02AB\x02CD\x03"]);
+        assert!(matches!(out.as_slice(), [Err(_), Ok(f)] if f == b"CD"));
+    }
+
+    #[test]
+    fn idle_gap_splits_on_silence() {
+        let cfg = framing(serde_json::json!({ "type": "idleGap", "gap_ms": 20 }));
+        let mut framer = cfg.build().unwrap();
+        let t0 = Instant::now();
+        assert!(framer.push(b"ab", t0).is_empty());
+        assert!(framer.push(b"c", t0 + Duration::from_millis(10)).is_empty());
+        assert!(framer.on_idle(t0 + Duration::from_millis(15)).is_empty());
+        assert_eq!(framer.on_idle(t0 + Duration::from_millis(30)), ok(&[b"abc"]));
+        // 新字节到达时先输出已超过间隔的旧数据
+        framer.push(b"d", t0 + Duration::from_millis(40));
+        assert_eq!(framer.push(b"e", t0 + Duration::from_millis(70)), ok(&[b"d"]));
+        assert_eq!(cfg.read_timeout(), Duration::from_millis(20));
+    }
+
+    #[test]
+    fn modbus_rtu_frames_by_function_code_and_checks_crc() {
+        let cfg = FramingConfig::ModbusRtu;
+        // 读保持寄存器应答：01 03 02 00 2A + CRC
+        let mut read = vec![0x01, 0x03, 0x02, 0x00, 0x2A];
+        read.extend_from_slice(&checksum::crc16_modbus(&read).to_le_bytes());
+        // 异常应答：01 83 02 + CRC
+        let mut exception = vec![0x01, 0x83, 0x02];
+        exception.extend_from_slice(&checksum::crc16_modbus(&exception).to_le_bytes());
+        let stream = [read.clone(), exception.clone()].concat();
+        assert_eq!(push_all(&cfg, &[&stream[..4], &stream[4..]]), ok(&[&read, &exception]));
+
+        let mut corrupted = read.clone();
+        corrupted[3] ^= 0xFF;
+        assert!(matches!(push_all(&cfg, &[&corrupted]).as_slice(), [Err(_)]));
+        assert!(matches!(push_all(&cfg, &[&[0x01, 0x2B]]).as_slice(), [Err(_)]));
+    }
+
+    #[test]
+    fn modbus_rtu_discards_partial_frame_on_idle() {
+        let mut framer = FramingConfig::ModbusRtu.build().unwrap();
+        let now = Instant::now();
+        assert!(framer.push(&[0x01, 0x03, 0x02], now).is_empty());
+        assert!(matches!(framer.on_idle(now).as_slice(), [Err(_)]));
+        assert!(framer.on_idle(now).is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_degenerate_configs() {
+        for json in [
+            serde_json::json!({ "type": "delimiter", "delimiters": [] }),
+            serde_json::json!({ "type": "fixedLength", "length": 5000 }),
+            serde_json::json!({ "type": "lengthPrefixed", "length_size": 3 }),
+            serde_json::json!({ "type": "stxEtx", "stx": 2, "etx": 2 }),
+            serde_json::json!({ "type": "idleGap", "gap_ms": 0 }),
+        ] {
+            assert!(framing(json.clone()).validate().is_err(), "{json}");
+        }
+        assert!(FramingConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn build_rejects_zero_fixed_length() {
+        let cfg = framing(serde_json::json!({ "type": "fixedLength", "length": 0 }));
+        assert!(cfg.build().is_err());
+    }
+
+    #[test]
+    fn build_rejects_zero_length_size() {
+        let cfg = framing(serde_json::json!({ "type": "lengthPrefixed", "length_size": 0 }));
+        assert!(cfg.build().is_err());
+    }
+}
@@ -1,14 +1,21 @@
 use std::collections::HashMap;
 use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use serde::Serialize;
-use serialport::SerialPort;
-use tauri::{AppHandle, Emitter};
+use tauri::{AppHandle, Emitter, Runtime};
 
+use super::capture::{self, CaptureRecord, Recorder};
+use super::codec;
 use super::config::{DeviceRole, SerialConfig, SerialDeviceConfig};
+use super::framing::{FrameResult, Framer};
+use super::stats::{DeviceStats, StatsCell};
+use super::transact::{FrameMatcher, TransactError, TransactionSlot};
+use super::transport::{self, Transport};
 
 // ─── 事件 Payload ────────────────────────────────────────────────────────────
 
@@ -20,12 +27,12 @@ pub struct SerialDataPayload {
     pub port: String,
     /// 原始字节数组
     pub data: Vec<u8>,
-    /// 尝试 UTF-8 解码；失败时为 None
+    /// 按设备 `display` 编码渲染；`text` 编码下 UTF-8 解码失败时为 None
     pub data_str: Option<String>,
     pub timestamp_ms: u64,
 }
 
-/// `serial:error` 事件：串口读取失败，监听线程将退出
+/// `serial:error` 事件：串口打开或读取失败（是否重连见随后的 `serial:status`）
 #[derive(Debug, Clone, Serialize)]
 pub struct SerialErrorPayload {
     pub device_id: String,
@@ -34,15 +41,46 @@ pub struct SerialErrorPayload {
     pub timestamp_ms: u64,
 }
 
-/// `serial:status` 事件：设备连接/断开状态变化
+/// 设备连接状态
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ConnectionState {
+    /// 端口已打开，读线程正常工作
+    Connected,
+    /// 连接断开，正在等待下一次重试
+    Reconnecting,
+    /// 已停止（手动停止、未启用重连或重试次数耗尽）
+    Disconnected,
+}
+
+/// `serial:status` 事件：设备连接/断开/重连状态变化
 #[derive(Debug, Clone, Serialize)]
 pub struct SerialStatusPayload {
     pub device_id: String,
     pub port: String,
+    /// 兼容旧前端：等价于 `state == Connected`
     pub connected: bool,
+    pub state: ConnectionState,
+    /// 当前重试次数（从 1 开始）；非重连状态时为 0
+    pub attempt: u32,
     pub timestamp_ms: u64,
 }
 
+/// `serial:replay-finished` 事件：一次回放结束（完成或被停止）
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayFinishedPayload {
+    pub device_id: String,
+    pub path: String,
+    /// 已回放的 RX 记录数
+    pub rx_records: usize,
+    /// 是否被 `stop_replay` 提前终止
+    pub cancelled: bool,
+    pub timestamp_ms: u64,
+}
+
+/// 读线程诊断日志的打印间隔
+const DIAG_INTERVAL: Duration = Duration::from_secs(5);
+
 fn now_ms() -> u64 {
     SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -50,264 +88,1155 @@ fn now_ms() -> u64 {
         .unwrap_or(0)
 }
 
+/// `apply_config` 的结果：各类处理涉及的 device_id
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ConfigApplyResult {
+    pub started: Vec<String>,
+    pub stopped: Vec<String>,
+    pub restarted: Vec<String>,
+    pub unchanged: Vec<String>,
+    /// 旧线程未能在 `STOP_TIMEOUT` 内退出的设备：不会重新启动，下次应用配置时重试
+    pub stuck: Vec<String>,
+}
+
 // ─── 单个运行中设备的句柄 ─────────────────────────────────────────────────────
 
+/// 当前可写端口；重连期间为 None
+type SharedPort = Arc<Mutex<Option<Box<dyn Transport>>>>;
+
 struct RunningDevice {
-    /// 设置为 true 时，读循环在下次超时后退出
+    /// 启动时的设备配置，用于配置变更时判断是否需要重启
+    cfg: SerialDeviceConfig,
+    /// 监听线程句柄，停止时 join 以确保端口已释放
+    thread: JoinHandle<()>,
+    /// 设置为 true 时，读循环在下次超时后退出，重连等待也会被打断
     cancel: Arc<AtomicBool>,
-    /// 写端口（克隆自读端口，线程安全）
-    write_port: Arc<Mutex<Box<dyn SerialPort>>>,
+    /// 写端口（克隆自读端口，线程安全）；每次重连成功后替换
+    write_port: SharedPort,
+    /// 监听线程是否仍在运行（重试耗尽后置为 false）
+    alive: Arc<AtomicBool>,
+    /// 请求/应答事务槽，读线程把解出的帧投递给等待中的事务
+    transactions: Arc<TransactionSlot>,
+    /// 抓包录制器，跨重连保持
+    recorder: Arc<Recorder>,
+    /// 运行统计，跨重连累计
+    stats: Arc<StatsCell>,
+}
+
+/// 停止设备时等待监听线程退出的上限
+///
+/// 读超时与重连等待都能被 cancel 及时打断，正常情况下线程在一个读超时周期内退出；
+/// 超过此时限通常意味着驱动层阻塞（如 USB 拔出时的 close 卡死），不再无限等待。
+pub const STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl RunningDevice {
+    /// 在 `deadline` 前等待监听线程退出（调用前需已设置 cancel）
+    ///
+    /// 超时时原样返回句柄（`Some`）：调用方应把它放回 `running`，使端口在线程最终退出前
+    /// 不会被重复打开，之后再次停止或应用配置时会重新等待。
+    fn join(self, deadline: Instant) -> Option<Self> {
+        while !self.thread.is_finished() {
+            if Instant::now() >= deadline {
+                log::error!(
+                    "串口 {} ({}) 监听线程未能在 {:?} 内退出",
+                    self.cfg.name,
+                    self.cfg.port,
+                    STOP_TIMEOUT
+                );
+                return Some(self);
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        if self.thread.join().is_err() {
+            log::error!("串口 {} 监听线程异常退出", self.cfg.port);
+        }
+        None
+    }
+
+    /// 已收到停止信号但线程尚未退出（上次停止超时）
+    fn stopping(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed) && self.alive.load(Ordering::Relaxed)
+    }
 }
 
 // ─── SerialManager ────────────────────────────────────────────────────────────
 
 /// 串口监听线程的生命周期管理器
 ///
-/// - `start_all`：按配置为每个 enabled 设备启动一个读线程
-/// - `stop_all`：设置所有 cancel flag，线程在 100ms 超时后自然退出
-/// - `restart_all`：先 stop_all 再 start_all（配置变更时调用）
+/// - `start_all`：按配置为每个 enabled 设备启动一个受监督的读线程
+/// - `stop_all`：设置所有 cancel flag 并等待线程退出、释放端口（有超时上限）
+/// - `start_device` / `stop_device` / `restart_device`：单独控制一个设备
+/// - `apply_config`：与运行中的配置比对，只启停/重启有变化的设备（配置变更时调用）
 /// - `send_to_device`：通过写端口向指定设备发送字节
+/// - `transact`：写入请求并等待该设备读线程解出的下一条匹配应答帧
+/// - `start_capture` / `stop_capture`：录制设备原始收发字节
+/// - `replay`：把抓包文件回放到分帧与 `serial:data` 推送流程
+/// - `stats`：各设备收发计数、错误与连接时长（另由 `spawn_stats_reporter` 定期推送）
+///
+/// 读线程在打开失败或读取出错时不会直接退出，而是按设备的
+/// `reconnect` 策略退避重试，因此 `running` 中的条目可跨越短暂断线。
 pub struct SerialManager {
     running: Mutex<HashMap<String, RunningDevice>>,
+    /// 最近一次通过 `start_all` / `apply_config` 应用的配置
+    config: Mutex<SerialConfig>,
+    /// 正在进行的回放的取消标志（同一时刻只允许一个回放）
+    replay_cancel: Mutex<Option<Arc<AtomicBool>>>,
 }
 
 impl SerialManager {
     pub fn new() -> Self {
         Self {
             running: Mutex::new(HashMap::new()),
+            config: Mutex::new(SerialConfig::default()),
+            replay_cancel: Mutex::new(None),
         }
     }
 
     /// 为配置中所有 `enabled = true` 的设备启动监听线程
-    pub fn start_all(&self, config: &SerialConfig, app: AppHandle) {
+    pub fn start_all<R: Runtime>(&self, config: &SerialConfig, app: AppHandle<R>) {
+        *self.config.lock().unwrap() = config.clone();
         let mut running = self.running.lock().unwrap();
+        // 清理重试耗尽后已退出的线程，使其可被重新启动
+        running.retain(|_, dev| dev.alive.load(Ordering::Relaxed));
         for dev in config.devices.iter().filter(|d| d.enabled) {
             if running.contains_key(&dev.device_id) {
-                continue; // 已在运行，跳过
+                continue; // 已在运行（或旧线程仍在退出），跳过
             }
-            if let Some(handle) = Self::spawn_reader(dev.clone(), app.clone()) {
-                running.insert(dev.device_id.clone(), handle);
+            match DeviceWorker::spawn(dev.clone(), app.clone()) {
+                Ok(handle) => {
+                    running.insert(dev.device_id.clone(), handle);
+                }
+                Err(e) => log::error!("{}", e),
             }
         }
     }
 
-    /// 停止所有监听线程（设置 cancel flag；线程在读超时后退出）
-    pub fn stop_all(&self) {
+    /// 停止所有监听线程，并等待其退出、释放端口（最长 `STOP_TIMEOUT`）
+    ///
+    /// 返回未能按时退出的设备 device_id；这些设备保留在 `running` 中，直到线程退出。
+    pub fn stop_all(&self) -> Vec<String> {
+        let devices: Vec<RunningDevice> = self.running.lock().unwrap().drain().map(|(_, d)| d).collect();
+        // 先统一发出取消信号，再共用同一截止时间逐个 join，总耗时不超过 STOP_TIMEOUT
+        for dev in &devices {
+            dev.cancel.store(true, Ordering::Relaxed);
+        }
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        let mut stuck = Vec::new();
+        for dev in devices {
+            if let Some(dev) = dev.join(deadline) {
+                stuck.push(dev.cfg.device_id.clone());
+                self.running.lock().unwrap().insert(dev.cfg.device_id.clone(), dev);
+            }
+        }
+        stuck
+    }
+
+    /// 启动单个设备的监听线程（不检查 `enabled`）；已在运行时返回错误
+    pub fn start_device<R: Runtime>(&self, cfg: &SerialDeviceConfig, app: AppHandle<R>) -> Result<(), String> {
         let mut running = self.running.lock().unwrap();
-        for (_, dev) in running.drain() {
+        if let Some(dev) = running.get(&cfg.device_id) {
+            if dev.stopping() {
+                return Err(format!("设备 {} 的旧监听线程尚未退出，请稍后重试", cfg.device_id));
+            }
+            if dev.alive.load(Ordering::Relaxed) {
+                return Err(format!("设备 {} 已在运行", cfg.device_id));
+            }
+        }
+        // 重试耗尽后已退出的旧条目直接替换（线程已结束，无需等待）
+        running.insert(cfg.device_id.clone(), DeviceWorker::spawn(cfg.clone(), app)?);
+        Ok(())
+    }
+
+    /// 停止单个设备并等待其线程退出（最长 `STOP_TIMEOUT`）；设备未运行时返回 `Ok(false)`
+    ///
+    /// 线程未能按时退出时返回错误，设备保留在 `running` 中（见 `RunningDevice::join`）。
+    pub fn stop_device(&self, device_id: &str) -> Result<bool, String> {
+        let Some(dev) = self.running.lock().unwrap().remove(device_id) else {
+            return Ok(false);
+        };
+        dev.cancel.store(true, Ordering::Relaxed);
+        match dev.join(Instant::now() + STOP_TIMEOUT) {
+            None => Ok(true),
+            Some(dev) => {
+                self.running.lock().unwrap().insert(device_id.to_string(), dev);
+                Err(format!("设备 {} 的监听线程未能在 {:?} 内退出", device_id, STOP_TIMEOUT))
+            }
+        }
+    }
+
+    /// 重启单个设备：等待旧线程释放端口后按 `cfg` 重新打开
+    ///
+    /// 旧线程未能按时退出时不重新打开，返回错误。重新打开经由 `start_device`，
+    /// 在同一次加锁内检查并插入：等待期间设备已被并发启动时返回错误，不会覆盖其线程。
+    pub fn restart_device<R: Runtime>(&self, cfg: &SerialDeviceConfig, app: AppHandle<R>) -> Result<(), String> {
+        self.stop_device(&cfg.device_id)?;
+        self.start_device(cfg, app)
+    }
+
+    /// 按新配置调整运行中的设备，只处理有变化的部分
+    ///
+    /// - 不在新配置中或已禁用的设备：停止
+    /// - 配置有变化或线程已退出（重试耗尽）的设备：重启
+    /// - 新增的 enabled 设备：启动
+    /// - 旧线程未能按时退出的设备：记入 `stuck`，不重新启动
+    /// - 其余设备保持运行，连接不受影响
+    pub fn apply_config<R: Runtime>(&self, config: &SerialConfig, app: AppHandle<R>) -> ConfigApplyResult {
+        *self.config.lock().unwrap() = config.clone();
+        let mut result = ConfigApplyResult::default();
+        let wanted: HashMap<&str, &SerialDeviceConfig> = config
+            .devices
+            .iter()
+            .filter(|d| d.enabled)
+            .map(|d| (d.device_id.as_str(), d))
+            .collect();
+
+        // 取出需要停止或重启的设备，锁外等待其退出
+        let to_stop: Vec<RunningDevice> = {
+            let mut running = self.running.lock().unwrap();
+            let ids: Vec<String> = running
+                .iter()
+                .filter(|(id, dev)| match wanted.get(id.as_str()) {
+                    Some(cfg) => {
+                        **cfg != dev.cfg
+                            || !dev.alive.load(Ordering::Relaxed)
+                            || dev.cancel.load(Ordering::Relaxed)
+                    }
+                    None => true,
+                })
+                .map(|(id, _)| id.clone())
+                .collect();
+            ids.iter().filter_map(|id| running.remove(id)).collect()
+        };
+        for dev in &to_stop {
             dev.cancel.store(true, Ordering::Relaxed);
         }
+        let deadline = Instant::now() + STOP_TIMEOUT;
+        let mut stuck = Vec::new();
+        for dev in to_stop {
+            let id = dev.cfg.device_id.clone();
+            match dev.join(deadline) {
+                None if wanted.contains_key(id.as_str()) => result.restarted.push(id),
+                None => result.stopped.push(id),
+                Some(dev) => {
+                    result.stuck.push(id);
+                    stuck.push(dev);
+                }
+            }
+        }
+
+        let mut running = self.running.lock().unwrap();
+        for dev in stuck {
+            running.insert(dev.cfg.device_id.clone(), dev);
+        }
+        for dev in config.devices.iter().filter(|d| d.enabled) {
+            if result.stuck.contains(&dev.device_id) {
+                continue;
+            }
+            if running.contains_key(&dev.device_id) {
+                result.unchanged.push(dev.device_id.clone());
+                continue;
+            }
+            match DeviceWorker::spawn(dev.clone(), app.clone()) {
+                Ok(handle) => {
+                    running.insert(dev.device_id.clone(), handle);
+                    if !result.restarted.contains(&dev.device_id) {
+                        result.started.push(dev.device_id.clone());
+                    }
+                }
+                Err(e) => {
+                    log::error!("{}", e);
+                    // 旧线程已停止但未能重新启动
+                    if let Some(i) = result.restarted.iter().position(|id| id == &dev.device_id) {
+                        result.stopped.push(result.restarted.remove(i));
+                    }
+                }
+            }
+        }
+        result
     }
 
-    /// 重启所有监听线程（配置变更后调用）
-    pub fn restart_all(&self, config: &SerialConfig, app: AppHandle) {
-        self.stop_all();
-        // 等待旧线程退出（最长 200ms）
-        std::thread::sleep(Duration::from_millis(200));
-        self.start_all(config, app);
+    /// 当前生效的串口配置
+    ///
+    /// 启动时由 `start_all` 设置（磁盘上的配置只在启动时加载、迁移一次），
+    /// 之后随保存、导入或外部修改经 `apply_config` 更新，命令无需再读磁盘。
+    pub fn config(&self) -> SerialConfig {
+        self.config.lock().unwrap().clone()
     }
 
     /// 向指定设备的串口写入数据
     pub fn send_to_device(&self, device_id: &str, data: Vec<u8>) -> Result<(), String> {
+        let io = self.device_io(device_id).map_err(|e| e.to_string())?;
+        io.write(device_id, &data).map_err(|e| e.to_string())
+    }
+
+    /// 写入请求并等待一条匹配的应答帧
+    pub fn transact(
+        &self,
+        device_id: &str,
+        data: &[u8],
+        timeout: Duration,
+        matcher: FrameMatcher,
+    ) -> Result<Vec<u8>, TransactError> {
+        self.transact_frames(device_id, data, timeout, matcher, 1)
+            .map(|mut frames| frames.remove(0))
+    }
+
+    /// 写入请求并等待 `count` 条匹配的应答帧
+    ///
+    /// 同一设备上的并发事务按调用顺序串行执行；应答帧仍会照常推送 `serial:data`。
+    pub fn transact_frames(
+        &self,
+        device_id: &str,
+        data: &[u8],
+        timeout: Duration,
+        matcher: FrameMatcher,
+        count: usize,
+    ) -> Result<Vec<Vec<u8>>, TransactError> {
+        let io = self.device_io(device_id)?;
+        io.transactions
+            .run(matcher, count, timeout, || io.write(device_id, data))
+    }
+
+    /// 设备实际生效的配置：运行中的设备取其线程正在使用的配置，
+    /// 未运行时取 `config()` 中的条目；两者都没有时为 None
+    pub fn device_config(&self, device_id: &str) -> Option<SerialDeviceConfig> {
+        if let Some(dev) = self.running.lock().unwrap().get(device_id) {
+            return Some(dev.cfg.clone());
+        }
+        self.config.lock().unwrap().find_device(device_id).cloned()
+    }
+
+    /// 设备当前配置的 `display` 编码（`serial:data` 的 `data_str` 同样按此渲染）；未运行时为 None
+    pub fn display_encoding(&self, device_id: &str) -> Option<codec::Encoding> {
+        self.running.lock().unwrap().get(device_id).map(|dev| dev.cfg.display)
+    }
+
+    /// 取出设备写路径所需的共享句柄，避免在写入/等待期间持有 `running` 锁
+    fn device_io(&self, device_id: &str) -> Result<DeviceIo, TransactError> {
+        let running = self.running.lock().unwrap();
+        let dev = running.get(device_id).ok_or_else(|| TransactError::NotConnected {
+            message: format!("设备 {} 未连接或未启用", device_id),
+        })?;
+        Ok(DeviceIo {
+            write_port: dev.write_port.clone(),
+            recorder: dev.recorder.clone(),
+            stats: dev.stats.clone(),
+            transactions: dev.transactions.clone(),
+        })
+    }
+
+    /// 所有运行中设备的统计快照（按 device_id 排序）
+    pub fn stats(&self) -> Vec<DeviceStats> {
+        let running = self.running.lock().unwrap();
+        let mut list: Vec<DeviceStats> = running.values().map(|d| d.stats.snapshot()).collect();
+        list.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+        list
+    }
+
+    /// 开始录制指定设备的收发字节；`path` 缺省时写入默认抓包目录，返回文件路径
+    ///
+    /// 文件头记录设备运行中的配置，回放时按录制时实际使用的分帧解析。
+    pub fn start_capture(&self, device_id: &str, path: Option<PathBuf>) -> Result<PathBuf, String> {
+        let running = self.running.lock().unwrap();
+        let dev = running
+            .get(device_id)
+            .ok_or_else(|| format!("设备 {} 未连接或未启用", device_id))?;
+        let path = path.unwrap_or_else(|| capture::default_capture_path(device_id, now_ms()));
+        dev.recorder
+            .start(&dev.cfg, path.clone(), now_ms())
+            .map_err(|e| format!("创建抓包文件失败: {}", e))?;
+        Ok(path)
+    }
+
+    /// 结束录制，返回抓包文件路径（未在录制时为 None）
+    pub fn stop_capture(&self, device_id: &str) -> Result<Option<PathBuf>, String> {
         let running = self.running.lock().unwrap();
         let dev = running
             .get(device_id)
             .ok_or_else(|| format!("设备 {} 未连接或未启用", device_id))?;
-        let mut port = dev.write_port.lock().unwrap();
+        Ok(dev.recorder.stop())
+    }
+
+    /// 在后台线程回放抓包文件
+    ///
+    /// 默认按文件头中的设备配置分帧；传入 `target` 时改用本机该设备的配置
+    /// （分帧、角色、device_id），便于把现场数据喂给本地调试中的设备。
+    /// 回放结束后推送 `serial:replay-finished`。
+    pub fn replay<R: Runtime>(
+        &self,
+        path: &Path,
+        target: Option<SerialDeviceConfig>,
+        speed: f64,
+        app: AppHandle<R>,
+    ) -> Result<(), String> {
+        let (captured, records) = capture::read_capture(path)?;
+        let cfg = target.unwrap_or(captured);
+        let framer = cfg.framing.build()?;
+
+        let mut current = self.replay_cancel.lock().unwrap();
+        if let Some(prev) = current.take() {
+            prev.store(true, Ordering::Relaxed);
+        }
+        let cancel = Arc::new(AtomicBool::new(false));
+        *current = Some(cancel.clone());
+
+        let out = DeviceOutput {
+            stats: Arc::new(StatsCell::new(&cfg.device_id, &cfg.port, now_ms())),
+            cfg,
+            app,
+            transactions: Arc::new(TransactionSlot::default()),
+        };
+        let path = path.display().to_string();
+        std::thread::spawn(move || run_replay(out, framer, records, path, speed, cancel));
+        Ok(())
+    }
+
+    /// 停止正在进行的回放
+    pub fn stop_replay(&self) {
+        if let Some(cancel) = self.replay_cancel.lock().unwrap().take() {
+            cancel.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+/// 定期推送 `serial:stats` 事件的间隔
+const STATS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// 启动后台线程，每 2 秒推送一次 `serial:stats`（元素为 `DeviceStats`）
+///
+/// 没有运行中的设备时不推送；管理器被释放后线程随之退出。
+pub fn spawn_stats_reporter<R: Runtime>(manager: &Arc<SerialManager>, app: AppHandle<R>) {
+    let weak = Arc::downgrade(manager);
+    std::thread::spawn(move || loop {
+        std::thread::sleep(STATS_INTERVAL);
+        let Some(manager) = weak.upgrade() else {
+            break;
+        };
+        let stats = manager.stats();
+        if !stats.is_empty() {
+            let _ = app.emit("serial:stats", stats);
+        }
+    });
+}
+
+/// 设备写路径的共享句柄
+struct DeviceIo {
+    write_port: SharedPort,
+    recorder: Arc<Recorder>,
+    stats: Arc<StatsCell>,
+    transactions: Arc<TransactionSlot>,
+}
+
+impl DeviceIo {
+    /// 写入并记录抓包与统计；设备正在重连时返回 `NotConnected`
+    fn write(&self, device_id: &str, data: &[u8]) -> Result<(), TransactError> {
+        let mut guard = self.write_port.lock().unwrap();
+        let port = guard.as_mut().ok_or_else(|| TransactError::NotConnected {
+            message: format!("设备 {} 连接已断开，正在重连", device_id),
+        })?;
         use std::io::Write;
-        port.write_all(&data)
-            .map_err(|e| format!("写入失败: {}", e))
-    }
-
-    // ─── 内部：为单个设备打开端口并启动读线程 ────────────────────────────────
-
-    fn spawn_reader(cfg: SerialDeviceConfig, app: AppHandle) -> Option<RunningDevice> {
-        // 打开串口，显式禁用硬件流控（USB CDC 虚拟串口不需要 RTS/CTS）
-        let port = serialport::new(&cfg.port, cfg.baud_rate)
-            .data_bits(cfg.data_bits_value())
-            .stop_bits(cfg.stop_bits_value())
-            .parity(cfg.parity_value())
-            .flow_control(serialport::FlowControl::None)
-            .timeout(Duration::from_millis(100))
-            .open();
-
-        let mut read_port = match port {
-            Ok(mut p) => {
-                // USB CDC 扫码枪通常需要 DTR=true 才会开始发送数据
-                // DTR 信号通知设备"主机已就绪"，没有它扫码枪会静默
-                if let Err(e) = p.write_data_terminal_ready(true) {
-                    log::warn!("[串口 {}|{}] 设置 DTR 失败（非致命）: {}", cfg.name, cfg.port, e);
-                } else {
-                    log::info!("[串口 {}|{}] DTR 已置高", cfg.name, cfg.port);
-                }
-                p
-            }
+        port.write_all(data).map_err(|e| TransactError::Write {
+            message: format!("写入失败: {}", e),
+        })?;
+        self.recorder.record_tx(now_ms(), data);
+        self.stats.on_tx(data.len());
+        Ok(())
+    }
+}
+
+// ─── 内部：帧输出 ────────────────────────────────────────────────────────────
+
+/// 解出帧之后的统一出口：投递事务、打印日志并推送事件
+///
+/// 实时读线程与抓包回放共用，保证回放时的事件与现场一致。
+struct DeviceOutput<R: Runtime> {
+    cfg: SerialDeviceConfig,
+    app: AppHandle<R>,
+    transactions: Arc<TransactionSlot>,
+    stats: Arc<StatsCell>,
+}
+
+impl<R: Runtime> DeviceOutput<R> {
+    /// 处理分帧器输出：先交给等待中的事务，再推送 `serial:data`；帧错误仅记录日志
+    fn handle_frame(&self, frame: FrameResult) {
+        let cfg = &self.cfg;
+        let data = match frame {
+            Ok(data) => data,
             Err(e) => {
                 log::warn!(
-                    "串口 {} ({}) 打开失败: {}",
-                    cfg.name,
-                    cfg.port,
-                    e
-                );
-                // 向前端报告连接失败
-                let _ = app.emit(
-                    "serial:error",
-                    SerialErrorPayload {
-                        device_id: cfg.device_id.clone(),
-                        port: cfg.port.clone(),
-                        error: e.to_string(),
-                        timestamp_ms: now_ms(),
-                    },
+                    device_id = cfg.device_id.as_str(), port = cfg.port.as_str();
+                    "[串口 {}|{}] 分帧错误: {}", cfg.name, cfg.port, e
                 );
-                return None;
+                self.stats.on_framing_error();
+                return;
             }
         };
+        self.stats.on_frame();
+        self.transactions.offer(&data);
+        let data_str = cfg.display.format(&data);
 
-        // 克隆写端口（读循环使用原始端口）
-        let write_port = match read_port.try_clone() {
-            Ok(p) => Arc::new(Mutex::new(p)),
-            Err(e) => {
-                log::warn!("串口 {} try_clone 失败: {}", cfg.port, e);
-                return None;
-            }
-        };
+        // info 级别：确保在默认日志配置下可见；扫码枪的数据即被测产品序列号，单独记为 sn
+        let (device_id, port) = (cfg.device_id.as_str(), cfg.port.as_str());
+        match &data_str {
+            Some(s) if cfg.role == DeviceRole::Scanner => log::info!(
+                device_id, port, sn = s.trim();
+                "[串口 {}|{}] RX: {}", cfg.name, cfg.port, s
+            ),
+            Some(s) => log::info!(device_id, port; "[串口 {}|{}] RX: {}", cfg.name, cfg.port, s),
+            None => log::info!(
+                device_id, port;
+                "[串口 {}|{}] RX (hex): {}", cfg.name, cfg.port, codec::to_hex(&data)
+            ),
+        }
 
-        let cancel = Arc::new(AtomicBool::new(false));
-        let cancel_clone = cancel.clone();
+        self.emit_data(data, data_str);
+    }
+
+    fn emit_data(&self, data: Vec<u8>, data_str: Option<String>) {
+        let _ = self.app.emit(
+            "serial:data",
+            SerialDataPayload {
+                device_id: self.cfg.device_id.clone(),
+                role: self.cfg.role.clone(),
+                port: self.cfg.port.clone(),
+                data,
+                data_str,
+                timestamp_ms: now_ms(),
+            },
+        );
+    }
+
+    fn emit_error(&self, error: String) {
+        let _ = self.app.emit(
+            "serial:error",
+            SerialErrorPayload {
+                device_id: self.cfg.device_id.clone(),
+                port: self.cfg.port.clone(),
+                error,
+                timestamp_ms: now_ms(),
+            },
+        );
+    }
 
-        // 通知前端：设备已连接
-        let _ = app.emit(
+    fn emit_status(&self, state: ConnectionState, attempt: u32) {
+        self.stats.on_state(state);
+        let _ = self.app.emit(
             "serial:status",
             SerialStatusPayload {
-                device_id: cfg.device_id.clone(),
-                port: cfg.port.clone(),
-                connected: true,
+                device_id: self.cfg.device_id.clone(),
+                port: self.cfg.port.clone(),
+                connected: state == ConnectionState::Connected,
+                state,
+                attempt,
                 timestamp_ms: now_ms(),
             },
         );
+    }
+}
 
-        log::info!("串口 {} ({}) 监听启动", cfg.name, cfg.port);
+// ─── 内部：单个设备的受监督读线程 ────────────────────────────────────────────
 
-        std::thread::spawn(move || {
-            let mut buf = vec![0u8; 256];
-            let mut line_buf: Vec<u8> = Vec::new();
-            // 每 50 次超时（约 5 秒）打印一次诊断日志
-            let mut timeout_count = 0u32;
+/// 读循环的退出原因
+enum ReadExit {
+    /// cancel flag 被置位
+    Cancelled,
+    /// 非超时读错误，连接视为断开
+    Failed(std::io::Error),
+}
 
-            loop {
-                if cancel_clone.load(Ordering::Relaxed) {
-                    break;
-                }
+/// 单个设备的监听线程状态：负责打开端口、读循环与断线重连
+struct DeviceWorker<R: Runtime> {
+    out: DeviceOutput<R>,
+    cancel: Arc<AtomicBool>,
+    write_port: SharedPort,
+    recorder: Arc<Recorder>,
+}
+
+impl<R: Runtime> DeviceWorker<R> {
+    /// 启动监听线程并返回其句柄；打开失败也会进入重连流程，只有创建线程失败时返回错误
+    fn spawn(cfg: SerialDeviceConfig, app: AppHandle<R>) -> Result<RunningDevice, String> {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let write_port: SharedPort = Arc::new(Mutex::new(None));
+        let alive = Arc::new(AtomicBool::new(true));
+        let transactions = Arc::new(TransactionSlot::default());
+        let recorder = Arc::new(Recorder::default());
+        let stats = Arc::new(StatsCell::new(&cfg.device_id, &cfg.port, now_ms()));
+
+        if cfg.record {
+            let path = capture::default_capture_path(&cfg.device_id, now_ms());
+            if let Err(e) = recorder.start(&cfg, path, now_ms()) {
+                log::warn!("串口 {} 自动抓包启动失败: {}", cfg.port, e);
+            }
+        }
+
+        let worker = DeviceWorker {
+            out: DeviceOutput {
+                cfg,
+                app,
+                transactions: transactions.clone(),
+                stats: stats.clone(),
+            },
+            cancel: cancel.clone(),
+            write_port: write_port.clone(),
+            recorder: recorder.clone(),
+        };
+        let worker_cfg = worker.out.cfg.clone();
+        let alive_clone = alive.clone();
+        // 线程名出现在 JSON 日志的 thread 字段中
+        let spawned = std::thread::Builder::new()
+            .name(format!("serial-{}", worker_cfg.device_id))
+            .spawn(move || {
+                worker.run();
+                alive_clone.store(false, Ordering::Relaxed);
+            });
+        let thread = match spawned {
+            Ok(thread) => thread,
+            Err(e) => {
+                recorder.stop();
+                return Err(format!("串口 {} 监听线程创建失败: {}", worker_cfg.port, e));
+            }
+        };
+
+        Ok(RunningDevice {
+            cfg: worker_cfg,
+            thread,
+            cancel,
+            write_port,
+            alive,
+            transactions,
+            recorder,
+            stats,
+        })
+    }
 
-                match read_port.read(&mut buf) {
-                    Ok(0) => continue,
-                    Ok(n) => {
-                        for &byte in &buf[..n] {
-                            if byte == b'\r' || byte == b'\n' {
-                                // 收到行结束符，将缓冲区作为一条完整消息emit
-                                if !line_buf.is_empty() {
-                                    let complete = std::mem::take(&mut line_buf);
-                                    let data_str = String::from_utf8(complete.clone())
-                                        .ok()
-                                        .map(|s| s.trim().to_string())
-                                        .filter(|s| !s.is_empty());
-
-                                    // info 级别：确保在默认日志配置下可见
-                                    match &data_str {
-                                        Some(s) => log::info!(
-                                            "[串口 {}|{}] RX: {}",
-                                            cfg.name, cfg.port, s
-                                        ),
-                                        None => log::info!(
-                                            "[串口 {}|{}] RX (hex): {}",
-                                            cfg.name, cfg.port,
-                                            complete.iter().map(|b| format!("{b:02X}")).collect::<Vec<_>>().join(" ")
-                                        ),
-                                    }
-
-                                    let _ = app.emit(
-                                        "serial:data",
-                                        SerialDataPayload {
-                                            device_id: cfg.device_id.clone(),
-                                            role: cfg.role.clone(),
-                                            port: cfg.port.clone(),
-                                            data: complete,
-                                            data_str,
-                                            timestamp_ms: now_ms(),
-                                        },
+    fn cancelled(&self) -> bool {
+        self.cancel.load(Ordering::Relaxed)
+    }
+
+    /// 线程入口：分帧配置无效时不打开端口，直接报告错误并退出
+    fn run(self) {
+        let cfg = &self.out.cfg;
+        match cfg.framing.build() {
+            Ok(mut framer) => self.supervise(framer.as_mut()),
+            Err(e) => {
+                log::error!(
+                    device_id = cfg.device_id.as_str(), port = cfg.port.as_str();
+                    "串口 {} ({}) 分帧配置无效: {}", cfg.name, cfg.port, e
+                );
+                self.out.emit_error(format!("分帧配置无效: {}", e));
+            }
+        }
+
+        if let Some(path) = self.recorder.stop() {
+            log::info!("串口 {} 抓包已保存: {}", cfg.port, path.display());
+        }
+        self.out.emit_status(ConnectionState::Disconnected, 0);
+        log::info!("串口 {} ({}) 监听线程退出", cfg.name, cfg.port);
+    }
+
+    /// 监督循环：打开 → 读取 → 出错后按退避策略重试，直到取消或重试耗尽
+    fn supervise(&self, framer: &mut dyn Framer) {
+        let cfg = &self.out.cfg;
+        let mut attempt = 0u32;
+
+        while !self.cancelled() {
+            match transport::open(cfg) {
+                Ok(mut read_port) => {
+                    // 克隆写端口（读循环使用原始端口）
+                    match read_port.try_clone_transport() {
+                        Ok(p) => {
+                            *self.write_port.lock().unwrap() = Some(p);
+                            attempt = 0;
+                            self.out.emit_status(ConnectionState::Connected, 0);
+                            log::info!("串口 {} ({}) 监听启动", cfg.name, cfg.port);
+
+                            // 丢弃断线前残留的半帧
+                            framer.reset();
+                            let exit = self.read_loop(&mut read_port, framer);
+                            *self.write_port.lock().unwrap() = None;
+                            match exit {
+                                ReadExit::Cancelled => break,
+                                ReadExit::Failed(e) => {
+                                    log::error!(
+                                        device_id = cfg.device_id.as_str(), port = cfg.port.as_str();
+                                        "串口 {} 读取错误: {}", cfg.port, e
                                     );
+                                    self.out.stats.on_read_error();
+                                    self.out.emit_error(e.to_string());
                                 }
-                            } else {
-                                line_buf.push(byte);
                             }
                         }
-                    }
-                    Err(e)
-                        if e.kind() == std::io::ErrorKind::TimedOut
-                            || e.kind() == std::io::ErrorKind::WouldBlock =>
-                    {
-                        timeout_count += 1;
-                        // 每 ~5 秒打印一次诊断：显示缓冲区待读字节数
-                        // 有字节但没被读到 → read() 问题
-                        // 一直 0   → 数据没到达串口层（DTR/模式/端口号问题）
-                        if timeout_count % 50 == 0 {
-                            let pending = read_port.bytes_to_read().unwrap_or(0);
-                            log::info!(
-                                "[串口 {}|{}] 等待中... 缓冲区待读 {} 字节",
-                                cfg.name, cfg.port, pending
-                            );
-                        }
-                        // 超时/非阻塞返回：正常，继续循环检查 cancel flag
-                        // 若缓冲区已有数据但长时间无行结束符，主动flush（适配无结束符协议）
-                        if line_buf.len() >= 64 {
-                            let complete = std::mem::take(&mut line_buf);
-                            let data_str = String::from_utf8(complete.clone()).ok();
-                            log::info!(
-                                "[串口 {}|{}] RX flush {}B",
-                                cfg.name, cfg.port, complete.len()
-                            );
-                            let _ = app.emit(
-                                "serial:data",
-                                SerialDataPayload {
-                                    device_id: cfg.device_id.clone(),
-                                    role: cfg.role.clone(),
-                                    port: cfg.port.clone(),
-                                    data: complete,
-                                    data_str,
-                                    timestamp_ms: now_ms(),
-                                },
-                            );
+                        Err(e) => {
+                            log::warn!("串口 {} try_clone 失败: {}", cfg.port, e);
+                            self.out.emit_error(e.to_string());
                         }
-                        continue;
                     }
-                    Err(e) => {
-                        log::error!("串口 {} 读取错误: {}", cfg.port, e);
-                        let _ = app.emit(
-                            "serial:error",
-                            SerialErrorPayload {
-                                device_id: cfg.device_id.clone(),
-                                port: cfg.port.clone(),
-                                error: e.to_string(),
-                                timestamp_ms: now_ms(),
-                            },
-                        );
-                        let _ = app.emit(
-                            "serial:status",
-                            SerialStatusPayload {
-                                device_id: cfg.device_id.clone(),
-                                port: cfg.port.clone(),
-                                connected: false,
-                                timestamp_ms: now_ms(),
-                            },
+                }
+                Err(e) => {
+                    log::warn!(
+                        device_id = cfg.device_id.as_str(), port = cfg.port.as_str();
+                        "串口 {} ({}) 打开失败: {}", cfg.name, cfg.port, e
+                    );
+                    self.out.stats.on_open_error();
+                    // 向前端报告连接失败
+                    self.out.emit_error(e.to_string());
+                }
+            }
+
+            if self.cancelled() {
+                break;
+            }
+            if !cfg.reconnect.enabled || cfg.reconnect.exhausted(attempt) {
+                if cfg.reconnect.enabled {
+                    log::error!(
+                        "串口 {} ({}) 重连 {} 次均失败，放弃",
+                        cfg.name, cfg.port, attempt
+                    );
+                }
+                break;
+            }
+
+            attempt += 1;
+            let delay = cfg.reconnect.delay_for(attempt);
+            log::info!(
+                "串口 {} ({}) 将在 {}ms 后第 {} 次重连",
+                cfg.name,
+                cfg.port,
+                delay.as_millis(),
+                attempt
+            );
+            self.out.emit_status(ConnectionState::Reconnecting, attempt);
+            sleep_cancellable(&self.cancel, delay);
+        }
+    }
+
+    fn read_loop(&self, read_port: &mut Box<dyn Transport>, framer: &mut dyn Framer) -> ReadExit {
+        let cfg = &self.out.cfg;
+        let mut buf = vec![0u8; 256];
+        // 约每 5 秒打印一次诊断日志
+        let mut last_diag = Instant::now();
+
+        loop {
+            if self.cancelled() {
+                return ReadExit::Cancelled;
+            }
+
+            match read_port.read(&mut buf) {
+                Ok(0) => continue,
+                Ok(n) => {
+                    self.recorder.record_rx(now_ms(), &buf[..n]);
+                    self.out.stats.on_rx(n, now_ms());
+                    for frame in framer.push(&buf[..n], Instant::now()) {
+                        self.out.handle_frame(frame);
+                    }
+                }
+                Err(e)
+                    if e.kind() == std::io::ErrorKind::TimedOut
+                        || e.kind() == std::io::ErrorKind::WouldBlock =>
+                {
+                    // 每 ~5 秒打印一次诊断：显示缓冲区待读字节数
+                    // 有字节但没被读到 → read() 问题
+                    // 一直 0   → 数据没到达串口层（DTR/模式/端口号问题）
+                    if last_diag.elapsed() >= DIAG_INTERVAL {
+                        last_diag = Instant::now();
+                        let pending = read_port.bytes_to_read().unwrap_or(0);
+                        log::info!(
+                            "[串口 {}|{}] 等待中... 缓冲区待读 {} 字节",
+                            cfg.name, cfg.port, pending
                         );
-                        break;
                     }
+                    // 超时/非阻塞返回：正常，继续循环检查 cancel flag
+                    // 由分帧器决定是否按空闲间隔或长度阈值冲刷缓冲区
+                    for frame in framer.on_idle(Instant::now()) {
+                        self.out.handle_frame(frame);
+                    }
+                    continue;
+                }
+                Err(e) => return ReadExit::Failed(e),
+            }
+        }
+    }
+}
+
+/// 分段睡眠，便于在等待期间及时响应 cancel
+fn sleep_cancellable(cancel: &AtomicBool, total: Duration) {
+    let deadline = Instant::now() + total;
+    while !cancel.load(Ordering::Relaxed) {
+        let now = Instant::now();
+        if now >= deadline {
+            break;
+        }
+        std::thread::sleep((deadline - now).min(Duration::from_millis(50)));
+    }
+}
+
+// ─── 内部：抓包回放 ──────────────────────────────────────────────────────────
+
+/// 按录制时的时间间隔（除以 `speed`）把 RX 记录送入分帧器；`speed <= 0` 时不等待
+fn run_replay<R: Runtime>(
+    out: DeviceOutput<R>,
+    mut framer: Box<dyn Framer>,
+    records: Vec<CaptureRecord>,
+    path: String,
+    speed: f64,
+    cancel: Arc<AtomicBool>,
+) {
+    let cfg = &out.cfg;
+    let idle_after = cfg.framing.read_timeout();
+    // 以录制时间轴构造虚拟时钟，空闲间隔分帧按录制时的节奏判定
+    let base = Instant::now();
+    let first_ts = records.first().map(record_ts).unwrap_or(0);
+    let mut prev_ts = first_ts;
+    let mut rx_records = 0usize;
+
+    log::info!("[回放 {}] 开始: {}，共 {} 条记录", cfg.device_id, path, records.len());
+    for rec in &records {
+        if cancel.load(Ordering::Relaxed) {
+            break;
+        }
+        let ts = record_ts(rec);
+        let gap = Duration::from_millis(ts.saturating_sub(prev_ts));
+        if speed > 0.0 {
+            sleep_cancellable(&cancel, gap.div_f64(speed));
+        }
+        let virtual_now = base + Duration::from_millis(ts.saturating_sub(first_ts));
+        if gap >= idle_after {
+            for frame in framer.on_idle(virtual_now) {
+                out.handle_frame(frame);
+            }
+        }
+        prev_ts = ts;
+
+        let data = match rec.bytes() {
+            Some(Ok(data)) => data,
+            Some(Err(e)) => {
+                log::warn!("[回放 {}] 跳过损坏记录: {}", cfg.device_id, e);
+                continue;
+            }
+            None => continue,
+        };
+        match rec {
+            CaptureRecord::Rx { .. } => {
+                rx_records += 1;
+                for frame in framer.push(&data, virtual_now) {
+                    out.handle_frame(frame);
                 }
             }
+            CaptureRecord::Tx { .. } => {
+                log::debug!("[回放 {}] TX {}B", cfg.device_id, data.len());
+            }
+            CaptureRecord::Header { .. } => {}
+        }
+    }
+    // 末尾残帧按"长时间空闲"冲刷
+    let end = base + Duration::from_millis(prev_ts.saturating_sub(first_ts)) + Duration::from_secs(60);
+    for frame in framer.on_idle(end) {
+        out.handle_frame(frame);
+    }
+
+    let cancelled = cancel.load(Ordering::Relaxed);
+    log::info!(
+        "[回放 {}] 结束: {} 条 RX 记录{}",
+        cfg.device_id,
+        rx_records,
+        if cancelled { "（已停止）" } else { "" }
+    );
+    let _ = out.app.emit(
+        "serial:replay-finished",
+        ReplayFinishedPayload {
+            device_id: cfg.device_id.clone(),
+            path,
+            rx_records,
+            cancelled,
+            timestamp_ms: now_ms(),
+        },
+    );
+}
+
+fn record_ts(rec: &CaptureRecord) -> u64 {
+    match rec {
+        CaptureRecord::Rx { ts_ms, .. } | CaptureRecord::Tx { ts_ms, .. } => *ts_ms,
+        CaptureRecord::Header { started_ms, .. } => *started_ms,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::codec::Encoding;
+    use tauri::test::{mock_app, MockRuntime};
+
+    fn app() -> AppHandle<MockRuntime> {
+        mock_app().handle().clone()
+    }
+
+    /// 默认按行分帧、快速重连的模拟设备
+    fn device(id: &str, port: &str) -> SerialDeviceConfig {
+        serde_json::from_value(serde_json::json!({
+            "device_id": id,
+            "name": id,
+            "role": "scanner",
+            "port": port,
+            "baud_rate": 9600,
+            "data_bits": 8,
+            "stop_bits": 1,
+            "parity": "None",
+            "enabled": true,
+            "reconnect": { "initial_delay_ms": 20, "max_delay_ms": 20 },
+        }))
+        .unwrap()
+    }
 
-            log::info!("串口 {} ({}) 监听线程退出", cfg.name, cfg.port);
+    fn config(devices: Vec<SerialDeviceConfig>) -> SerialConfig {
+        SerialConfig {
+            devices,
+            ..Default::default()
+        }
+    }
+
+    fn wait_until(mut cond: impl FnMut() -> bool) -> bool {
+        let deadline = Instant::now() + Duration::from_secs(3);
+        while Instant::now() < deadline {
+            if cond() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        false
+    }
+
+    fn echo(manager: &SerialManager, id: &str) -> Result<Vec<u8>, TransactError> {
+        manager.transact(id, b"ping\n", Duration::from_millis(500), Box::new(|_: &[u8]| true))
+    }
+
+    fn alive(manager: &SerialManager, id: &str) -> bool {
+        manager.running.lock().unwrap()[id].alive.load(Ordering::Relaxed)
+    }
+
+    fn running_ids(manager: &SerialManager) -> Vec<String> {
+        manager.stats().into_iter().map(|s| s.device_id).collect()
+    }
+
+    /// 忽略 cancel 的监听线程（模拟驱动层 close 卡死），`release` 后才退出
+    fn hung(cfg: SerialDeviceConfig) -> (RunningDevice, std::sync::mpsc::Sender<()>) {
+        let (release, wait) = std::sync::mpsc::channel::<()>();
+        let alive = Arc::new(AtomicBool::new(true));
+        let alive_clone = alive.clone();
+        let thread = std::thread::spawn(move || {
+            let _ = wait.recv();
+            alive_clone.store(false, Ordering::Relaxed);
         });
+        let dev = RunningDevice {
+            stats: Arc::new(StatsCell::new(&cfg.device_id, &cfg.port, now_ms())),
+            cfg,
+            thread,
+            cancel: Arc::new(AtomicBool::new(false)),
+            write_port: Arc::new(Mutex::new(None)),
+            alive,
+            transactions: Arc::new(TransactionSlot::default()),
+            recorder: Arc::new(Recorder::default()),
+        };
+        (dev, release)
+    }
+
+    #[test]
+    fn start_all_skips_disabled_and_running_devices() {
+        let manager = SerialManager::new();
+        let mut disabled = device("b", "sim://echo");
+        disabled.enabled = false;
+        let cfg = config(vec![device("a", "sim://echo"), disabled]);
+
+        manager.start_all(&cfg, app());
+        manager.start_all(&cfg, app());
+        assert_eq!(running_ids(&manager), ["a"]);
+        assert!(wait_until(|| echo(&manager, "a").is_ok_and(|f| f == b"ping")));
+
+        let started = Instant::now();
+        assert!(manager.stop_all().is_empty());
+        assert!(started.elapsed() < STOP_TIMEOUT);
+        assert!(running_ids(&manager).is_empty());
+    }
+
+    #[test]
+    fn stop_device_joins_within_timeout() {
+        let manager = SerialManager::new();
+        manager.start_device(&device("a", "sim://echo"), app()).unwrap();
+        assert!(manager.start_device(&device("a", "sim://echo"), app()).is_err());
+        assert!(wait_until(|| echo(&manager, "a").is_ok()));
+
+        let started = Instant::now();
+        assert_eq!(manager.stop_device("a"), Ok(true));
+        assert!(started.elapsed() < STOP_TIMEOUT);
+        assert_eq!(manager.stop_device("a"), Ok(false));
+        assert!(matches!(echo(&manager, "a"), Err(TransactError::NotConnected { .. })));
+    }
+
+    #[test]
+    fn device_config_prefers_running_device() {
+        let manager = SerialManager::new();
+        let mut idle = device("idle", "sim://echo");
+        idle.enabled = false;
+        manager.start_all(&config(vec![device("a", "sim://echo"), idle]), app());
+
+        // 单独启动的设备按其线程使用的配置返回，即使与已应用的配置不同
+        let mut b = device("b", "sim://echo");
+        b.display = Encoding::Hex;
+        manager.start_device(&b, app()).unwrap();
+
+        assert_eq!(manager.device_config("a").unwrap().port, "sim://echo");
+        assert_eq!(manager.device_config("b").unwrap().display, Encoding::Hex);
+        assert!(!manager.device_config("idle").unwrap().enabled);
+        assert!(manager.device_config("missing").is_none());
+        manager.stop_all();
+    }
+
+    #[test]
+    fn capture_records_running_config() {
+        let manager = SerialManager::new();
+        let mut a = device("a", "sim://echo");
+        a.display = Encoding::Hex;
+        manager.start_device(&a, app()).unwrap();
+        assert!(manager.start_capture("missing", None).is_err());
+
+        let path = std::env::temp_dir().join(format!("serial-capture-{}.jsonl", std::process::id()));
+        assert_eq!(manager.start_capture("a", Some(path.clone())).unwrap(), path);
+        assert_eq!(manager.stop_capture("a").unwrap(), Some(path.clone()));
+        let (recorded, _) = capture::read_capture(&path).unwrap();
+        assert_eq!(recorded, a);
+        manager.stop_all();
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn stuck_device_is_kept_and_not_restarted() {
+        let manager = SerialManager::new();
+        let (dev, release) = hung(device("a", "sim://echo"));
+        manager.running.lock().unwrap().insert("a".into(), dev);
+
+        // 超时后报告错误，设备仍占位，不能重复启动
+        assert!(manager.stop_device("a").is_err());
+        assert!(manager.start_device(&device("a", "sim://echo"), app()).is_err());
+
+        // 旧线程仍未退出时，配置变更不会重启该设备
+        let mut changed = device("a", "sim://echo");
+        changed.display = Encoding::Hex;
+        let result = manager.apply_config(&config(vec![changed.clone()]), app());
+        assert_eq!(result.stuck, ["a"]);
+        assert!(result.restarted.is_empty() && result.started.is_empty());
+        assert_eq!(manager.display_encoding("a"), Some(Encoding::Text));
+
+        // 线程退出后再次应用即可重启
+        release.send(()).unwrap();
+        assert!(wait_until(|| !alive(&manager, "a")));
+        let result = manager.apply_config(&config(vec![changed]), app());
+        assert_eq!(result.restarted, ["a"]);
+        assert_eq!(manager.display_encoding("a"), Some(Encoding::Hex));
+        assert_eq!(manager.stop_device("a"), Ok(true));
+    }
+
+    #[test]
+    fn restart_device_replaces_running_worker() {
+        let manager = SerialManager::new();
+        // 未运行时直接启动
+        manager.restart_device(&device("a", "sim://echo"), app()).unwrap();
+        assert!(wait_until(|| echo(&manager, "a").is_ok()));
+
+        let mut changed = device("a", "sim://echo");
+        changed.display = Encoding::Hex;
+        manager.restart_device(&changed, app()).unwrap();
+        assert_eq!(running_ids(&manager), ["a"]);
+        assert_eq!(manager.display_encoding("a"), Some(Encoding::Hex));
+        assert!(wait_until(|| echo(&manager, "a").is_ok()));
+        assert_eq!(manager.stop_device("a"), Ok(true));
+    }
+
+    #[test]
+    fn reconnects_after_disconnect() {
+        let manager = SerialManager::new();
+        manager.start_device(&device("a", "sim://echo?disconnect_after_ms=50"), app()).unwrap();
+        assert!(wait_until(|| manager
+            .stats()
+            .first()
+            .is_some_and(|s| s.read_errors >= 2 && s.reconnects >= 2)));
+        assert_eq!(manager.stop_device("a"), Ok(true));
+    }
+
+    #[test]
+    fn exited_device_is_restarted() {
+        let manager = SerialManager::new();
+        let mut dev = device("a", "sim://echo?disconnect_after_ms=50");
+        dev.reconnect.enabled = false;
+        let cfg = config(vec![dev.clone()]);
+        manager.start_all(&cfg, app());
+        assert!(wait_until(|| !alive(&manager, "a")));
+
+        // 重试耗尽后线程已退出：同一配置也会重启
+        let result = manager.apply_config(&cfg, app());
+        assert_eq!(result.restarted, ["a"]);
+        assert!(result.unchanged.is_empty());
+        assert!(manager.stop_all().is_empty());
+
+        manager.start_all(&cfg, app());
+        assert!(wait_until(|| !alive(&manager, "a")));
+        assert!(manager.start_device(&dev, app()).is_ok());
+        assert!(manager.stop_all().is_empty());
+    }
+
+    #[test]
+    fn invalid_framing_stops_the_worker() {
+        let manager = SerialManager::new();
+        let mut dev = device("a", "sim://echo");
+        let framing = serde_json::json!({ "type": "fixedLength", "length": 0 });
+        dev.framing = serde_json::from_value(framing).unwrap();
+        manager.start_device(&dev, app()).unwrap();
+        assert!(wait_until(|| !alive(&manager, "a")));
+        assert!(manager.stop_all().is_empty());
+    }
+
+    #[test]
+    fn apply_config_classifies_changes() {
+        let manager = SerialManager::new();
+        manager.start_all(
+            &config(vec![
+                device("kept", "sim://echo"),
+                device("changed", "sim://echo"),
+                device("removed", "sim://echo"),
+                device("disabled", "sim://echo"),
+            ]),
+            app(),
+        );
+
+        let mut changed = device("changed", "sim://echo");
+        changed.display = Encoding::Hex;
+        let mut disabled = device("disabled", "sim://echo");
+        disabled.enabled = false;
+        let mut idle = device("idle", "sim://echo");
+        idle.enabled = false;
+        let next = config(vec![
+            device("kept", "sim://echo"),
+            changed,
+            disabled,
+            device("added", "sim://echo"),
+            idle,
+        ]);
+
+        let started = Instant::now();
+        let mut result = manager.apply_config(&next, app());
+        assert!(started.elapsed() < STOP_TIMEOUT);
+        result.stopped.sort();
+        assert_eq!(result.started, ["added"]);
+        assert_eq!(result.stopped, ["disabled", "removed"]);
+        assert_eq!(result.restarted, ["changed"]);
+        assert_eq!(result.unchanged, ["kept"]);
+        assert_eq!(running_ids(&manager), ["added", "changed", "kept"]);
+        assert_eq!(manager.display_encoding("changed"), Some(Encoding::Hex));
+        // 命令读取的是最近一次应用的配置
+        assert_eq!(manager.config().devices, next.devices);
 
-        Some(RunningDevice { cancel, write_port })
+        // 再次应用相同配置不影响任何设备
+        let again = manager.apply_config(&next, app());
+        assert!(again.started.is_empty() && again.stopped.is_empty() && again.restarted.is_empty());
+        assert_eq!(again.unchanged.len(), 3);
+        assert!(manager.stop_all().is_empty());
     }
 }
@@ -1,21 +1,40 @@
+pub mod capture;
+pub mod checksum;
+pub mod codec;
 pub mod commands;
 pub mod config;
+pub mod framing;
 pub mod manager;
+pub mod modbus;
+pub mod net;
+pub mod ports;
+pub mod sim;
+pub mod stats;
+pub mod templates;
+pub mod transact;
+pub mod transport;
 
+use std::sync::{Arc, Mutex};
+
+use config::ConfigWarning;
 use manager::SerialManager;
 
 /// Tauri 托管状态：持有串口管理器
 ///
 /// 通过 `app.manage(SerialState::new())` 注入，
 /// 在命令函数中通过 `State<'_, SerialState>` 访问。
+/// `manager` 以 `Arc` 持有，便于阻塞型命令（如 Modbus 事务）移交到后台线程。
 pub struct SerialState {
-    pub manager: SerialManager,
+    pub manager: Arc<SerialManager>,
+    /// 启动时加载配置产生的警告；设置页打开较晚，`get_serial_config` 时重新推送
+    pub config_warnings: Mutex<Vec<ConfigWarning>>,
 }
 
 impl SerialState {
     pub fn new() -> Self {
         Self {
-            manager: SerialManager::new(),
+            manager: Arc::new(SerialManager::new()),
+            config_warnings: Mutex::new(Vec::new()),
         }
     }
 }
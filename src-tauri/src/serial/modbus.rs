@@ -0,0 +1,480 @@
+//! Modbus RTU 主站
+//!
+//! 复用 `SerialManager` 中设备的写端口与读线程：请求经 `transact` 写出，
+//! 应答由读线程分帧后交回。对应设备需配置 `framing: { "type": "modbusRtu" }`，
+//! 否则应答帧会被其它分帧方式切碎。
+
+use std::fmt;
+use std::time::Duration;
+
+use serde::Serialize;
+
+use super::checksum::crc16_modbus;
+use super::manager::SerialManager;
+use super::transact::TransactError;
+
+// ─── 功能码 ──────────────────────────────────────────────────────────────────
+
+pub const READ_COILS: u8 = 0x01;
+pub const READ_DISCRETE_INPUTS: u8 = 0x02;
+pub const READ_HOLDING_REGISTERS: u8 = 0x03;
+pub const READ_INPUT_REGISTERS: u8 = 0x04;
+pub const WRITE_SINGLE_COIL: u8 = 0x05;
+pub const WRITE_SINGLE_REGISTER: u8 = 0x06;
+pub const WRITE_MULTIPLE_COILS: u8 = 0x0F;
+pub const WRITE_MULTIPLE_REGISTERS: u8 = 0x10;
+
+/// 未指定超时时的默认应答等待时间
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_millis(1000);
+
+/// 广播地址：从站不应答，仅允许写操作
+const BROADCAST_UNIT: u8 = 0;
+
+// ─── 错误 ────────────────────────────────────────────────────────────────────
+
+/// Modbus 调用失败原因（序列化为 `{ kind: "exception", code: 2, ... }`）
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ModbusError {
+    /// 参数不合法（数量越界、广播读等），请求未发送
+    InvalidRequest { message: String },
+    /// 设备未启动或正在重连
+    NotConnected { message: String },
+    /// 写入端口失败
+    Io { message: String },
+    /// 超时未收到应答
+    Timeout { timeout_ms: u64 },
+    /// 从站返回异常应答
+    Exception { code: u8, message: String },
+    /// 应答格式与请求不符
+    InvalidResponse { message: String },
+}
+
+impl fmt::Display for ModbusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModbusError::InvalidRequest { message }
+            | ModbusError::NotConnected { message }
+            | ModbusError::Io { message }
+            | ModbusError::InvalidResponse { message } => f.write_str(message),
+            ModbusError::Timeout { timeout_ms } => write!(f, "Modbus 应答超时（{}ms）", timeout_ms),
+            ModbusError::Exception { code, message } => {
+                write!(f, "Modbus 异常 {:02X}: {}", code, message)
+            }
+        }
+    }
+}
+
+impl From<TransactError> for ModbusError {
+    fn from(e: TransactError) -> Self {
+        match e {
+            TransactError::InvalidRequest { message } => ModbusError::InvalidRequest { message },
+            TransactError::NotConnected { message } => ModbusError::NotConnected { message },
+            TransactError::Write { message } => ModbusError::Io { message },
+            TransactError::Timeout { timeout_ms } => ModbusError::Timeout { timeout_ms },
+        }
+    }
+}
+
+fn invalid_request(message: impl Into<String>) -> ModbusError {
+    ModbusError::InvalidRequest { message: message.into() }
+}
+
+fn invalid_response(message: impl Into<String>) -> ModbusError {
+    ModbusError::InvalidResponse { message: message.into() }
+}
+
+/// 标准异常码说明
+pub fn exception_message(code: u8) -> &'static str {
+    match code {
+        0x01 => "非法功能码",
+        0x02 => "非法数据地址",
+        0x03 => "非法数据值",
+        0x04 => "从站设备故障",
+        0x05 => "已确认，处理中",
+        0x06 => "从站设备忙",
+        0x08 => "存储奇偶校验错误",
+        0x0A => "网关路径不可用",
+        0x0B => "网关目标设备无响应",
+        _ => "未知异常",
+    }
+}
+
+// ─── 帧编解码 ────────────────────────────────────────────────────────────────
+
+/// 组装 RTU 帧：地址 + PDU + CRC16（低字节在前）
+pub fn encode_frame(unit: u8, pdu: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(pdu.len() + 3);
+    frame.push(unit);
+    frame.extend_from_slice(pdu);
+    let crc = crc16_modbus(&frame);
+    frame.extend_from_slice(&crc.to_le_bytes());
+    frame
+}
+
+/// 校验应答帧并返回功能码之后、CRC 之前的数据部分
+pub fn decode_response(unit: u8, function: u8, frame: &[u8]) -> Result<&[u8], ModbusError> {
+    if frame.len() < 5 {
+        return Err(invalid_response(format!("应答过短（{} 字节）", frame.len())));
+    }
+    let (body, crc) = frame.split_at(frame.len() - 2);
+    if crc != crc16_modbus(body).to_le_bytes() {
+        return Err(invalid_response("应答 CRC 校验失败"));
+    }
+    if body[0] != unit {
+        return Err(invalid_response(format!("应答地址 {} 与请求 {} 不符", body[0], unit)));
+    }
+    if body[1] == function | 0x80 {
+        let code = body[2];
+        return Err(ModbusError::Exception {
+            code,
+            message: exception_message(code).to_string(),
+        });
+    }
+    if body[1] != function {
+        return Err(invalid_response(format!(
+            "应答功能码 {:02X} 与请求 {:02X} 不符",
+            body[1], function
+        )));
+    }
+    Ok(&body[2..])
+}
+
+/// 线圈/离散量：按 LSB 优先打包为字节
+fn pack_bits(values: &[bool]) -> Vec<u8> {
+    let mut bytes = vec![0u8; values.len().div_ceil(8)];
+    for (i, &on) in values.iter().enumerate() {
+        if on {
+            bytes[i / 8] |= 1 << (i % 8);
+        }
+    }
+    bytes
+}
+
+fn unpack_bits(bytes: &[u8], count: usize) -> Vec<bool> {
+    (0..count).map(|i| bytes[i / 8] & (1 << (i % 8)) != 0).collect()
+}
+
+fn check_count(count: u16, max: u16) -> Result<(), ModbusError> {
+    if count == 0 || count > max {
+        return Err(invalid_request(format!("数量必须在 1..={} 之间，当前 {}", max, count)));
+    }
+    Ok(())
+}
+
+// ─── 请求 PDU ────────────────────────────────────────────────────────────────
+
+/// 功能码 01–04：起始地址 + 数量
+fn read_pdu(function: u8, addr: u16, count: u16) -> Vec<u8> {
+    let [ah, al] = addr.to_be_bytes();
+    let [ch, cl] = count.to_be_bytes();
+    vec![function, ah, al, ch, cl]
+}
+
+/// 功能码 05：ON 为 0xFF00，OFF 为 0x0000
+fn write_single_coil_pdu(addr: u16, value: bool) -> Vec<u8> {
+    let [ah, al] = addr.to_be_bytes();
+    vec![WRITE_SINGLE_COIL, ah, al, if value { 0xFF } else { 0x00 }, 0x00]
+}
+
+/// 功能码 06
+fn write_single_register_pdu(addr: u16, value: u16) -> Vec<u8> {
+    let [ah, al] = addr.to_be_bytes();
+    let [vh, vl] = value.to_be_bytes();
+    vec![WRITE_SINGLE_REGISTER, ah, al, vh, vl]
+}
+
+/// 功能码 15：起始地址 + 数量 + 字节数 + 按位打包的线圈值
+fn write_multiple_coils_pdu(addr: u16, values: &[bool]) -> Result<Vec<u8>, ModbusError> {
+    let count = u16::try_from(values.len()).map_err(|_| invalid_request("线圈数量过多"))?;
+    check_count(count, 1968)?;
+    let packed = pack_bits(values);
+    let mut pdu = read_pdu(WRITE_MULTIPLE_COILS, addr, count);
+    pdu.push(packed.len() as u8);
+    pdu.extend_from_slice(&packed);
+    Ok(pdu)
+}
+
+/// 功能码 16：起始地址 + 数量 + 字节数 + 大端寄存器值
+fn write_multiple_registers_pdu(addr: u16, values: &[u16]) -> Result<Vec<u8>, ModbusError> {
+    let count = u16::try_from(values.len()).map_err(|_| invalid_request("寄存器数量过多"))?;
+    check_count(count, 123)?;
+    let mut pdu = read_pdu(WRITE_MULTIPLE_REGISTERS, addr, count);
+    pdu.push((count * 2) as u8);
+    pdu.extend(values.iter().flat_map(|v| v.to_be_bytes()));
+    Ok(pdu)
+}
+
+// ─── 主站 ────────────────────────────────────────────────────────────────────
+
+/// 面向单个串口设备的 Modbus RTU 主站
+pub struct ModbusMaster<'a> {
+    manager: &'a SerialManager,
+    device_id: &'a str,
+    timeout: Duration,
+}
+
+impl<'a> ModbusMaster<'a> {
+    pub fn new(manager: &'a SerialManager, device_id: &'a str, timeout: Duration) -> Self {
+        Self {
+            manager,
+            device_id,
+            timeout,
+        }
+    }
+
+    /// 发送一个 PDU 并返回应答数据部分；广播写入不等待应答，返回空
+    fn request(&self, unit: u8, pdu: &[u8]) -> Result<Vec<u8>, ModbusError> {
+        let function = pdu[0];
+        let frame = encode_frame(unit, pdu);
+        log::debug!(
+            "[Modbus {}] TX unit={} fc={:02X} {}B",
+            self.device_id,
+            unit,
+            function,
+            frame.len()
+        );
+
+        if unit == BROADCAST_UNIT {
+            self.manager
+                .send_to_device(self.device_id, frame)
+                .map_err(|message| ModbusError::Io { message })?;
+            return Ok(Vec::new());
+        }
+
+        let matcher = Box::new(move |f: &[u8]| {
+            f.len() >= 2 && f[0] == unit && (f[1] & 0x7F) == function
+        });
+        let response = self
+            .manager
+            .transact(self.device_id, &frame, self.timeout, matcher)?;
+        decode_response(unit, function, &response).map(|d| d.to_vec())
+    }
+
+    fn read_bits(&self, unit: u8, function: u8, addr: u16, count: u16) -> Result<Vec<bool>, ModbusError> {
+        check_count(count, 2000)?;
+        self.reject_broadcast(unit)?;
+        let data = self.request(unit, &read_pdu(function, addr, count))?;
+        let expected = (count as usize).div_ceil(8);
+        if data.first().map(|&n| n as usize) != Some(expected) || data.len() != expected + 1 {
+            return Err(invalid_response(format!("应答字节数与请求数量 {} 不符", count)));
+        }
+        Ok(unpack_bits(&data[1..], count as usize))
+    }
+
+    fn read_registers(&self, unit: u8, function: u8, addr: u16, count: u16) -> Result<Vec<u16>, ModbusError> {
+        check_count(count, 125)?;
+        self.reject_broadcast(unit)?;
+        let data = self.request(unit, &read_pdu(function, addr, count))?;
+        let expected = count as usize * 2;
+        if data.first().map(|&n| n as usize) != Some(expected) || data.len() != expected + 1 {
+            return Err(invalid_response(format!("应答字节数与请求数量 {} 不符", count)));
+        }
+        Ok(data[1..]
+            .chunks_exact(2)
+            .map(|c| u16::from_be_bytes([c[0], c[1]]))
+            .collect())
+    }
+
+    fn reject_broadcast(&self, unit: u8) -> Result<(), ModbusError> {
+        if unit == BROADCAST_UNIT {
+            return Err(invalid_request("广播地址 0 不支持读操作"));
+        }
+        Ok(())
+    }
+
+    /// 写类应答应回显请求中的前 4 字节（地址 + 值/数量）
+    fn expect_echo(&self, unit: u8, data: &[u8], echo: &[u8]) -> Result<(), ModbusError> {
+        if unit != BROADCAST_UNIT && data != echo {
+            return Err(invalid_response("写应答回显与请求不一致"));
+        }
+        Ok(())
+    }
+
+    /// 功能码 01：读线圈
+    pub fn read_coils(&self, unit: u8, addr: u16, count: u16) -> Result<Vec<bool>, ModbusError> {
+        self.read_bits(unit, READ_COILS, addr, count)
+    }
+
+    /// 功能码 02：读离散输入
+    pub fn read_discrete_inputs(&self, unit: u8, addr: u16, count: u16) -> Result<Vec<bool>, ModbusError> {
+        self.read_bits(unit, READ_DISCRETE_INPUTS, addr, count)
+    }
+
+    /// 功能码 03：读保持寄存器
+    pub fn read_holding_registers(&self, unit: u8, addr: u16, count: u16) -> Result<Vec<u16>, ModbusError> {
+        self.read_registers(unit, READ_HOLDING_REGISTERS, addr, count)
+    }
+
+    /// 功能码 04：读输入寄存器
+    pub fn read_input_registers(&self, unit: u8, addr: u16, count: u16) -> Result<Vec<u16>, ModbusError> {
+        self.read_registers(unit, READ_INPUT_REGISTERS, addr, count)
+    }
+
+    /// 功能码 05：写单个线圈
+    pub fn write_single_coil(&self, unit: u8, addr: u16, value: bool) -> Result<(), ModbusError> {
+        let pdu = write_single_coil_pdu(addr, value);
+        let data = self.request(unit, &pdu)?;
+        self.expect_echo(unit, &data, &pdu[1..])
+    }
+
+    /// 功能码 06：写单个保持寄存器
+    pub fn write_single_register(&self, unit: u8, addr: u16, value: u16) -> Result<(), ModbusError> {
+        let pdu = write_single_register_pdu(addr, value);
+        let data = self.request(unit, &pdu)?;
+        self.expect_echo(unit, &data, &pdu[1..])
+    }
+
+    /// 功能码 15：写多个线圈
+    pub fn write_multiple_coils(&self, unit: u8, addr: u16, values: &[bool]) -> Result<(), ModbusError> {
+        let pdu = write_multiple_coils_pdu(addr, values)?;
+        let data = self.request(unit, &pdu)?;
+        self.expect_echo(unit, &data, &pdu[1..5])
+    }
+
+    /// 功能码 16：写多个保持寄存器
+    pub fn write_multiple_registers(&self, unit: u8, addr: u16, values: &[u16]) -> Result<(), ModbusError> {
+        let pdu = write_multiple_registers_pdu(addr, values)?;
+        let data = self.request(unit, &pdu)?;
+        self.expect_echo(unit, &data, &pdu[1..5])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serial::config::SerialConfig;
+    use std::time::Instant;
+
+    #[test]
+    fn crc16_matches_reference() {
+        assert_eq!(crc16_modbus(b"123456789"), 0x4B37);
+        assert_eq!(
+            encode_frame(0x01, &[READ_HOLDING_REGISTERS, 0x00, 0x00, 0x00, 0x0A]),
+            [0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD]
+        );
+    }
+
+    #[test]
+    fn requests_are_encoded_per_function_code() {
+        let frame = |unit: u8, pdu: Vec<u8>| encode_frame(unit, &pdu);
+        assert_eq!(
+            frame(0x11, read_pdu(READ_COILS, 0x13, 0x25)),
+            [0x11, 0x01, 0x00, 0x13, 0x00, 0x25, 0x0E, 0x84]
+        );
+        assert_eq!(
+            frame(0x01, read_pdu(READ_DISCRETE_INPUTS, 0xC4, 0x16)),
+            [0x01, 0x02, 0x00, 0xC4, 0x00, 0x16, 0xB8, 0x39]
+        );
+        assert_eq!(
+            frame(0x01, read_pdu(READ_HOLDING_REGISTERS, 0x00, 0x0A)),
+            [0x01, 0x03, 0x00, 0x00, 0x00, 0x0A, 0xC5, 0xCD]
+        );
+        assert_eq!(
+            frame(0x01, read_pdu(READ_INPUT_REGISTERS, 0x08, 0x01)),
+            [0x01, 0x04, 0x00, 0x08, 0x00, 0x01, 0xB0, 0x08]
+        );
+        assert_eq!(
+            frame(0x01, write_single_coil_pdu(0xAC, true)),
+            [0x01, 0x05, 0x00, 0xAC, 0xFF, 0x00, 0x4C, 0x1B]
+        );
+        assert_eq!(write_single_coil_pdu(0xAC, false)[3..], [0x00, 0x00]);
+        assert_eq!(
+            frame(0x01, write_single_register_pdu(0x01, 0x03)),
+            [0x01, 0x06, 0x00, 0x01, 0x00, 0x03, 0x98, 0x0B]
+        );
+        let coils = [true, false, true, true, false, false, true, true, true, false];
+        assert_eq!(
+            frame(0x01, write_multiple_coils_pdu(0x13, &coils).unwrap()),
+            [0x01, 0x0F, 0x00, 0x13, 0x00, 0x0A, 0x02, 0xCD, 0x01, 0x72, 0xCB]
+        );
+        assert_eq!(
+            frame(0x01, write_multiple_registers_pdu(0x01, &[0x000A, 0x0102]).unwrap()),
+            [0x01, 0x10, 0x00, 0x01, 0x00, 0x02, 0x04, 0x00, 0x0A, 0x01, 0x02, 0x92, 0x30]
+        );
+
+        assert!(write_multiple_coils_pdu(0, &[]).is_err());
+        assert!(write_multiple_coils_pdu(0, &[false; 1969]).is_err());
+        assert!(write_multiple_registers_pdu(0, &[0; 124]).is_err());
+    }
+
+    #[test]
+    fn exception_responses_are_mapped() {
+        let frame = encode_frame(0x01, &[READ_HOLDING_REGISTERS | 0x80, 0x02]);
+        assert_eq!(frame, [0x01, 0x83, 0x02, 0xC0, 0xF1]);
+        match decode_response(0x01, READ_HOLDING_REGISTERS, &frame) {
+            Err(ModbusError::Exception { code, message }) => {
+                assert_eq!(code, 0x02);
+                assert_eq!(message, "非法数据地址");
+            }
+            other => panic!("应为异常应答: {:?}", other),
+        }
+        assert_eq!(exception_message(0x7F), "未知异常");
+
+        // 其它功能码的异常应答不算匹配
+        let frame = encode_frame(0x01, &[WRITE_SINGLE_COIL | 0x80, 0x02]);
+        let err = decode_response(0x01, READ_HOLDING_REGISTERS, &frame).unwrap_err();
+        assert!(matches!(err, ModbusError::InvalidResponse { .. }));
+    }
+
+    #[test]
+    fn malformed_responses_are_rejected() {
+        let mut frame = encode_frame(0x01, &[READ_HOLDING_REGISTERS, 0x02, 0x12, 0x34]);
+        let data = decode_response(0x01, READ_HOLDING_REGISTERS, &frame).unwrap();
+        assert_eq!(data, [0x02, 0x12, 0x34]);
+        assert!(decode_response(0x02, READ_HOLDING_REGISTERS, &frame).is_err());
+        assert!(decode_response(0x01, READ_HOLDING_REGISTERS, &frame[..4]).is_err());
+        *frame.last_mut().unwrap() ^= 0xFF;
+        assert!(decode_response(0x01, READ_HOLDING_REGISTERS, &frame).is_err());
+    }
+
+    #[test]
+    fn round_trip_against_simulated_plc() {
+        let device = serde_json::from_value(serde_json::json!({
+            "device_id": "plc",
+            "name": "plc",
+            "role": "plc",
+            "port": "sim://plc?unit=3",
+            "baud_rate": 9600,
+            "data_bits": 8,
+            "stop_bits": 1,
+            "parity": "None",
+            "enabled": true,
+            "framing": { "type": "modbusRtu" },
+        }))
+        .unwrap();
+        let config = SerialConfig {
+            devices: vec![device],
+            ..Default::default()
+        };
+        let manager = SerialManager::new();
+        manager.start_all(&config, tauri::test::mock_app().handle().clone());
+        let master = ModbusMaster::new(&manager, "plc", DEFAULT_TIMEOUT);
+
+        // 读线程打开端口后才能收发
+        let deadline = Instant::now() + Duration::from_secs(3);
+        while master.read_holding_registers(3, 0, 1).is_err() {
+            assert!(Instant::now() < deadline, "模拟 PLC 未就绪");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        master.write_single_register(3, 10, 0xBEEF).unwrap();
+        master.write_multiple_registers(3, 11, &[1, 2, 3]).unwrap();
+        assert_eq!(master.read_holding_registers(3, 10, 4).unwrap(), [0xBEEF, 1, 2, 3]);
+        assert_eq!(master.read_input_registers(3, 12, 2).unwrap(), [2, 3]);
+
+        let coils = [true, false, true, true, false, false, true, true, true];
+        master.write_multiple_coils(3, 20, &coils).unwrap();
+        master.write_single_coil(3, 21, true).unwrap();
+        let mut expected = coils.to_vec();
+        expected[1] = true;
+        assert_eq!(master.read_coils(3, 20, 9).unwrap(), expected);
+        assert_eq!(master.read_discrete_inputs(3, 28, 1).unwrap(), [true]);
+
+        let err = master.read_holding_registers(3, 999, 2).unwrap_err();
+        assert!(matches!(err, ModbusError::Exception { code: 0x02, .. }));
+        let err = master.read_coils(0, 0, 1).unwrap_err();
+        assert!(matches!(err, ModbusError::InvalidRequest { .. }));
+        assert!(manager.stop_all().is_empty());
+    }
+}
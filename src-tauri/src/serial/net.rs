@@ -0,0 +1,503 @@
+//! 串口服务器（Moxa 类）网络传输
+//!
+//! 在 `SerialDeviceConfig.port` 中以 URL 形式选择：
+//!
+//! - `tcp://192.168.0.10:4001`：TCP 原始透传（Raw / TCP Server 模式），字节流原样收发
+//! - `rfc2217://192.168.0.10:4001`：RFC 2217（Telnet COM-PORT-OPTION），
+//!   连接后把设备配置中的波特率、数据位、校验、停止位下发到串口服务器
+//!
+//! 两者都实现 `Transport`，读写、分帧、事件推送与本机串口走同一条路径；
+//! 连接断开时读操作返回错误，由读线程按重连策略重试。
+
+use std::collections::HashSet;
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use super::config::SerialDeviceConfig;
+use super::transport::Transport;
+
+const TCP_SCHEME: &str = "tcp://";
+const RFC2217_SCHEME: &str = "rfc2217://";
+
+/// 建立 TCP 连接的超时
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+/// 单次写入的超时，避免对端不读时写路径无限阻塞
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// 端口名是否指向网络串口服务器
+pub fn is_net_port(port: &str) -> bool {
+    port.starts_with(TCP_SCHEME) || port.starts_with(RFC2217_SCHEME)
+}
+
+/// 网络传输协议
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetProtocol {
+    Raw,
+    Rfc2217,
+}
+
+/// 解析后的网络端口地址
+#[derive(Debug, Clone)]
+pub struct NetSpec {
+    pub protocol: NetProtocol,
+    /// `host:port`
+    pub addr: String,
+}
+
+impl NetSpec {
+    /// 解析 `tcp://host:port` / `rfc2217://host:port`
+    pub fn parse(port: &str) -> Result<Self, String> {
+        let (protocol, rest) = if let Some(rest) = port.strip_prefix(TCP_SCHEME) {
+            (NetProtocol::Raw, rest)
+        } else if let Some(rest) = port.strip_prefix(RFC2217_SCHEME) {
+            (NetProtocol::Rfc2217, rest)
+        } else {
+            return Err(format!("{} 不是网络串口地址", port));
+        };
+        let addr = rest.trim_end_matches('/');
+        let (host, tcp_port) = addr
+            .rsplit_once(':')
+            .ok_or_else(|| format!("{} 缺少端口号（应为 host:port）", port))?;
+        if host.is_empty() {
+            return Err(format!("{} 缺少主机地址", port));
+        }
+        match tcp_port.parse::<u16>() {
+            Ok(p) if p != 0 => {}
+            _ => return Err(format!("{} 的端口号无效: {}", port, tcp_port)),
+        }
+        Ok(Self {
+            protocol,
+            addr: addr.to_string(),
+        })
+    }
+}
+
+// ─── Telnet / RFC 2217 ───────────────────────────────────────────────────────
+
+const IAC: u8 = 255;
+const DONT: u8 = 254;
+const DO: u8 = 253;
+const WONT: u8 = 252;
+const WILL: u8 = 251;
+const SB: u8 = 250;
+const SE: u8 = 240;
+
+const OPT_BINARY: u8 = 0;
+const OPT_SGA: u8 = 3;
+const OPT_COM_PORT: u8 = 44;
+
+/// COM-PORT-OPTION 客户端子命令（服务器应答为 +100）
+const CPO_SET_BAUDRATE: u8 = 1;
+const CPO_SET_DATASIZE: u8 = 2;
+const CPO_SET_PARITY: u8 = 3;
+const CPO_SET_STOPSIZE: u8 = 4;
+const CPO_SET_CONTROL: u8 = 5;
+
+/// SET-CONTROL 取值
+const CONTROL_NO_FLOW: u8 = 1;
+const CONTROL_DTR_ON: u8 = 8;
+
+/// 子协商内容的长度上限（超出部分丢弃，防止异常对端撑爆内存）
+const MAX_SB_LEN: usize = 64;
+
+#[derive(Debug, Clone, Copy)]
+enum TelnetState {
+    Data,
+    Iac,
+    Negotiate(u8),
+    Sub,
+    SubIac,
+}
+
+/// Telnet 流解析：剥离 IAC 命令、还原转义的 0xFF，并应答选项协商
+struct Telnet {
+    state: TelnetState,
+    /// 本端已同意启用的选项（WILL）
+    local: HashSet<u8>,
+    /// 对端已同意启用的选项（DO）
+    remote: HashSet<u8>,
+    sub: Vec<u8>,
+}
+
+impl Telnet {
+    fn new() -> Self {
+        Self {
+            state: TelnetState::Data,
+            local: HashSet::new(),
+            remote: HashSet::new(),
+            sub: Vec::new(),
+        }
+    }
+
+    /// 原地解码 `buf`，返回数据字节数与需要回给对端的协商应答
+    fn decode(&mut self, buf: &mut [u8]) -> (usize, Vec<u8>) {
+        let mut out = 0;
+        let mut replies = Vec::new();
+        for i in 0..buf.len() {
+            let b = buf[i];
+            self.state = match self.state {
+                TelnetState::Data if b == IAC => TelnetState::Iac,
+                TelnetState::Data => {
+                    buf[out] = b;
+                    out += 1;
+                    TelnetState::Data
+                }
+                TelnetState::Iac => match b {
+                    IAC => {
+                        buf[out] = IAC;
+                        out += 1;
+                        TelnetState::Data
+                    }
+                    DO | DONT | WILL | WONT => TelnetState::Negotiate(b),
+                    SB => {
+                        self.sub.clear();
+                        TelnetState::Sub
+                    }
+                    // NOP / GA 等其余命令忽略
+                    _ => TelnetState::Data,
+                },
+                TelnetState::Negotiate(cmd) => {
+                    self.negotiate(cmd, b, &mut replies);
+                    TelnetState::Data
+                }
+                TelnetState::Sub if b == IAC => TelnetState::SubIac,
+                TelnetState::Sub => {
+                    self.push_sub(b);
+                    TelnetState::Sub
+                }
+                TelnetState::SubIac => match b {
+                    SE => {
+                        self.on_subnegotiation();
+                        TelnetState::Data
+                    }
+                    IAC => {
+                        self.push_sub(IAC);
+                        TelnetState::Sub
+                    }
+                    _ => TelnetState::Sub,
+                },
+            };
+        }
+        (out, replies)
+    }
+
+    fn push_sub(&mut self, b: u8) {
+        if self.sub.len() < MAX_SB_LEN {
+            self.sub.push(b);
+        }
+    }
+
+    /// 只在选项状态变化时应答，避免双方反复确认形成协商循环
+    fn negotiate(&mut self, cmd: u8, opt: u8, replies: &mut Vec<u8>) {
+        let local_ok = matches!(opt, OPT_BINARY | OPT_SGA | OPT_COM_PORT);
+        let remote_ok = matches!(opt, OPT_BINARY | OPT_SGA);
+        let reply = match cmd {
+            DO if !local_ok => Some(WONT),
+            DO => self.local.insert(opt).then_some(WILL),
+            DONT => {
+                if opt == OPT_COM_PORT {
+                    log::warn!("串口服务器拒绝 RFC 2217 COM-PORT-OPTION，串口参数未能下发");
+                }
+                self.local.remove(&opt).then_some(WONT)
+            }
+            WILL if !remote_ok => Some(DONT),
+            WILL => self.remote.insert(opt).then_some(DO),
+            WONT => self.remote.remove(&opt).then_some(DONT),
+            _ => None,
+        };
+        if let Some(reply) = reply {
+            replies.extend_from_slice(&[IAC, reply, opt]);
+        }
+    }
+
+    fn on_subnegotiation(&mut self) {
+        if let [OPT_COM_PORT, code, value @ ..] = self.sub.as_slice() {
+            log::debug!(
+                "RFC 2217 应答: 命令 {} 值 {:02X?}",
+                code.wrapping_sub(100),
+                value
+            );
+        }
+    }
+
+    /// 连接建立后主动发送的协商：双向 BINARY、抑制 GA，并声明 COM-PORT-OPTION
+    fn handshake(&mut self) -> Vec<u8> {
+        self.local.extend([OPT_BINARY, OPT_SGA, OPT_COM_PORT]);
+        self.remote.extend([OPT_BINARY, OPT_SGA]);
+        vec![
+            IAC, WILL, OPT_BINARY, IAC, DO, OPT_BINARY, IAC, WILL, OPT_SGA, IAC, DO, OPT_SGA, IAC,
+            WILL, OPT_COM_PORT,
+        ]
+    }
+}
+
+/// 按设备配置生成 COM-PORT-OPTION 子协商序列（波特率、数据位、校验、停止位、流控、DTR）
+fn com_port_settings(cfg: &SerialDeviceConfig) -> Vec<u8> {
+    let parity = match cfg.parity.as_str() {
+        "Odd" => 2,
+        "Even" => 3,
+        _ => 1,
+    };
+    let stop = if cfg.stop_bits == 2 { 2 } else { 1 };
+    let mut out = Vec::new();
+    let mut sub = |cmd: u8, value: &[u8]| {
+        out.extend_from_slice(&[IAC, SB, OPT_COM_PORT, cmd]);
+        out.extend(escape_iac(value));
+        out.extend_from_slice(&[IAC, SE]);
+    };
+    sub(CPO_SET_BAUDRATE, &cfg.baud_rate.to_be_bytes());
+    sub(CPO_SET_DATASIZE, &[cfg.data_bits]);
+    sub(CPO_SET_PARITY, &[parity]);
+    sub(CPO_SET_STOPSIZE, &[stop]);
+    sub(CPO_SET_CONTROL, &[CONTROL_NO_FLOW]);
+    sub(CPO_SET_CONTROL, &[CONTROL_DTR_ON]);
+    out
+}
+
+/// 数据中的 0xFF 需写成 IAC IAC
+fn escape_iac(data: &[u8]) -> Vec<u8> {
+    let mut out = Vec::with_capacity(data.len() + 4);
+    for &b in data {
+        out.push(b);
+        if b == IAC {
+            out.push(IAC);
+        }
+    }
+    out
+}
+
+// ─── NetTransport ─────────────────────────────────────────────────────────────
+
+/// TCP 原始透传 / RFC 2217 传输
+///
+/// 读端持有独立的 `TcpStream`；写端与读端应答 Telnet 协商时共用同一把写锁，
+/// 保证 IAC 序列不会与数据交错。
+pub struct NetTransport {
+    reader: TcpStream,
+    writer: Arc<Mutex<TcpStream>>,
+    /// RFC 2217 模式下的 Telnet 解析状态；原始透传为 None
+    telnet: Option<Telnet>,
+}
+
+impl NetTransport {
+    pub fn open(cfg: &SerialDeviceConfig) -> io::Result<Self> {
+        let spec = NetSpec::parse(&cfg.port)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let stream = connect(&spec.addr)?;
+        stream.set_read_timeout(Some(cfg.framing.read_timeout().max(Duration::from_millis(1))))?;
+        stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        stream.set_nodelay(true)?;
+
+        let mut transport = NetTransport {
+            writer: Arc::new(Mutex::new(stream.try_clone()?)),
+            reader: stream,
+            telnet: None,
+        };
+        if spec.protocol == NetProtocol::Rfc2217 {
+            let mut telnet = Telnet::new();
+            let mut init = telnet.handshake();
+            init.extend(com_port_settings(cfg));
+            transport.writer.lock().unwrap().write_all(&init)?;
+            transport.telnet = Some(telnet);
+            log::info!(
+                "[串口 {}|{}] RFC 2217 已下发参数: {} {}{}{}",
+                cfg.name,
+                cfg.port,
+                cfg.baud_rate,
+                cfg.data_bits,
+                cfg.parity.chars().next().unwrap_or('N'),
+                cfg.stop_bits
+            );
+        }
+        Ok(transport)
+    }
+}
+
+/// 依次尝试解析出的地址，返回第一个连接成功的
+fn connect(addr: &str) -> io::Result<TcpStream> {
+    let mut last_err = None;
+    for sock in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&sock, CONNECT_TIMEOUT) {
+            Ok(s) => return Ok(s),
+            Err(e) => last_err = Some(e),
+        }
+    }
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(io::ErrorKind::NotFound, format!("无法解析地址 {}", addr))
+    }))
+}
+
+impl Read for NetTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.reader.read(buf)?;
+            // TCP 读到 0 表示对端关闭；转为错误以触发重连（串口读超时才返回 0）
+            if n == 0 {
+                return Err(io::Error::new(io::ErrorKind::ConnectionAborted, "对端关闭了连接"));
+            }
+            let Some(telnet) = self.telnet.as_mut() else {
+                return Ok(n);
+            };
+            let (len, replies) = telnet.decode(&mut buf[..n]);
+            if !replies.is_empty() {
+                self.writer.lock().unwrap().write_all(&replies)?;
+            }
+            // 本次只收到协商命令时继续读，不向上层返回 0
+            if len > 0 {
+                return Ok(len);
+            }
+        }
+    }
+}
+
+impl Write for NetTransport {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let mut writer = self.writer.lock().unwrap();
+        if self.telnet.is_some() {
+            writer.write_all(&escape_iac(data))?;
+        } else {
+            writer.write_all(data)?;
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.lock().unwrap().flush()
+    }
+}
+
+impl Transport for NetTransport {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(NetTransport {
+            reader: self.reader.try_clone()?,
+            writer: self.writer.clone(),
+            telnet: self.telnet.as_ref().map(|_| Telnet::new()),
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::net::TcpListener;
+    use std::thread;
+
+    fn device(port: String, baud_rate: u32) -> SerialDeviceConfig {
+        serde_json::from_value(serde_json::json!({
+            "device_id": "net",
+            "name": "net",
+            "role": "scanner",
+            "port": port,
+            "baud_rate": baud_rate,
+            "data_bits": 8,
+            "stop_bits": 1,
+            "parity": "Even",
+            "enabled": true,
+        }))
+        .unwrap()
+    }
+
+    /// 读够 `len` 字节（读超时时继续等待）
+    fn read_exact(port: &mut impl Read, len: usize) -> Vec<u8> {
+        let mut out = Vec::new();
+        let mut buf = [0u8; 64];
+        while out.len() < len {
+            match port.read(&mut buf[..len - out.len()]) {
+                Ok(n) => out.extend_from_slice(&buf[..n]),
+                Err(e) => assert!(
+                    matches!(e.kind(), io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock),
+                    "读取失败: {}",
+                    e
+                ),
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn tcp_passes_bytes_through() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut buf = [0u8; 4];
+            stream.read_exact(&mut buf).unwrap();
+            stream.write_all(&buf).unwrap();
+        });
+
+        let mut port = NetTransport::open(&device(format!("tcp://{}", addr), 9600)).unwrap();
+        let mut writer = port.try_clone_transport().unwrap();
+        writer.write_all(&[b'a', IAC, SE, b'z']).unwrap();
+        assert_eq!(read_exact(&mut port, 4), [b'a', IAC, SE, b'z']);
+        server.join().unwrap();
+
+        // 对端关闭后读操作报错，触发重连
+        assert!(port.read(&mut [0u8; 4]).is_err());
+    }
+
+    #[test]
+    fn rfc2217_negotiates_and_escapes() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        // 波特率 0xFFFF 的低两字节需在子协商中转义
+        let mut expected = vec![
+            IAC, WILL, OPT_BINARY, IAC, DO, OPT_BINARY, IAC, WILL, OPT_SGA, IAC, DO, OPT_SGA, IAC,
+            WILL, OPT_COM_PORT,
+        ];
+        for (cmd, value) in [
+            (CPO_SET_BAUDRATE, &[0, 0, IAC, IAC, IAC, IAC][..]),
+            (CPO_SET_DATASIZE, &[8]),
+            (CPO_SET_PARITY, &[3]),
+            (CPO_SET_STOPSIZE, &[1]),
+            (CPO_SET_CONTROL, &[CONTROL_NO_FLOW]),
+            (CPO_SET_CONTROL, &[CONTROL_DTR_ON]),
+        ] {
+            expected.extend_from_slice(&[IAC, SB, OPT_COM_PORT, cmd]);
+            expected.extend_from_slice(value);
+            expected.extend_from_slice(&[IAC, SE]);
+        }
+
+        let server = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut init = vec![0u8; expected.len()];
+            stream.read_exact(&mut init).unwrap();
+            assert_eq!(init, expected);
+
+            // 已启用的选项不再应答；不支持的 ECHO(1) 应回 DONT；子协商应答不出现在数据中
+            let mut script = vec![IAC, DO, OPT_COM_PORT, IAC, WILL, 1];
+            script.extend_from_slice(&[IAC, SB, OPT_COM_PORT, 100 + CPO_SET_BAUDRATE]);
+            script.extend_from_slice(&[0, 0, 0x25, 0x80, IAC, SE, b'a', IAC, IAC, b'b']);
+            stream.write_all(&script).unwrap();
+
+            let mut reply = [0u8; 3];
+            stream.read_exact(&mut reply).unwrap();
+            assert_eq!(reply, [IAC, DONT, 1]);
+            let mut data = [0u8; 3];
+            stream.read_exact(&mut data).unwrap();
+            assert_eq!(data, [IAC, IAC, b'c']);
+        });
+
+        let cfg = device(format!("rfc2217://{}", addr), 0xFFFF);
+        let mut port = NetTransport::open(&cfg).unwrap();
+        assert_eq!(read_exact(&mut port, 3), [b'a', IAC, b'b']);
+        port.write_all(&[IAC, b'c']).unwrap();
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn telnet_decode_handles_split_sequences() {
+        let mut telnet = Telnet::new();
+        let mut first = [b'x', IAC];
+        assert_eq!(telnet.decode(&mut first).0, 1);
+        let mut second = [IAC, IAC, WILL];
+        let (n, replies) = telnet.decode(&mut second);
+        assert_eq!(&second[..n], [IAC]);
+        assert!(replies.is_empty());
+        let mut third = [OPT_SGA, b'y'];
+        let (n, replies) = telnet.decode(&mut third);
+        assert_eq!(&third[..n], b"y");
+        assert_eq!(replies, [IAC, DO, OPT_SGA]);
+    }
+}
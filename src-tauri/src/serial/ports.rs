@@ -0,0 +1,157 @@
+//! 串口枚举与热插拔检测
+//!
+//! `list_ports` 返回带 USB 信息（VID/PID、序列号、厂商、产品名）的端口列表；
+//! `spawn_port_watcher` 在后台轮询端口列表，变化时推送 `serial:ports-changed`。
+//! 设备配置可通过 `usb_serial` 绑定 USB 序列号，打开时由 `resolve_port` 解析为实际端口名，
+//! 避免 `/dev/ttyUSBn` 编号在重启或重新插拔后变化。
+
+use std::collections::HashSet;
+use std::io;
+use std::time::Duration;
+
+use serde::Serialize;
+use serialport::SerialPortType;
+use tauri::{AppHandle, Emitter};
+
+use super::config::SerialDeviceConfig;
+
+/// 热插拔轮询间隔
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 端口类型
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum PortKind {
+    Usb,
+    Pci,
+    Bluetooth,
+    Unknown,
+}
+
+/// 单个可用串口的详细信息（`list_available_ports` 返回值）
+#[derive(Debug, Clone, Serialize, PartialEq, Eq)]
+pub struct PortInfo {
+    pub port_name: String,
+    pub kind: PortKind,
+    /// USB 厂商 ID（仅 USB 端口）
+    pub vid: Option<u16>,
+    /// USB 产品 ID（仅 USB 端口）
+    pub pid: Option<u16>,
+    /// USB 序列号，可用于 `SerialDeviceConfig::usb_serial` 绑定
+    pub serial_number: Option<String>,
+    pub manufacturer: Option<String>,
+    pub product: Option<String>,
+}
+
+impl From<serialport::SerialPortInfo> for PortInfo {
+    fn from(p: serialport::SerialPortInfo) -> Self {
+        let mut info = PortInfo {
+            port_name: p.port_name,
+            kind: PortKind::Unknown,
+            vid: None,
+            pid: None,
+            serial_number: None,
+            manufacturer: None,
+            product: None,
+        };
+        match p.port_type {
+            SerialPortType::UsbPort(usb) => {
+                info.kind = PortKind::Usb;
+                info.vid = Some(usb.vid);
+                info.pid = Some(usb.pid);
+                info.serial_number = usb.serial_number;
+                info.manufacturer = usb.manufacturer;
+                info.product = usb.product;
+            }
+            SerialPortType::PciPort => info.kind = PortKind::Pci,
+            SerialPortType::BluetoothPort => info.kind = PortKind::Bluetooth,
+            SerialPortType::Unknown => {}
+        }
+        info
+    }
+}
+
+/// 枚举本机可用串口（按端口名排序）
+pub fn list_ports() -> Result<Vec<PortInfo>, String> {
+    let mut ports: Vec<PortInfo> = serialport::available_ports()
+        .map_err(|e| format!("获取端口列表失败: {}", e))?
+        .into_iter()
+        .map(PortInfo::from)
+        .collect();
+    ports.sort_by(|a, b| a.port_name.cmp(&b.port_name));
+    Ok(ports)
+}
+
+/// 解析设备实际要打开的端口名
+///
+/// 配置了 `usb_serial` 时按 USB 序列号查找当前端口，找不到返回 `NotFound`
+/// （由读线程按重连策略重试，设备插回后自动恢复）；否则直接使用 `port`。
+pub fn resolve_port(cfg: &SerialDeviceConfig) -> io::Result<String> {
+    let Some(serial) = cfg.usb_serial.as_deref() else {
+        return Ok(cfg.port.clone());
+    };
+    let ports = list_ports().map_err(io::Error::other)?;
+    ports
+        .into_iter()
+        .find(|p| p.serial_number.as_deref() == Some(serial))
+        .map(|p| p.port_name)
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("未找到 USB 序列号为 {} 的串口", serial),
+            )
+        })
+}
+
+/// `serial:ports-changed` 事件 payload
+#[derive(Debug, Clone, Serialize)]
+pub struct PortsChangedPayload {
+    /// 变化后的完整端口列表
+    pub ports: Vec<PortInfo>,
+    /// 新出现的端口名
+    pub added: Vec<String>,
+    /// 消失的端口名
+    pub removed: Vec<String>,
+}
+
+/// 启动后台线程轮询端口列表，端口出现或消失时推送 `serial:ports-changed`
+pub fn spawn_port_watcher(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut last = list_ports().unwrap_or_default();
+        loop {
+            std::thread::sleep(WATCH_INTERVAL);
+            let ports = match list_ports() {
+                Ok(p) => p,
+                Err(e) => {
+                    log::debug!("热插拔检测: {}", e);
+                    continue;
+                }
+            };
+            if ports == last {
+                continue;
+            }
+            let before: HashSet<&str> = last.iter().map(|p| p.port_name.as_str()).collect();
+            let after: HashSet<&str> = ports.iter().map(|p| p.port_name.as_str()).collect();
+            let added: Vec<String> = ports
+                .iter()
+                .filter(|p| !before.contains(p.port_name.as_str()))
+                .map(|p| p.port_name.clone())
+                .collect();
+            let removed: Vec<String> = last
+                .iter()
+                .filter(|p| !after.contains(p.port_name.as_str()))
+                .map(|p| p.port_name.clone())
+                .collect();
+            log::info!("串口列表变化: 新增 {:?}，移除 {:?}", added, removed);
+            let _ = app.emit(
+                "serial:ports-changed",
+                PortsChangedPayload {
+                    ports: ports.clone(),
+                    added,
+                    removed,
+                },
+            );
+            last = ports;
+        }
+    });
+}
@@ -0,0 +1,400 @@
+//! 模拟串口设备，用于无硬件的开发调试与 CI
+//!
+//! 在 `SerialDeviceConfig.port` 中以 `sim://<kind>?<参数>` 选择，例如：
+//!
+//! - `sim://scanner?interval_ms=2000&prefix=SN`：定时发出条码（以 `\r\n` 结尾）
+//! - `sim://scanner?codes=A001,A002,NG01`：按顺序循环发出指定条码
+//! - `sim://plc?unit=1`：Modbus RTU 从站，支持功能码 01/02/03/04/05/06/15/16
+//! - `sim://light`、`sim://echo`：把收到的指令原样回显
+//!
+//! 通用故障注入参数：
+//!
+//! - `disconnect_after_ms=N`：打开 N 毫秒后读操作返回断线错误，触发重连
+//! - `corrupt_every=N`：每发出 N 帧，把其中一帧的最后一个字节取反（校验失败）
+
+use std::collections::{HashMap, VecDeque};
+use std::io::{self, Read, Write};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use super::config::SerialDeviceConfig;
+use super::modbus;
+use super::transport::Transport;
+
+const SCHEME: &str = "sim://";
+
+/// PLC 模拟器的线圈 / 寄存器数量
+const PLC_TABLE_SIZE: usize = 1000;
+
+/// 端口名是否指向模拟设备
+pub fn is_sim_port(port: &str) -> bool {
+    port.starts_with(SCHEME)
+}
+
+/// 解析后的模拟设备描述
+#[derive(Debug, Clone)]
+pub struct SimSpec {
+    pub kind: String,
+    pub params: HashMap<String, String>,
+}
+
+impl SimSpec {
+    /// 解析 `sim://kind?k=v&k2=v2`
+    pub fn parse(port: &str) -> Result<Self, String> {
+        let rest = port
+            .strip_prefix(SCHEME)
+            .ok_or_else(|| format!("{} 不是模拟设备地址", port))?;
+        let (kind, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let params = query
+            .split('&')
+            .filter(|kv| !kv.is_empty())
+            .map(|kv| {
+                let (k, v) = kv.split_once('=').unwrap_or((kv, ""));
+                (k.to_string(), v.to_string())
+            })
+            .collect();
+        let spec = SimSpec {
+            kind: kind.to_string(),
+            params,
+        };
+        spec.validate()?;
+        Ok(spec)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if !["scanner", "plc", "light", "echo"].contains(&self.kind.as_str()) {
+            return Err(format!("未知的模拟设备类型 {}", self.kind));
+        }
+        for key in ["interval_ms", "disconnect_after_ms", "corrupt_every", "unit"] {
+            self.num(key, 0)?;
+        }
+        if self.kind == "scanner" && self.num("interval_ms", 1)? == 0 {
+            return Err("interval_ms 不能为 0".into());
+        }
+        Ok(())
+    }
+
+    fn num(&self, key: &str, default: u64) -> Result<u64, String> {
+        match self.params.get(key) {
+            Some(v) => v
+                .parse()
+                .map_err(|_| format!("模拟设备参数 {}={} 不是整数", key, v)),
+            None => Ok(default),
+        }
+    }
+
+    fn str(&self, key: &str) -> Option<&str> {
+        self.params.get(key).map(String::as_str)
+    }
+}
+
+// ─── 模拟设备行为 ────────────────────────────────────────────────────────────
+
+enum Behavior {
+    Scanner {
+        interval: Duration,
+        prefix: String,
+        codes: Vec<String>,
+        counter: u64,
+        next_at: Instant,
+    },
+    Plc {
+        unit: u8,
+        coils: Vec<bool>,
+        registers: Vec<u16>,
+        request: Vec<u8>,
+    },
+    Echo,
+}
+
+struct SimState {
+    behavior: Behavior,
+    /// 待主机读取的字节
+    rx: VecDeque<u8>,
+    disconnect_at: Option<Instant>,
+    corrupt_every: u64,
+    frames_out: u64,
+}
+
+impl SimState {
+    /// 把一帧放入待读队列，按 `corrupt_every` 注入校验错误
+    fn emit(&mut self, mut frame: Vec<u8>) {
+        self.frames_out += 1;
+        if self.corrupt_every > 0 && self.frames_out.is_multiple_of(self.corrupt_every) {
+            if let Some(last) = frame.last_mut() {
+                *last = !*last;
+            }
+        }
+        self.rx.extend(frame);
+    }
+
+    /// 产生到期的定时输出，返回下一次定时输出的时间
+    fn tick(&mut self, now: Instant) -> Option<Instant> {
+        let Behavior::Scanner {
+            interval,
+            prefix,
+            codes,
+            counter,
+            next_at,
+        } = &mut self.behavior
+        else {
+            return None;
+        };
+        if now < *next_at {
+            return Some(*next_at);
+        }
+        let code = if codes.is_empty() {
+            format!("{}{:06}", prefix, *counter + 1)
+        } else {
+            codes[*counter as usize % codes.len()].clone()
+        };
+        *counter += 1;
+        *next_at = now + *interval;
+        let next = *next_at;
+        log::debug!("[模拟扫码枪] 输出条码 {}", code);
+        self.emit(format!("{}\r\n", code).into_bytes());
+        Some(next)
+    }
+
+    fn on_write(&mut self, data: &[u8]) {
+        match &mut self.behavior {
+            Behavior::Scanner { .. } => {}
+            Behavior::Echo => self.emit(data.to_vec()),
+            Behavior::Plc { request, .. } => {
+                request.extend_from_slice(data);
+                let mut frames = Vec::new();
+                while let Some(len) = modbus_request_len(request) {
+                    if request.len() < len {
+                        break;
+                    }
+                    frames.push(request.drain(..len).collect::<Vec<u8>>());
+                }
+                for frame in frames {
+                    if let Some(resp) = self.plc_respond(&frame) {
+                        self.emit(resp);
+                    }
+                }
+            }
+        }
+    }
+
+    /// 作为 Modbus 从站处理一条请求；地址不符或 CRC 错误时不应答（与真实从站一致）
+    fn plc_respond(&mut self, frame: &[u8]) -> Option<Vec<u8>> {
+        let Behavior::Plc {
+            unit,
+            coils,
+            registers,
+            ..
+        } = &mut self.behavior
+        else {
+            return None;
+        };
+        let (body, crc) = frame.split_at(frame.len() - 2);
+        if crc != super::checksum::crc16_modbus(body).to_le_bytes() || body[0] != *unit {
+            return None;
+        }
+        let unit = *unit;
+        let fc = body[1];
+        let word = |i: usize| u16::from_be_bytes([body[i], body[i + 1]]) as usize;
+        let exception = |code: u8| Some(modbus::encode_frame(unit, &[fc | 0x80, code]));
+
+        let pdu = match fc {
+            modbus::READ_COILS | modbus::READ_DISCRETE_INPUTS => {
+                let (addr, count) = (word(2), word(4));
+                if count == 0 || addr + count > coils.len() {
+                    return exception(0x02);
+                }
+                let mut bytes = vec![0u8; count.div_ceil(8)];
+                for i in 0..count {
+                    if coils[addr + i] {
+                        bytes[i / 8] |= 1 << (i % 8);
+                    }
+                }
+                [&[fc, bytes.len() as u8][..], &bytes].concat()
+            }
+            modbus::READ_HOLDING_REGISTERS | modbus::READ_INPUT_REGISTERS => {
+                let (addr, count) = (word(2), word(4));
+                if count == 0 || count > 125 || addr + count > registers.len() {
+                    return exception(0x02);
+                }
+                let mut pdu = vec![fc, (count * 2) as u8];
+                pdu.extend(registers[addr..addr + count].iter().flat_map(|v| v.to_be_bytes()));
+                pdu
+            }
+            modbus::WRITE_SINGLE_COIL => {
+                let addr = word(2);
+                let value = match word(4) {
+                    0xFF00 => true,
+                    0x0000 => false,
+                    _ => return exception(0x03),
+                };
+                if addr >= coils.len() {
+                    return exception(0x02);
+                }
+                coils[addr] = value;
+                body[1..6].to_vec()
+            }
+            modbus::WRITE_SINGLE_REGISTER => {
+                let addr = word(2);
+                if addr >= registers.len() {
+                    return exception(0x02);
+                }
+                registers[addr] = word(4) as u16;
+                body[1..6].to_vec()
+            }
+            modbus::WRITE_MULTIPLE_COILS => {
+                let (addr, count) = (word(2), word(4));
+                if count.div_ceil(8) != body[6] as usize {
+                    return exception(0x03);
+                }
+                if addr + count > coils.len() {
+                    return exception(0x02);
+                }
+                for i in 0..count {
+                    coils[addr + i] = body[7 + i / 8] & (1 << (i % 8)) != 0;
+                }
+                body[1..6].to_vec()
+            }
+            modbus::WRITE_MULTIPLE_REGISTERS => {
+                let (addr, count) = (word(2), word(4));
+                if count * 2 != body[6] as usize {
+                    return exception(0x03);
+                }
+                if addr + count > registers.len() {
+                    return exception(0x02);
+                }
+                for i in 0..count {
+                    registers[addr + i] = word(7 + i * 2) as u16;
+                }
+                body[1..6].to_vec()
+            }
+            _ => return exception(0x01),
+        };
+        Some(modbus::encode_frame(unit, &pdu))
+    }
+}
+
+/// 主站请求帧长度；功能码未知时按 8 字节处理（从站会回异常 01）
+fn modbus_request_len(buf: &[u8]) -> Option<usize> {
+    match *buf.get(1)? {
+        modbus::WRITE_MULTIPLE_COILS | modbus::WRITE_MULTIPLE_REGISTERS => {
+            buf.get(6).map(|&n| 9 + n as usize)
+        }
+        _ => Some(8),
+    }
+}
+
+// ─── 传输实现 ────────────────────────────────────────────────────────────────
+
+/// 模拟设备传输；克隆出的写端与读端共享同一份设备状态
+pub struct SimTransport {
+    shared: Arc<(Mutex<SimState>, Condvar)>,
+    timeout: Duration,
+}
+
+impl SimTransport {
+    pub fn open(cfg: &SerialDeviceConfig) -> io::Result<Self> {
+        let spec = SimSpec::parse(&cfg.port)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let num = |key: &str, default: u64| spec.num(key, default).unwrap_or(default);
+        let now = Instant::now();
+
+        let behavior = match spec.kind.as_str() {
+            "scanner" => Behavior::Scanner {
+                interval: Duration::from_millis(num("interval_ms", 3000)),
+                prefix: spec.str("prefix").unwrap_or("SIM").to_string(),
+                codes: spec
+                    .str("codes")
+                    .map(|s| s.split(',').map(str::to_string).collect())
+                    .unwrap_or_default(),
+                counter: 0,
+                next_at: now + Duration::from_millis(num("interval_ms", 3000)),
+            },
+            "plc" => Behavior::Plc {
+                unit: num("unit", 1) as u8,
+                coils: vec![false; PLC_TABLE_SIZE],
+                registers: vec![0; PLC_TABLE_SIZE],
+                request: Vec::new(),
+            },
+            _ => Behavior::Echo,
+        };
+        let disconnect_at = match num("disconnect_after_ms", 0) {
+            0 => None,
+            ms => Some(now + Duration::from_millis(ms)),
+        };
+
+        log::info!("[模拟设备 {}] 已打开 {}", cfg.name, cfg.port);
+        let state = SimState {
+            behavior,
+            rx: VecDeque::new(),
+            disconnect_at,
+            corrupt_every: num("corrupt_every", 0),
+            frames_out: 0,
+        };
+        Ok(Self {
+            shared: Arc::new((Mutex::new(state), Condvar::new())),
+            timeout: cfg.framing.read_timeout(),
+        })
+    }
+}
+
+impl Read for SimTransport {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (lock, cvar) = &*self.shared;
+        let deadline = Instant::now() + self.timeout;
+        let mut state = lock.lock().unwrap();
+        loop {
+            let now = Instant::now();
+            if state.disconnect_at.is_some_and(|t| now >= t) {
+                return Err(io::Error::new(io::ErrorKind::BrokenPipe, "模拟设备断开"));
+            }
+            let next_tick = state.tick(now);
+            if !state.rx.is_empty() {
+                let n = buf.len().min(state.rx.len());
+                for (dst, src) in buf.iter_mut().zip(state.rx.drain(..n)) {
+                    *dst = src;
+                }
+                return Ok(n);
+            }
+            if now >= deadline {
+                return Err(io::Error::new(io::ErrorKind::TimedOut, "模拟设备读超时"));
+            }
+            let wake = [Some(deadline), next_tick, state.disconnect_at]
+                .into_iter()
+                .flatten()
+                .min()
+                .unwrap_or(deadline);
+            state = cvar.wait_timeout(state, wake.saturating_duration_since(now)).unwrap().0;
+        }
+    }
+}
+
+impl Write for SimTransport {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        let (lock, cvar) = &*self.shared;
+        let mut state = lock.lock().unwrap();
+        if state.disconnect_at.is_some_and(|t| Instant::now() >= t) {
+            return Err(io::Error::new(io::ErrorKind::BrokenPipe, "模拟设备断开"));
+        }
+        state.on_write(data);
+        cvar.notify_all();
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Transport for SimTransport {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(SimTransport {
+            shared: self.shared.clone(),
+            timeout: self.timeout,
+        }))
+    }
+
+    fn bytes_to_read(&self) -> io::Result<u32> {
+        Ok(self.shared.0.lock().unwrap().rx.len() as u32)
+    }
+}
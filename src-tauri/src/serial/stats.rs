@@ -0,0 +1,128 @@
+use std::sync::Mutex;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use super::manager::ConnectionState;
+
+/// 单个设备的运行统计快照（`get_serial_stats` 返回值与 `serial:stats` 事件元素）
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceStats {
+    pub device_id: String,
+    pub port: String,
+    pub state: ConnectionState,
+    pub bytes_rx: u64,
+    pub bytes_tx: u64,
+    /// 成功解出的帧数
+    pub frames_rx: u64,
+    /// 写入次数（每次 `send_to_device` / 事务请求计一帧）
+    pub frames_tx: u64,
+    /// 分帧错误（校验失败、超长、残帧等）次数
+    pub framing_errors: u64,
+    /// 打开端口失败次数
+    pub open_errors: u64,
+    /// 连接建立后读取出错（断线）次数
+    pub read_errors: u64,
+    /// 重连尝试次数
+    pub reconnects: u64,
+    /// 最近一次收到数据的时间（Unix 毫秒）
+    pub last_rx_ms: Option<u64>,
+    /// 监听线程启动时间（Unix 毫秒）
+    pub started_ms: u64,
+    /// 当前连接已持续的时间；未连接时为 0
+    pub uptime_ms: u64,
+}
+
+/// 读线程与写路径共享的统计计数器
+pub struct StatsCell {
+    inner: Mutex<Inner>,
+}
+
+struct Inner {
+    stats: DeviceStats,
+    connected_at: Option<Instant>,
+}
+
+impl StatsCell {
+    pub fn new(device_id: &str, port: &str, started_ms: u64) -> Self {
+        Self {
+            inner: Mutex::new(Inner {
+                stats: DeviceStats {
+                    device_id: device_id.to_string(),
+                    port: port.to_string(),
+                    state: ConnectionState::Disconnected,
+                    bytes_rx: 0,
+                    bytes_tx: 0,
+                    frames_rx: 0,
+                    frames_tx: 0,
+                    framing_errors: 0,
+                    open_errors: 0,
+                    read_errors: 0,
+                    reconnects: 0,
+                    last_rx_ms: None,
+                    started_ms,
+                    uptime_ms: 0,
+                },
+                connected_at: None,
+            }),
+        }
+    }
+
+    fn update(&self, f: impl FnOnce(&mut DeviceStats)) {
+        f(&mut self.inner.lock().unwrap().stats);
+    }
+
+    pub fn on_rx(&self, n: usize, now_ms: u64) {
+        self.update(|s| {
+            s.bytes_rx += n as u64;
+            s.last_rx_ms = Some(now_ms);
+        });
+    }
+
+    pub fn on_tx(&self, n: usize) {
+        self.update(|s| {
+            s.bytes_tx += n as u64;
+            s.frames_tx += 1;
+        });
+    }
+
+    pub fn on_frame(&self) {
+        self.update(|s| s.frames_rx += 1);
+    }
+
+    pub fn on_framing_error(&self) {
+        self.update(|s| s.framing_errors += 1);
+    }
+
+    pub fn on_open_error(&self) {
+        self.update(|s| s.open_errors += 1);
+    }
+
+    pub fn on_read_error(&self) {
+        self.update(|s| s.read_errors += 1);
+    }
+
+    /// 记录连接状态变化；进入 `Reconnecting` 计一次重连
+    pub fn on_state(&self, state: ConnectionState) {
+        let mut inner = self.inner.lock().unwrap();
+        match state {
+            ConnectionState::Connected => inner.connected_at = Some(Instant::now()),
+            ConnectionState::Reconnecting => {
+                inner.connected_at = None;
+                inner.stats.reconnects += 1;
+            }
+            ConnectionState::Disconnected => inner.connected_at = None,
+        }
+        inner.stats.state = state;
+    }
+
+    pub fn snapshot(&self) -> DeviceStats {
+        let inner = self.inner.lock().unwrap();
+        let mut stats = inner.stats.clone();
+        stats.uptime_ms = inner
+            .connected_at
+            .map(|t| t.elapsed().as_millis() as u64)
+            .unwrap_or(0);
+        stats
+    }
+}
@@ -0,0 +1,312 @@
+//! 设备命名指令模板
+//!
+//! 每个设备可在 `command_templates` 中保存若干带参数的常用指令，例如光源的
+//! `set_brightness(channel, level)`：
+//!
+//! ```json
+//! {
+//!   "name": "set_brightness",
+//!   "encoding": "hex",
+//!   "template": "AA 55 {channel} {level}",
+//!   "params": [
+//!     { "name": "channel", "min": 1, "max": 4, "format": "hex8" },
+//!     { "name": "level", "min": 0, "max": 100, "default": 80, "format": "hex8" }
+//!   ]
+//! }
+//! ```
+//!
+//! 执行时把 `{参数名}` 替换为按 `format` 格式化的参数值，再按 `encoding` 解析为字节，
+//! 最后追加设备配置的校验（见 `SerialDeviceConfig::encode_payload`）。
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::codec::Encoding;
+
+/// 参数值代入模板时的格式
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum ParamFormat {
+    /// 十进制文本，如 `80`（用于 `text` / `escaped` 模板）
+    #[default]
+    Dec,
+    /// 1 字节十六进制，如 `50`（用于 `hex` 模板）
+    Hex8,
+    /// 2 字节十六进制，高字节在前，如 `01 F4`
+    Hex16Be,
+    /// 2 字节十六进制，低字节在前，如 `F4 01`
+    Hex16Le,
+}
+
+impl ParamFormat {
+    /// 格式本身能表示的取值范围
+    fn range(self) -> (i64, i64) {
+        match self {
+            ParamFormat::Dec => (i64::MIN, i64::MAX),
+            ParamFormat::Hex8 => (0, 0xFF),
+            ParamFormat::Hex16Be | ParamFormat::Hex16Le => (0, 0xFFFF),
+        }
+    }
+
+    fn render(self, value: i64) -> String {
+        match self {
+            ParamFormat::Dec => value.to_string(),
+            ParamFormat::Hex8 => format!("{:02X}", value),
+            ParamFormat::Hex16Be => format!("{:02X} {:02X}", value >> 8, value & 0xFF),
+            ParamFormat::Hex16Le => format!("{:02X} {:02X}", value & 0xFF, value >> 8),
+        }
+    }
+}
+
+/// 模板参数声明
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TemplateParam {
+    pub name: String,
+    /// 允许的最小值（含）
+    #[serde(default)]
+    pub min: Option<i64>,
+    /// 允许的最大值（含）
+    #[serde(default)]
+    pub max: Option<i64>,
+    /// 调用时未提供该参数时使用的值；为 None 时参数必填
+    #[serde(default)]
+    pub default: Option<i64>,
+    #[serde(default)]
+    pub format: ParamFormat,
+}
+
+/// 命名指令模板
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct CommandTemplate {
+    /// 设备内唯一的指令名，如 "set_brightness"、"home"
+    pub name: String,
+    /// 界面上显示的说明
+    #[serde(default)]
+    pub description: String,
+    /// `template` 的编码（替换参数之后再解析）
+    #[serde(default)]
+    pub encoding: Encoding,
+    /// 指令内容，`{参数名}` 为占位符
+    pub template: String,
+    #[serde(default)]
+    pub params: Vec<TemplateParam>,
+}
+
+/// 模板文本片段
+enum Segment<'a> {
+    Literal(&'a str),
+    Param(&'a str),
+}
+
+/// 把模板拆成字面量与 `{参数名}` 占位符
+fn segments(template: &str) -> Result<Vec<Segment<'_>>, String> {
+    let mut out = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        if open > 0 {
+            out.push(Segment::Literal(&rest[..open]));
+        }
+        let close = rest[open..]
+            .find('}')
+            .map(|i| open + i)
+            .ok_or_else(|| format!("模板中的 {{ 未闭合: {}", template))?;
+        let name = rest[open + 1..close].trim();
+        if name.is_empty() {
+            return Err(format!("模板中存在空占位符: {}", template));
+        }
+        out.push(Segment::Param(name));
+        rest = &rest[close + 1..];
+    }
+    if !rest.is_empty() {
+        out.push(Segment::Literal(rest));
+    }
+    Ok(out)
+}
+
+impl TemplateParam {
+    /// 合法取值范围：声明的 min/max 与格式本身范围的交集
+    fn bounds(&self) -> (i64, i64) {
+        let (lo, hi) = self.format.range();
+        (self.min.unwrap_or(lo).max(lo), self.max.unwrap_or(hi).min(hi))
+    }
+
+    fn check(&self, value: i64) -> Result<(), String> {
+        let (lo, hi) = self.bounds();
+        if value < lo || value > hi {
+            return Err(format!("参数 {} 的值 {} 超出范围 [{}, {}]", self.name, value, lo, hi));
+        }
+        Ok(())
+    }
+}
+
+impl CommandTemplate {
+    /// 校验模板定义：参数名唯一、范围合法、占位符均已声明
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("指令名不能为空".into());
+        }
+        let mut names = HashSet::new();
+        for p in &self.params {
+            if p.name.trim().is_empty() {
+                return Err(format!("指令 {} 存在空参数名", self.name));
+            }
+            if !names.insert(p.name.as_str()) {
+                return Err(format!("指令 {} 的参数 {} 重复", self.name, p.name));
+            }
+            let (lo, hi) = p.bounds();
+            if lo > hi {
+                return Err(format!("指令 {} 的参数 {} 取值范围为空", self.name, p.name));
+            }
+            if let Some(d) = p.default {
+                p.check(d).map_err(|e| format!("指令 {} 的默认值无效: {}", self.name, e))?;
+            }
+        }
+        for seg in segments(&self.template)? {
+            if let Segment::Param(name) = seg {
+                if !names.contains(name) {
+                    return Err(format!("指令 {} 的模板引用了未声明的参数 {}", self.name, name));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 代入参数并按 `encoding` 解析为字节（不含校验）
+    ///
+    /// 未声明的参数、缺失且无默认值的参数、超出范围的值均返回错误。
+    pub fn render(&self, args: &HashMap<String, i64>) -> Result<Vec<u8>, String> {
+        if let Some(unknown) = args.keys().find(|k| !self.params.iter().any(|p| &p.name == *k)) {
+            return Err(format!("指令 {} 没有参数 {}", self.name, unknown));
+        }
+        let mut text = String::with_capacity(self.template.len());
+        for seg in segments(&self.template)? {
+            match seg {
+                Segment::Literal(s) => text.push_str(s),
+                Segment::Param(name) => {
+                    let param = self
+                        .params
+                        .iter()
+                        .find(|p| p.name == name)
+                        .ok_or_else(|| format!("指令 {} 的模板引用了未声明的参数 {}", self.name, name))?;
+                    let value = args
+                        .get(name)
+                        .copied()
+                        .or(param.default)
+                        .ok_or_else(|| format!("指令 {} 缺少参数 {}", self.name, name))?;
+                    param.check(value)?;
+                    text.push_str(&param.format.render(value));
+                }
+            }
+        }
+        self.encoding
+            .parse(&text)
+            .map_err(|e| format!("指令 {} 渲染结果无法解析: {}", self.name, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(json: serde_json::Value) -> CommandTemplate {
+        serde_json::from_value(json).unwrap()
+    }
+
+    fn set_brightness() -> CommandTemplate {
+        template(serde_json::json!({
+            "name": "set_brightness",
+            "encoding": "hex",
+            "template": "AA 55 {channel} {level}",
+            "params": [
+                { "name": "channel", "min": 1, "max": 4, "format": "hex8" },
+                { "name": "level", "min": 0, "max": 100, "default": 80, "format": "hex8" },
+            ],
+        }))
+    }
+
+    fn args(pairs: &[(&str, i64)]) -> HashMap<String, i64> {
+        pairs.iter().map(|(k, v)| (k.to_string(), *v)).collect()
+    }
+
+    #[test]
+    fn render_substitutes_args_and_defaults() {
+        let t = set_brightness();
+        t.validate().unwrap();
+        assert_eq!(t.render(&args(&[("channel", 2)])).unwrap(), [0xAA, 0x55, 0x02, 0x50]);
+        assert_eq!(
+            t.render(&args(&[("channel", 4), ("level", 100)])).unwrap(),
+            [0xAA, 0x55, 0x04, 0x64]
+        );
+    }
+
+    #[test]
+    fn render_formats_values() {
+        let t = template(serde_json::json!({
+            "name": "move",
+            "encoding": "hex",
+            "template": "{be} {le}",
+            "params": [
+                { "name": "be", "format": "hex16Be" },
+                { "name": "le", "format": "hex16Le" },
+            ],
+        }));
+        assert_eq!(t.render(&args(&[("be", 500), ("le", 500)])).unwrap(), [0x01, 0xF4, 0xF4, 0x01]);
+
+        let t = template(serde_json::json!({
+            "name": "home",
+            "encoding": "escaped",
+            "template": "HOME {speed}\\r\\n",
+            "params": [{ "name": "speed", "default": -10 }],
+        }));
+        assert_eq!(t.render(&args(&[])).unwrap(), b"HOME -10\r\n");
+    }
+
+    #[test]
+    fn render_rejects_bad_args() {
+        let t = set_brightness();
+        // 缺少必填参数、越界（声明范围与格式范围）、未声明的参数
+        assert!(t.render(&args(&[])).is_err());
+        assert!(t.render(&args(&[("channel", 5)])).is_err());
+        assert!(t.render(&args(&[("channel", 1), ("level", 101)])).is_err());
+        assert!(t.render(&args(&[("channel", 1), ("speed", 1)])).is_err());
+
+        let wide = template(serde_json::json!({
+            "name": "raw",
+            "encoding": "hex",
+            "template": "{v}",
+            "params": [{ "name": "v", "max": 1000, "format": "hex8" }],
+        }));
+        assert!(wide.render(&args(&[("v", 256)])).is_err());
+    }
+
+    #[test]
+    fn validate_rejects_inconsistent_definitions() {
+        let mut undeclared = set_brightness();
+        undeclared.template = "AA {chan}".into();
+        let mut unclosed = set_brightness();
+        unclosed.template = "AA {channel".into();
+        let mut empty_placeholder = set_brightness();
+        empty_placeholder.template = "AA {}".into();
+        let mut duplicate = set_brightness();
+        duplicate.params[1].name = "channel".into();
+        let mut empty_range = set_brightness();
+        empty_range.params[0].min = Some(5);
+        let mut bad_default = set_brightness();
+        bad_default.params[1].default = Some(200);
+        let mut unnamed = set_brightness();
+        unnamed.name = " ".into();
+
+        for t in [
+            undeclared,
+            unclosed,
+            empty_placeholder,
+            duplicate,
+            empty_range,
+            bad_default,
+            unnamed,
+        ] {
+            assert!(t.validate().is_err(), "{:?}", t);
+        }
+    }
+}
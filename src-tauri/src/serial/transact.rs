@@ -0,0 +1,204 @@
+use std::fmt;
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use regex::bytes::Regex;
+use serde::{Deserialize, Serialize};
+
+/// `Expect::Frames` 一次最多收集的帧数
+pub const MAX_EXPECT_FRAMES: usize = 1024;
+
+/// 单次事务的最长等待时间
+pub const MAX_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// 判断某一帧是否为当前事务所等待的应答
+pub type FrameMatcher = Box<dyn Fn(&[u8]) -> bool + Send>;
+
+/// `serial_transact` 的应答匹配条件（JSON 形如 `{ "type": "prefix", "prefix": [170, 85] }`）
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum Expect {
+    /// 写入后的下一帧
+    #[default]
+    Any,
+    /// 以指定字节开头的下一帧
+    Prefix { prefix: Vec<u8> },
+    /// 原始字节满足正则的下一帧（按字节匹配，非 UTF-8 数据同样适用）
+    Regex { pattern: String },
+    /// 写入后连续收到的 `count` 帧
+    Frames { count: usize },
+}
+
+impl Expect {
+    /// 转换为匹配器与需要收集的帧数
+    pub fn into_matcher(self) -> Result<(FrameMatcher, usize), TransactError> {
+        let invalid = |message: String| TransactError::InvalidRequest { message };
+        Ok(match self {
+            Expect::Any => (Box::new(|_: &[u8]| true), 1),
+            Expect::Prefix { prefix } => (Box::new(move |f: &[u8]| f.starts_with(&prefix)), 1),
+            Expect::Regex { pattern } => {
+                let re = Regex::new(&pattern)
+                    .map_err(|e| invalid(format!("正则表达式无效: {}", e)))?;
+                (Box::new(move |f: &[u8]| re.is_match(f)), 1)
+            }
+            Expect::Frames { count } => {
+                if count == 0 || count > MAX_EXPECT_FRAMES {
+                    return Err(invalid(format!("count 必须在 1..={} 之间", MAX_EXPECT_FRAMES)));
+                }
+                (Box::new(|_: &[u8]| true), count)
+            }
+        })
+    }
+}
+
+/// 请求/应答事务失败原因（序列化为 `{ kind: "timeout", ... }` 供前端区分）
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum TransactError {
+    /// 匹配条件等参数不合法，请求未发送
+    InvalidRequest { message: String },
+    /// 设备未启动，或正在重连、端口暂不可写
+    NotConnected { message: String },
+    /// 写入端口失败
+    Write { message: String },
+    /// 超时仍未收到匹配的应答帧
+    Timeout { timeout_ms: u64 },
+}
+
+impl fmt::Display for TransactError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransactError::InvalidRequest { message }
+            | TransactError::NotConnected { message }
+            | TransactError::Write { message } => f.write_str(message),
+            TransactError::Timeout { timeout_ms } => {
+                write!(f, "等待应答超时（{}ms）", timeout_ms)
+            }
+        }
+    }
+}
+
+struct Waiter {
+    matcher: FrameMatcher,
+    /// 还需收集的匹配帧数
+    remaining: usize,
+    tx: mpsc::Sender<Vec<u8>>,
+}
+
+/// 单个设备的事务槽：串行化并发事务，并把读线程解出的匹配帧交给等待者
+///
+/// 同一时刻只有一个等待者；读线程对每一帧调用 `offer`，
+/// 调用方通过 `run` 完成"登记等待 → 写入 → 等待应答"。
+#[derive(Default)]
+pub struct TransactionSlot {
+    /// 持有期间其它事务排队等待
+    serial: Mutex<()>,
+    waiter: Mutex<Option<Waiter>>,
+}
+
+impl TransactionSlot {
+    /// 读线程调用：若帧与当前等待者匹配则投递，返回是否已被事务消费
+    pub fn offer(&self, frame: &[u8]) -> bool {
+        let mut waiter = self.waiter.lock().unwrap();
+        match waiter.as_mut() {
+            Some(w) if (w.matcher)(frame) => {
+                let _ = w.tx.send(frame.to_vec());
+                w.remaining -= 1;
+                if w.remaining == 0 {
+                    waiter.take();
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// 执行一次事务：先登记等待者再写入，避免应答早于登记到达而丢失
+    ///
+    /// 收齐 `count` 条匹配帧后返回；`timeout` 为整个事务的总时限。
+    pub fn run(
+        &self,
+        matcher: FrameMatcher,
+        count: usize,
+        timeout: Duration,
+        write: impl FnOnce() -> Result<(), TransactError>,
+    ) -> Result<Vec<Vec<u8>>, TransactError> {
+        // 在加锁与登记之前拒绝，避免计算截止时间溢出时持锁 panic 使锁中毒
+        if timeout > MAX_TIMEOUT {
+            return Err(TransactError::InvalidRequest {
+                message: format!("timeout 不能超过 {}ms", MAX_TIMEOUT.as_millis()),
+            });
+        }
+        let _serial = self.serial.lock().unwrap();
+        let (tx, rx) = mpsc::channel();
+        *self.waiter.lock().unwrap() = Some(Waiter {
+            matcher,
+            remaining: count,
+            tx,
+        });
+
+        let result = write().and_then(|_| {
+            let deadline = Instant::now() + timeout;
+            let mut frames = Vec::new();
+            while frames.len() < count {
+                let left = deadline.saturating_duration_since(Instant::now());
+                match rx.recv_timeout(left) {
+                    Ok(frame) => frames.push(frame),
+                    Err(_) => {
+                        return Err(TransactError::Timeout {
+                            timeout_ms: timeout.as_millis() as u64,
+                        })
+                    }
+                }
+            }
+            Ok(frames)
+        });
+        // 超时或写入失败时清理残留的等待者
+        self.waiter.lock().unwrap().take();
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn huge_timeout_is_rejected_without_poisoning() {
+        let slot = TransactionSlot::default();
+        let err = slot
+            .run(Box::new(|_: &[u8]| true), 1, Duration::from_millis(u64::MAX), || Ok(()))
+            .unwrap_err();
+        assert!(matches!(err, TransactError::InvalidRequest { .. }));
+
+        // 锁未中毒，后续事务照常执行
+        let err = slot
+            .run(Box::new(|_: &[u8]| true), 1, Duration::from_millis(10), || Ok(()))
+            .unwrap_err();
+        assert!(matches!(err, TransactError::Timeout { timeout_ms: 10 }));
+    }
+
+    #[test]
+    fn frame_count_is_bounded() {
+        assert!(Expect::Frames { count: 0 }.into_matcher().is_err());
+        assert!(Expect::Frames { count: usize::MAX }.into_matcher().is_err());
+        let (_, count) = Expect::Frames { count: 3 }.into_matcher().unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn offered_frames_complete_the_transaction() {
+        let slot = std::sync::Arc::new(TransactionSlot::default());
+        let reader = slot.clone();
+        let (matcher, count) = Expect::Prefix { prefix: vec![0xAA] }.into_matcher().unwrap();
+        let frames = slot
+            .run(matcher, count, Duration::from_secs(1), move || {
+                assert!(!reader.offer(b"\x01ignored"));
+                assert!(reader.offer(b"\xAAok"));
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(frames, vec![b"\xAAok".to_vec()]);
+    }
+}
@@ -0,0 +1,68 @@
+use std::io::{self, Read, Write};
+
+use serialport::SerialPort;
+
+use super::config::SerialDeviceConfig;
+use super::net;
+use super::ports;
+use super::sim;
+
+/// 设备字节流传输：真实串口与模拟设备共用同一读写接口
+///
+/// 读线程持有一个实例做阻塞读（超时返回 `TimedOut`），
+/// 写端通过 `try_clone_transport` 克隆得到，与读端共享底层连接。
+pub trait Transport: Read + Write + Send {
+    /// 克隆出共享底层连接的独立句柄（用作写端）
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>>;
+
+    /// 底层缓冲区中待读取的字节数（仅用于诊断日志）
+    fn bytes_to_read(&self) -> io::Result<u32> {
+        Ok(0)
+    }
+}
+
+impl Transport for Box<dyn SerialPort> {
+    fn try_clone_transport(&self) -> io::Result<Box<dyn Transport>> {
+        Ok(Box::new(self.try_clone()?))
+    }
+
+    fn bytes_to_read(&self) -> io::Result<u32> {
+        Ok(SerialPort::bytes_to_read(self.as_ref())?)
+    }
+}
+
+/// 按 `port` 的形式打开传输：`sim://` 为模拟设备，`tcp://` / `rfc2217://` 为网络串口服务器，
+/// 其余视为本机串口名（配置了 `usb_serial` 时按序列号解析端口名）
+pub fn open(cfg: &SerialDeviceConfig) -> io::Result<Box<dyn Transport>> {
+    if sim::is_sim_port(&cfg.port) {
+        return Ok(Box::new(sim::SimTransport::open(cfg)?));
+    }
+    if net::is_net_port(&cfg.port) {
+        return Ok(Box::new(net::NetTransport::open(cfg)?));
+    }
+    Ok(Box::new(open_serial(cfg)?))
+}
+
+/// 打开串口，显式禁用硬件流控（USB CDC 虚拟串口不需要 RTS/CTS）
+fn open_serial(cfg: &SerialDeviceConfig) -> io::Result<Box<dyn SerialPort>> {
+    let port = ports::resolve_port(cfg)?;
+    if port != cfg.port {
+        log::info!("[串口 {}|{}] 按 USB 序列号解析为 {}", cfg.name, cfg.port, port);
+    }
+    let mut p = serialport::new(&port, cfg.baud_rate)
+        .data_bits(cfg.data_bits_value())
+        .stop_bits(cfg.stop_bits_value())
+        .parity(cfg.parity_value())
+        .flow_control(serialport::FlowControl::None)
+        .timeout(cfg.framing.read_timeout())
+        .open()?;
+
+    // USB CDC 扫码枪通常需要 DTR=true 才会开始发送数据
+    // DTR 信号通知设备"主机已就绪"，没有它扫码枪会静默
+    if let Err(e) = p.write_data_terminal_ready(true) {
+        log::warn!("[串口 {}|{}] 设置 DTR 失败（非致命）: {}", cfg.name, cfg.port, e);
+    } else {
+        log::info!("[串口 {}|{}] DTR 已置高", cfg.name, cfg.port);
+    }
+    Ok(p)
+}
@@ -1,6 +1,11 @@
+import { invoke } from '@tauri-apps/api/core';
+
 /**
  * Production line configuration for the host (上位机).
  * 包含产线名称和工位名称。
+ *
+ * 持久化在后端 `app_config.json` 的 productionLine 分区（字段为 snake_case），
+ * 这里保留 camelCase 访问器并负责转换。
  */
 export interface ProductionLineConfig {
   productionLineName: string;
@@ -29,8 +34,46 @@ export function resetConfig(): void {
   runtimeConfig = { ...defaultConfig };
 }
 
+/** 后端 productionLine 分区的结构 */
+export interface ProductionLineSection {
+  production_line_name: string;
+  workstation_name: string;
+}
+
+export function fromSection(section: ProductionLineSection): ProductionLineConfig {
+  return {
+    productionLineName: section.production_line_name,
+    workstationName: section.workstation_name,
+  };
+}
+
+/** 从后端加载并更新运行时配置 */
+export async function loadConfig(): Promise<ProductionLineConfig> {
+  const section = await invoke<ProductionLineSection>('get_config_section', {
+    section: 'productionLine',
+  });
+  runtimeConfig = fromSection(section);
+  return getConfig();
+}
+
+/** 合并更新并保存到后端；保存成功后后端推送 config:changed */
+export async function saveConfig(update: Partial<ProductionLineConfig>): Promise<ProductionLineConfig> {
+  const next = { ...runtimeConfig, ...update };
+  const saved = await invoke<ProductionLineSection>('update_config_section', {
+    section: 'productionLine',
+    value: {
+      production_line_name: next.productionLineName,
+      workstation_name: next.workstationName,
+    },
+  });
+  runtimeConfig = fromSection(saved);
+  return getConfig();
+}
+
 export default {
   getConfig,
   setConfig,
   resetConfig,
+  loadConfig,
+  saveConfig,
 };
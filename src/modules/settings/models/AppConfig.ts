@@ -0,0 +1,110 @@
+/** 配置分区（与 Rust ConfigSection enum 对应，serde camelCase） */
+export type ConfigSection =
+  | 'general'
+  | 'productionLine'
+  | 'logging'
+  | 'serial'
+  | 'detection'
+  | 'storage'
+  | 'mes'
+  | 'database'
+
+export type Theme = 'light' | 'dark' | 'auto'
+
+/** 通用设置（对应 Rust GeneralConfig） */
+export interface GeneralConfig {
+  language: string
+  theme: Theme
+}
+
+/** 产线与工位（对应 Rust ProductionLineConfig） */
+export interface ProductionLineSection {
+  production_line_name: string
+  workstation_name: string
+}
+
+/** 外观检测设置（对应 Rust DetectionConfig） */
+export interface DetectionConfig {
+  backend: string
+  model_path: string | null
+  /** 0.0 ~ 1.0 */
+  confidence_threshold: number
+}
+
+/** 存储设置（对应 Rust StorageConfig） */
+export interface StorageConfig {
+  path: string
+  /** 0 表示不自动清理 */
+  retention_days: number
+}
+
+/** MES 集成设置（对应 Rust MesConfig） */
+export interface MesConfig {
+  enabled: boolean
+  url: string
+  token: string
+  timeout_ms: number
+}
+
+/** 数据库连接设置（对应 Rust DatabaseConfig） */
+export interface DatabaseConfig {
+  host: string
+  port: number
+  user: string
+  password: string
+  database: string
+}
+
+/** 配置文件（对应 Rust ConfigFile） */
+export type ConfigFile = 'app' | 'logging' | 'serial'
+
+/** `config:changed` 事件 payload */
+export interface ConfigChangedPayload<T = unknown> {
+  section: ConfigSection
+  value: T
+}
+
+/** `config:reloaded` / `config:reload-error` 事件 payload（配置文件被外部修改） */
+export interface ConfigReloadPayload {
+  file: ConfigFile
+  path: string
+  message: string
+}
+
+// ─── 配置包（对应 Rust config::bundle）────────────────────────────────────────
+
+export interface BundleEntry {
+  kind: ConfigFile
+  file: string
+  version: number | null
+}
+
+export interface BundleManifest {
+  format_version: number
+  app_version: string
+  created_at_ms: number
+  source: string
+  files: BundleEntry[]
+}
+
+/** merge：覆盖同名字段、按 device_id 合并设备；replace：整体替换包内包含的配置 */
+export type ImportMode = 'merge' | 'replace'
+
+export type ChangeKind = 'added' | 'removed' | 'modified'
+
+export interface ConfigChange {
+  /** 如 `mes.url`、`serial.devices[scanner_1].baud_rate` */
+  path: string
+  kind: ChangeKind
+  old: unknown
+  new: unknown
+}
+
+export interface ImportReport {
+  manifest: BundleManifest
+  mode: ImportMode
+  dry_run: boolean
+  changes: ConfigChange[]
+  warnings: string[]
+  skipped: string[]
+}
@@ -0,0 +1,35 @@
+// 诊断包（对应 Rust diagnostics 模块）
+
+export interface AppInfo {
+  name: string;
+  version: string;
+  tauri_version: string;
+  /** 编译时启用的功能，如 logging、serial */
+  features: string[];
+}
+
+export interface SystemInfo {
+  os: string;
+  family: string;
+  arch: string;
+  os_version: string | null;
+}
+
+export interface DetectionInfo {
+  backend: string;
+  ready: boolean;
+}
+
+/** export_diagnostics 返回值，与包内 diagnostics.json 相同 */
+export interface DiagnosticsReport {
+  format_version: number;
+  created_at_ms: number;
+  app: AppInfo;
+  system: SystemInfo;
+  /** 未启用检测功能时为 null */
+  detection: DetectionInfo | null;
+  /** 包内文件，如 logs/easydesktopapp.log、config/serial_config.json */
+  files: string[];
+  /** 收集过程中跳过的内容及原因 */
+  warnings: string[];
+}
@@ -4,4 +4,70 @@ export interface LogConfig {
   keep_files: number;
   level: string;
   to_stdout: boolean;
-}
\ No newline at end of file
+  /** 按模块（log target 前缀）设置的级别，如 { "easydesktopapp_lib::serial": "Trace", "tao": "Warn" } */
+  module_levels: Record<string, string>;
+  /** 行格式：text 为纯文本，json 为每行一个 JSON 对象 */
+  format: LogFormat;
+  /** 归档保留天数，null 表示不按时间清理 */
+  max_age_days: number | null;
+  /** 日志目录总大小上限（字节），null 表示不限制 */
+  max_total_size: number | null;
+  /** 是否把轮转归档压缩为 .log.gz */
+  compress_archives: boolean;
+}
+
+export type LogFormat = 'text' | 'json';
+
+/** update_log_config 返回值（对应 Rust LogApplyResult） */
+export interface LogApplyResult {
+  /** 当前生效的日志级别 */
+  level: string;
+  /** 是否仍有字段需要重启后才生效 */
+  restart_required: boolean;
+  /** 需要重启才生效的字段，如 max_file_size、keep_files */
+  restart_fields: string[];
+}
+
+// ─── 日志查看（对应 Rust logging::viewer）────────────────────────────────────
+
+export interface LogFileInfo {
+  name: string;
+  size: number;
+  modified_ms: number;
+  /** 是否为正在写入的当前文件 */
+  current: boolean;
+}
+
+export interface LogEntry {
+  file: string;
+  /** 起始行号；实时推送的条目为 0 */
+  line: number;
+  /** `YYYY-MM-DD HH:MM:SS`（本地时间） */
+  timestamp: string;
+  level: string;
+  target: string;
+  message: string;
+}
+
+export interface LogPage {
+  /** 按时间顺序 */
+  entries: LogEntry[];
+  total: number;
+}
+
+export interface LogQuery {
+  text?: string;
+  levels?: string[];
+  /** `YYYY-MM-DD[ HH:MM:SS]` */
+  from?: string;
+  to?: string;
+  files?: string[];
+  limit?: number;
+}
+
+export interface LogSearchResult {
+  /** 从新到旧 */
+  entries: LogEntry[];
+  truncated: boolean;
+  files_searched: number;
+}
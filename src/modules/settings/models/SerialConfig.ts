@@ -3,7 +3,69 @@ export type DeviceRole = 'scanner' | 'plc' | 'light' | 'robot' | 'camera'
 
 export type Parity = 'None' | 'Odd' | 'Even'
 
-export type DeviceStatus = 'idle' | 'connected' | 'error'
+export type DeviceStatus = 'idle' | 'connected' | 'reconnecting' | 'error'
+
+/** 连接状态（与 Rust ConnectionState enum 对应，serde camelCase） */
+export type ConnectionState = 'connected' | 'reconnecting' | 'disconnected'
+
+/** 断线重连策略（对应 Rust ReconnectConfig） */
+export interface ReconnectConfig {
+  enabled: boolean
+  initial_delay_ms: number
+  max_delay_ms: number
+  multiplier: number
+  /** 0 表示无限重试 */
+  max_attempts: number
+}
+
+/** 分帧方式（对应 Rust FramingConfig，serde tag = "type"） */
+export type FramingConfig =
+  | { type: 'delimiter'; delimiters: number[]; flush_len: number }
+  | { type: 'fixedLength'; length: number }
+  | {
+      type: 'lengthPrefixed'
+      length_offset: number
+      length_size: 1 | 2
+      big_endian: boolean
+      trailer_len: number
+    }
+  | { type: 'stxEtx'; stx: number; etx: number; bcc: boolean }
+  | { type: 'idleGap'; gap_ms: number }
+  | { type: 'modbusRtu' }
+
+/** 负载编码（对应 Rust codec::Encoding）：发送解析与 data_str 渲染共用 */
+export type Encoding = 'text' | 'hex' | 'escaped'
+
+/** 自动追加的校验类型（对应 Rust ChecksumKind） */
+export type ChecksumKind = 'none' | 'sum8' | 'xor' | 'crc16Modbus' | 'crc16Ccitt'
+
+/** 设备校验配置：start 为参与计算的起始偏移（跳过帧头） */
+export interface ChecksumConfig {
+  kind: ChecksumKind
+  start: number
+}
+
+/** 模板参数代入格式（对应 Rust ParamFormat） */
+export type ParamFormat = 'dec' | 'hex8' | 'hex16Be' | 'hex16Le'
+
+/** 模板参数声明 */
+export interface TemplateParam {
+  name: string
+  min?: number | null
+  max?: number | null
+  /** 未提供时使用的值；为空时参数必填 */
+  default?: number | null
+  format?: ParamFormat
+}
+
+/** 命名指令模板（对应 Rust CommandTemplate），`{参数名}` 为占位符 */
+export interface CommandTemplate {
+  name: string
+  description?: string
+  encoding?: Encoding
+  template: string
+  params?: TemplateParam[]
+}
 
 /** 单个串口设备配置（对应 Rust SerialDeviceConfig） */
 export interface SerialDeviceConfig {
@@ -11,18 +73,61 @@ export interface SerialDeviceConfig {
   name: string
   role: DeviceRole
   port: string
+  /** 绑定的 USB 序列号；设置后按序列号查找实际端口 */
+  usb_serial: string | null
   baud_rate: number
   data_bits: number
   stop_bits: number
   parity: Parity
   enabled: boolean
+  reconnect: ReconnectConfig
+  framing: FramingConfig
+  /** 启动监听时自动抓包 */
+  record: boolean
+  /** send_serial_payload 自动追加的校验 */
+  checksum: ChecksumConfig
+  /** serial:data 中 data_str 的渲染方式 */
+  display: Encoding
+  /** 命名指令模板，由 execute_device_command 调用 */
+  command_templates: CommandTemplate[]
+}
+
+/** 端口类型（对应 Rust PortKind，serde camelCase） */
+export type PortKind = 'usb' | 'pci' | 'bluetooth' | 'unknown'
+
+/** 可用串口信息（`list_available_ports` 返回值，对应 Rust PortInfo） */
+export interface PortInfo {
+  port_name: string
+  kind: PortKind
+  vid: number | null
+  pid: number | null
+  serial_number: string | null
+  manufacturer: string | null
+  product: string | null
+}
+
+/** `serial:ports-changed` 事件 payload */
+export interface PortsChangedPayload {
+  ports: PortInfo[]
+  added: string[]
+  removed: string[]
 }
 
 /** 串口配置整体（对应 Rust SerialConfig） */
 export interface SerialConfig {
+  /** 配置文件格式版本；前端提交时可省略 */
+  version?: number
   devices: SerialDeviceConfig[]
 }
 
+/** `serial:config-warning` 事件 payload（对应 Rust ConfigWarning） */
+export interface ConfigWarning {
+  kind: 'readFailed' | 'parseFailed' | 'restored' | 'migrated' | 'newerVersion'
+  message: string
+  /** 原文件备份路径 */
+  backup: string | null
+}
+
 /** `serial:data` 事件 payload（对应 Rust SerialDataPayload） */
 export interface SerialDataPayload {
   device_id: string
@@ -46,9 +151,41 @@ export interface SerialStatusPayload {
   device_id: string
   port: string
   connected: boolean
+  state: ConnectionState
+  /** 当前重试次数；非重连状态时为 0 */
+  attempt: number
   timestamp_ms: number
 }
 
+/** 设备运行统计（get_serial_stats 返回值 / serial:stats 事件元素） */
+export interface DeviceStats {
+  device_id: string
+  port: string
+  state: ConnectionState
+  bytes_rx: number
+  bytes_tx: number
+  frames_rx: number
+  frames_tx: number
+  framing_errors: number
+  open_errors: number
+  read_errors: number
+  reconnects: number
+  last_rx_ms: number | null
+  started_ms: number
+  /** 当前连接持续时长；未连接时为 0 */
+  uptime_ms: number
+}
+
+/** `update_serial_config` 返回值：本次配置变更涉及的设备（对应 Rust ConfigApplyResult） */
+export interface ConfigApplyResult {
+  started: string[]
+  stopped: string[]
+  restarted: string[]
+  unchanged: string[]
+  /** 旧线程未能按时退出的设备：本次未重新启动，下次应用配置时重试 */
+  stuck: string[]
+}
+
 /** 设备角色的中文标签和颜色 */
 export const ROLE_META: Record<DeviceRole, { label: string; type: 'primary' | 'success' | 'warning' | 'danger' | 'info' }> = {
   scanner: { label: '扫码枪', type: 'primary' },
@@ -68,10 +205,23 @@ export function createDefaultDevice(role: DeviceRole): SerialDeviceConfig {
     name: ROLE_META[role].label,
     role,
     port: '',
+    usb_serial: null,
     baud_rate: 9600,
     data_bits: 8,
     stop_bits: 1,
     parity: 'None',
     enabled: true,
+    reconnect: {
+      enabled: true,
+      initial_delay_ms: 500,
+      max_delay_ms: 10000,
+      multiplier: 2,
+      max_attempts: 0,
+    },
+    framing: { type: 'delimiter', delimiters: [13, 10], flush_len: 64 },
+    record: false,
+    checksum: { kind: 'none', start: 0 },
+    display: 'text',
+    command_templates: [],
   }
 }
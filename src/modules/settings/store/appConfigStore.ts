@@ -0,0 +1,99 @@
+import { defineStore } from 'pinia'
+import { invoke } from '@tauri-apps/api/core'
+import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { useLogStore } from './logStore'
+import { useSerialStore } from './serialStore'
+import type { LogConfig } from '../models/LogConfig'
+import type { SerialConfig } from '../models/SerialConfig'
+import type {
+  BundleManifest,
+  ConfigChangedPayload,
+  ConfigSection,
+  ImportMode,
+  ImportReport,
+} from '../models/AppConfig'
+import type { DiagnosticsReport } from '../models/Diagnostics'
+
+let unlistenChanged: UnlistenFn | null = null
+
+/**
+ * 统一应用配置（对应 Rust config 模块）
+ *
+ * 各分区按需加载；任一窗口保存后后端推送 `config:changed`，这里同步更新，
+ * 因此组件只需读取 `sections[name]`。
+ */
+export const useAppConfigStore = defineStore('appConfig', {
+  state: () => ({
+    sections: {} as Partial<Record<ConfigSection, unknown>>,
+    isLoading: false,
+    error: null as string | null,
+  }),
+
+  getters: {
+    section:
+      (state) =>
+      <T>(name: ConfigSection): T | undefined =>
+        state.sections[name] as T | undefined,
+  },
+
+  actions: {
+    /** 订阅 `config:changed`（重复调用只注册一次） */
+    async subscribe(): Promise<void> {
+      if (unlistenChanged) return
+      unlistenChanged = await listen<ConfigChangedPayload>('config:changed', (e) => {
+        const { section, value } = e.payload
+        this.sections[section] = value
+        // 日志 / 串口分区另有专用 store，一并同步
+        if (section === 'logging') useLogStore().logConfig = value as LogConfig
+        if (section === 'serial') useSerialStore().config = value as SerialConfig
+      })
+    },
+
+    async loadSection<T>(name: ConfigSection): Promise<T> {
+      this.isLoading = true
+      this.error = null
+      try {
+        const value = await invoke<T>('get_config_section', { section: name })
+        this.sections[name] = value
+        return value
+      } catch (err) {
+        this.error = String(err)
+        throw err
+      } finally {
+        this.isLoading = false
+      }
+    },
+
+    /** 校验并保存分区；返回后端补全缺省字段后的值 */
+    async updateSection<T>(name: ConfigSection, value: T): Promise<T> {
+      this.isLoading = true
+      this.error = null
+      try {
+        const saved = await invoke<T>('update_config_section', { section: name, value })
+        this.sections[name] = saved
+        return saved
+      } catch (err) {
+        this.error = String(err)
+        throw err
+      } finally {
+        this.isLoading = false
+      }
+    },
+
+    // ─── 配置包 ──────────────────────────────────────────────────────────────
+
+    async exportBundle(path: string): Promise<BundleManifest> {
+      return invoke<BundleManifest>('export_config_bundle', { path })
+    },
+
+    /** dryRun 为 true 时只返回差异；实际导入后各分区通过 config:changed 刷新 */
+    async importBundle(path: string, mode: ImportMode, dryRun: boolean): Promise<ImportReport> {
+      return invoke<ImportReport>('import_config_bundle', { path, mode, dryRun })
+    },
+
+    /** 导出诊断包（日志、配置、版本与设备状态，密钥已脱敏） */
+    async exportDiagnostics(path: string): Promise<DiagnosticsReport> {
+      return invoke<DiagnosticsReport>('export_diagnostics', { path })
+    },
+  },
+})
@@ -1,5 +1,5 @@
 import { defineStore } from 'pinia';
-import type { LogConfig } from '../models/LogConfig';
+import type { LogApplyResult, LogConfig } from '../models/LogConfig';
 import { invoke } from '@tauri-apps/api/core'
 export const useLogStore = defineStore('log', {
   // State
@@ -13,6 +13,11 @@ export const useLogStore = defineStore('log', {
       keep_files: 10,                   // Keep 10 archived files
       level: 'Info',                    // Default log level
       to_stdout: true,                  // Also output to stdout
+      module_levels: {},                // Per-module overrides
+      format: 'text',                   // Line format: text or json
+      max_age_days: null,               // No age limit
+      max_total_size: null,             // No total size cap
+      compress_archives: true,          // Gzip rotated archives
     } as LogConfig,
     isLoading: false,
     error: null as string | null,
@@ -35,7 +40,8 @@ export const useLogStore = defineStore('log', {
       }
     },
 
-    async updateLogConfig(config: LogConfig): Promise<void> {
+    /** 保存并立即应用；返回值说明是否仍需重启 */
+    async updateLogConfig(config: LogConfig): Promise<LogApplyResult> {
       this.isLoading = true;
       this.error = null;
 
@@ -52,7 +58,7 @@ export const useLogStore = defineStore('log', {
         this.logConfig = config;
 
         // Call the Tauri command to update log configuration
-        await invoke('update_log_config', { config });
+        return await invoke<LogApplyResult>('update_log_config', { config });
       } catch (err) {
         this.error = err instanceof Error ? err.message : 'Failed to update log configuration';
         console.error('Error updating log config:', err);
@@ -0,0 +1,64 @@
+import { defineStore } from 'pinia';
+import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
+import type { LogEntry, LogFileInfo, LogPage, LogQuery, LogSearchResult } from '../models/LogConfig';
+
+/** 实时尾随最多保留的条数 */
+const MAX_LIVE_ENTRIES = 1000;
+
+let unlistenEntry: UnlistenFn | null = null;
+
+export const useLogViewerStore = defineStore('logViewer', {
+  state: () => ({
+    logDir: '',
+    files: [] as LogFileInfo[],
+    /** 实时推送的日志（`log:entry`） */
+    liveEntries: [] as LogEntry[],
+    tailing: false,
+    isLoading: false,
+    error: null as string | null,
+  }),
+
+  actions: {
+    async loadFiles(): Promise<void> {
+      this.isLoading = true;
+      this.error = null;
+      try {
+        this.logDir = await invoke<string>('get_log_dir');
+        this.files = await invoke<LogFileInfo[]>('list_log_files');
+      } catch (err) {
+        this.error = String(err);
+      } finally {
+        this.isLoading = false;
+      }
+    },
+
+    /** offset 为从最新一条往前跳过的条数，0 即最后一页 */
+    async readPage(file: string, offset = 0, limit = 200): Promise<LogPage> {
+      return invoke<LogPage>('read_log_page', { file, offset, limit });
+    },
+
+    async search(query: LogQuery): Promise<LogSearchResult> {
+      return invoke<LogSearchResult>('search_logs', { query });
+    },
+
+    async startTail(): Promise<void> {
+      if (this.tailing) return;
+      unlistenEntry = await listen<LogEntry>('log:entry', (e) => {
+        this.liveEntries.push(e.payload);
+        if (this.liveEntries.length > MAX_LIVE_ENTRIES) {
+          this.liveEntries.splice(0, this.liveEntries.length - MAX_LIVE_ENTRIES);
+        }
+      });
+      await invoke('set_log_stream', { enabled: true });
+      this.tailing = true;
+    },
+
+    async stopTail(): Promise<void> {
+      await invoke('set_log_stream', { enabled: false });
+      unlistenEntry?.();
+      unlistenEntry = null;
+      this.tailing = false;
+    },
+  },
+});
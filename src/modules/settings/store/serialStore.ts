@@ -7,6 +7,10 @@ import type {
   SerialErrorPayload,
   SerialStatusPayload,
   DeviceStatus,
+  PortInfo,
+  PortsChangedPayload,
+  ConfigApplyResult,
+  Encoding,
 } from '../models/SerialConfig'
 
 const MAX_RECENT_DATA = 200
@@ -14,7 +18,7 @@ const MAX_RECENT_DATA = 200
 export const useSerialStore = defineStore('serial', {
   state: () => ({
     config: { devices: [] } as SerialConfig,
-    availablePorts: [] as string[],
+    availablePorts: [] as PortInfo[],
     /** 运行时设备状态（非持久化，由 serial:status / serial:error 事件驱动） */
     deviceStatuses: {} as Record<string, DeviceStatus>,
     /** 最近收到的串口数据（最多 MAX_RECENT_DATA 条） */
@@ -58,11 +62,14 @@ export const useSerialStore = defineStore('serial', {
       this.isLoading = true
       this.error = null
       try {
-        await invoke('update_serial_config', { config })
+        const result = await invoke<ConfigApplyResult>('update_serial_config', { config })
         this.config = config
-        // 重置所有状态为 idle，等待 serial:status 更新
+        // 未变化的设备保持原状态，其余重置为 idle，等待 serial:status 更新
+        const unchanged = new Set(result.unchanged)
         for (const dev of config.devices) {
-          this.deviceStatuses[dev.device_id] = 'idle'
+          if (!unchanged.has(dev.device_id)) {
+            this.deviceStatuses[dev.device_id] = 'idle'
+          }
         }
       } catch (err) {
         this.error = err instanceof Error ? err.message : String(err)
@@ -91,7 +98,7 @@ export const useSerialStore = defineStore('serial', {
 
     async fetchAvailablePorts(): Promise<void> {
       try {
-        this.availablePorts = await invoke<string[]>('list_available_ports')
+        this.availablePorts = await invoke<PortInfo[]>('list_available_ports')
       } catch (err) {
         this.availablePorts = []
         console.warn('获取串口列表失败:', err)
@@ -110,12 +117,35 @@ export const useSerialStore = defineStore('serial', {
       this.listenersActive = false
     },
 
+    async startDevice(deviceId: string): Promise<void> {
+      await invoke('start_serial_device', { deviceId })
+    },
+
+    async stopDevice(deviceId: string): Promise<void> {
+      await invoke('stop_serial_device', { deviceId })
+      this.deviceStatuses[deviceId] = 'idle'
+    },
+
+    async restartDevice(deviceId: string): Promise<void> {
+      await invoke('restart_serial_device', { deviceId })
+    },
+
     // ─── 发送指令 ──────────────────────────────────────────────────────────
 
     async sendCommand(deviceId: string, data: number[]): Promise<void> {
       await invoke('send_serial_command', { deviceId, data })
     },
 
+    /** 按编码解析并追加设备校验后发送，返回实际写出的字节 */
+    async sendPayload(deviceId: string, payload: string, encoding: Encoding = 'text'): Promise<number[]> {
+      return invoke<number[]>('send_serial_payload', { deviceId, payload, encoding })
+    },
+
+    /** 执行设备的命名指令模板，返回实际写出的字节 */
+    async executeCommand(deviceId: string, name: string, args: Record<string, number> = {}): Promise<number[]> {
+      return invoke<number[]>('execute_device_command', { deviceId, name, args })
+    },
+
     // ─── 本地设备增删改（对话框操作，不立即持久化）──────────────────────────
 
     addDevice(device: SerialDeviceConfig): void {
@@ -155,9 +185,16 @@ export const useSerialStore = defineStore('serial', {
     },
 
     onStatusChanged(payload: SerialStatusPayload): void {
-      this.deviceStatuses[payload.device_id] = payload.connected
-        ? 'connected'
-        : 'idle'
+      const map: Record<SerialStatusPayload['state'], DeviceStatus> = {
+        connected: 'connected',
+        reconnecting: 'reconnecting',
+        disconnected: 'idle',
+      }
+      this.deviceStatuses[payload.device_id] = map[payload.state]
+    },
+
+    onPortsChanged(payload: PortsChangedPayload): void {
+      this.availablePorts = payload.ports
     },
   },
 })